@@ -9,7 +9,7 @@ mod pdf;
 // Use the re-exported functions directly from the 'pdf' module
 use crate::pdf::{
     add_annotation, add_ink_annotation, delete_annotation, update_annotation_contents, replace_text_block, pdf_to_docx, add_signature_visual, delete_pages, extract_pdf_page, merge_pdfs, parse_pdf,
-    rotate_pdf, sign_pdf_pfx, split_pdf, verify_signatures, sanitize_pdf, reorder_pages, compress_pdf, decrypt_pdf, encrypt_pdf, flatten_annotations, images_to_pdf, pdf_to_images, add_watermark, start_folder_watcher, forensic_redact, compare_pdfs_text, update_metadata, batch_update_metadata, get_pdf_outline, set_pdf_outline, get_annotations, get_form_fields, set_form_fields, create_form_fields, pdf_to_text, pdf_to_text_string, write_text_file, markdown_to_pdf, generate_briefing,
+    rotate_pdf, sign_pdf_pfx, split_pdf, verify_signatures, sanitize_pdf, reorder_pages, compress_pdf, decrypt_pdf, encrypt_pdf, flatten_annotations, images_to_pdf, pdf_to_images, add_watermark, start_folder_watcher, forensic_redact, compare_pdfs_text, update_metadata, batch_update_metadata, get_pdf_outline, set_pdf_outline, get_annotations, get_form_fields, set_form_fields, create_form_fields, pdf_to_text, pdf_to_text_string, write_text_file, markdown_to_pdf, generate_briefing, export_as_xobject, batch_watermark, content_hash, list_links, rewrite_uris, check_font_embedding, embed_standard_fonts, crop_to_common_box, diagnose_text, add_tounicode, merge_duplex, structure_tree, set_annotation_flags, concatenate_pages_vertically, get_view_settings, cleanup_scan, find_empty_pages, transform_content, generate_index_sheet, collapse_revisions, is_tagged, count_struct_elements, untag_pdf, split_by_size_respecting_bookmarks, match_by_id, rgb_to_cmyk, split_on_text, prepend_cover, find_heavy_pages, flatten_heavy_pages, list_separations, flatten_separations, page_report, extract_range_complete, get_rotations, set_global_rotation, check_pdfa, downgrade_version, insert_blank_page, pad_to_multiple, diff_pages, strip_icc_profiles, tile_overview, detect_source_app, merge_pages_matching, detect_text_direction, set_text_direction, get_page_resources, prune_page_resources, split_with_overlap, estimate_print_cost, set_pdf_metadata, list_destinations, add_goto_link, get_page_count, generate_toc_page, get_page_info, split_by_plan, extract_pdf_range, get_print_boxes, keep_pages, set_print_boxes, remove_object, validate_content_streams, add_text_watermark, clear_broken_content, add_page_numbers, ink_coverage, merge_from_list, split_into_n, split_into_chunks, find_duplicate_annotations, stamp_timestamp, find_external_references, extract_images, is_encrypted, embed_external_references, secure_export, cancel_operation, reset_cancellation, append_pdf, extract_text, merge_interleaved, crop_pages, insert_blank_pages, render_thumbnails, preview_rotation, linearize_pdf,
 };
 
 fn main() {
@@ -18,6 +18,7 @@ fn main() {
         .plugin(tauri_plugin_dialog::init()) // Needs the dialog plugin for v2
         .plugin(tauri_plugin_os::init()) // Needs the os plugin for v2 (if you use more os features)
         .plugin(tauri_plugin_opener::init()) // Ensure opener is initialized
+        .manage(pdf::CancellationToken::default()) // Shared by cancel_operation and long-running commands
         .setup(|app| {
             // It's often better to handle potential errors rather than unwrap
             if let Some(_window) = app.get_webview_window("main") {
@@ -66,6 +67,94 @@ fn main() {
             pdf_to_text,
             pdf_to_text_string,
             write_text_file,
+            export_as_xobject,
+            batch_watermark,
+            content_hash,
+            list_links,
+            rewrite_uris,
+            check_font_embedding,
+            embed_standard_fonts,
+            crop_to_common_box,
+            diagnose_text,
+            add_tounicode,
+            merge_duplex,
+            structure_tree,
+            set_annotation_flags,
+            concatenate_pages_vertically,
+            get_view_settings,
+            cleanup_scan,
+            find_empty_pages,
+            transform_content,
+            generate_index_sheet,
+            collapse_revisions,
+            is_tagged,
+            count_struct_elements,
+            untag_pdf,
+            split_by_size_respecting_bookmarks,
+            match_by_id,
+            rgb_to_cmyk,
+            split_on_text,
+            prepend_cover,
+            find_heavy_pages,
+            flatten_heavy_pages,
+            list_separations,
+            flatten_separations,
+            page_report,
+            extract_range_complete,
+            get_rotations,
+            set_global_rotation,
+            check_pdfa,
+            downgrade_version,
+            insert_blank_page,
+            pad_to_multiple,
+            diff_pages,
+            strip_icc_profiles,
+            tile_overview,
+            detect_source_app,
+            merge_pages_matching,
+            detect_text_direction,
+            set_text_direction,
+            get_page_resources,
+            prune_page_resources,
+            split_with_overlap,
+            estimate_print_cost,
+            set_pdf_metadata,
+            list_destinations,
+            add_goto_link,
+            get_page_count,
+            generate_toc_page,
+            get_page_info,
+            split_by_plan,
+            extract_pdf_range,
+            get_print_boxes,
+            keep_pages,
+            set_print_boxes,
+            remove_object,
+            validate_content_streams,
+            add_text_watermark,
+            clear_broken_content,
+            add_page_numbers,
+            ink_coverage,
+            merge_from_list,
+            split_into_n,
+            split_into_chunks,
+            find_duplicate_annotations,
+            stamp_timestamp,
+            find_external_references,
+            extract_images,
+            is_encrypted,
+            embed_external_references,
+            secure_export,
+            cancel_operation,
+            reset_cancellation,
+            append_pdf,
+            extract_text,
+            merge_interleaved,
+            crop_pages,
+            insert_blank_pages,
+            render_thumbnails,
+            preview_rotation,
+            linearize_pdf,
 
 
             commands::open_file_dialog,
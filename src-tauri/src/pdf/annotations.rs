@@ -1,4 +1,6 @@
-use lopdf::{dictionary, Document, Object};
+use crate::pdf::utils::{load_document, page_geometry, rebuild_clean, rect_from_object};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -24,6 +26,7 @@ fn color_array(color: Option<[f32; 3]>) -> Object {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_annotation(
     path: &str,
     page: u32,
@@ -32,6 +35,7 @@ pub fn add_annotation(
     contents: Option<String>,
     color: Option<[f32; 3]>,
     output_path: &str,
+    clean: Option<bool>,
 ) -> Result<(), String> {
     if page == 0 {
         return Err("Page number must be 1-based.".to_string());
@@ -256,6 +260,10 @@ pub fn add_annotation(
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path)
         .map_err(|e| format!("Failed to save annotated PDF to '{}': {}", output_path, e))?;
 
@@ -270,6 +278,7 @@ pub fn add_ink_annotation(
     color: Option<[f32; 3]>,
     width: Option<f32>,
     output_path: &str,
+    clean: Option<bool>,
 ) -> Result<(), String> {
     if page == 0 {
         return Err("Page number must be 1-based.".to_string());
@@ -323,12 +332,21 @@ pub fn add_ink_annotation(
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_annotation(path: &str, annot_id: (u32, u16), output_path: &str) -> Result<(), String> {
+pub fn delete_annotation(
+    path: &str,
+    annot_id: (u32, u16),
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     
     // 1. Remove from all page Annots arrays
@@ -351,12 +369,22 @@ pub fn delete_annotation(path: &str, annot_id: (u32, u16), output_path: &str) ->
     // 2. Remove the object itself
     doc.objects.remove(&annot_id);
     
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn update_annotation_contents(path: &str, annot_id: (u32, u16), new_contents: String, output_path: &str) -> Result<(), String> {
+pub fn update_annotation_contents(
+    path: &str,
+    annot_id: (u32, u16),
+    new_contents: String,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     
     if let Ok(Object::Dictionary(mut annot)) = doc.get_object_mut(annot_id).cloned() {
@@ -366,10 +394,627 @@ pub fn update_annotation_contents(path: &str, annot_id: (u32, u16), new_contents
         return Err("Annotation not found or not a dictionary".to_string());
     }
     
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
 
+#[derive(serde::Serialize)]
+pub struct LinkInfo {
+    pub page: u32,
+    pub kind: String,
+    pub target: String,
+}
+
+fn pdf_text(object: &Object) -> Option<String> {
+    match object {
+        Object::String(bytes, _) => Some(String::from_utf8_lossy(bytes).to_string()),
+        Object::Name(bytes) => Some(String::from_utf8_lossy(bytes).to_string()),
+        _ => None,
+    }
+}
+
+/// Look up a key in a `/Names` name tree, descending through `/Kids`.
+fn lookup_name_tree<'a>(
+    doc: &'a Document,
+    node: &'a Dictionary,
+    key: &[u8],
+    depth: u32,
+) -> Option<&'a Object> {
+    if depth > 32 {
+        return None;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(|o| o.as_array()) {
+        for pair in names.chunks(2) {
+            if let [Object::String(name, _), value] = pair {
+                if name.as_slice() == key {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(|o| o.as_array()) {
+        for kid in kids {
+            let kid_dict = kid
+                .as_reference()
+                .and_then(|id| doc.get_dictionary(id))
+                .ok();
+            if let Some(found) = kid_dict.and_then(|d| lookup_name_tree(doc, d, key, depth + 1)) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Find the destination array behind a named destination, checking both the
+/// `/Names /Dests` name tree and the older catalog `/Dests` dictionary.
+fn lookup_named_destination<'a>(doc: &'a Document, name: &[u8]) -> Option<&'a Object> {
+    let catalog = doc.catalog().ok()?;
+
+    let from_tree = catalog
+        .get_deref(b"Names", doc)
+        .and_then(|o| o.as_dict())
+        .and_then(|names| names.get_deref(b"Dests", doc))
+        .and_then(|o| o.as_dict())
+        .ok()
+        .and_then(|tree| lookup_name_tree(doc, tree, name, 0));
+
+    let found = from_tree.or_else(|| {
+        catalog
+            .get_deref(b"Dests", doc)
+            .and_then(|o| o.as_dict())
+            .and_then(|dests| dests.get(name))
+            .ok()
+    })?;
+
+    // Entries may be the array itself or a dictionary wrapping it in /D
+    let resolved = doc.dereference(found).map(|(_, o)| o).ok()?;
+    match resolved {
+        Object::Dictionary(d) => d.get_deref(b"D", doc).ok(),
+        other => Some(other),
+    }
+}
+
+/// Resolve an explicit or named destination to a 1-based page number.
+pub(crate) fn resolve_destination_page(doc: &Document, dest: &Object) -> Option<u32> {
+    let dest = doc.dereference(dest).map(|(_, o)| o).ok()?;
+    let explicit = match dest {
+        Object::Array(_) => dest,
+        Object::String(name, _) | Object::Name(name) => lookup_named_destination(doc, name)?,
+        _ => return None,
+    };
+    let page_ref = explicit.as_array().ok()?.first()?.as_reference().ok()?;
+    doc.get_pages()
+        .into_iter()
+        .find(|(_, id)| *id == page_ref)
+        .map(|(num, _)| num)
+}
+
+#[tauri::command]
+pub fn list_links(path: &str) -> Result<Vec<LinkInfo>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut links = Vec::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let annots = match doc.get_page_annotations(page_id) {
+            Ok(a) => a,
+            Err(_) => continue,
+        };
+        for annot in annots {
+            let is_link = annot
+                .get(b"Subtype")
+                .and_then(|o| o.as_name())
+                .map(|n| n == b"Link")
+                .unwrap_or(false);
+            if !is_link {
+                continue;
+            }
+
+            let (kind, target) = if let Ok(dest) = annot.get(b"Dest") {
+                ("goto".to_string(), describe_page_target(&doc, dest))
+            } else if let Ok(action) = annot.get_deref(b"A", &doc).and_then(|o| o.as_dict()) {
+                let action_type = action
+                    .get(b"S")
+                    .and_then(|o| o.as_name_str())
+                    .unwrap_or("Unknown");
+                match action_type {
+                    "URI" => (
+                        "uri".to_string(),
+                        action
+                            .get_deref(b"URI", &doc)
+                            .ok()
+                            .and_then(pdf_text)
+                            .unwrap_or_default(),
+                    ),
+                    "GoTo" => (
+                        "goto".to_string(),
+                        action
+                            .get(b"D")
+                            .map(|d| describe_page_target(&doc, d))
+                            .unwrap_or_else(|_| "unresolved".to_string()),
+                    ),
+                    other => (
+                        other.to_lowercase(),
+                        action
+                            .get_deref(b"F", &doc)
+                            .ok()
+                            .and_then(|f| match f {
+                                Object::Dictionary(spec) => spec.get(b"F").ok().and_then(pdf_text),
+                                other => pdf_text(other),
+                            })
+                            .unwrap_or_default(),
+                    ),
+                }
+            } else {
+                continue;
+            };
+
+            links.push(LinkInfo {
+                page: page_num,
+                kind,
+                target,
+            });
+        }
+    }
+
+    Ok(links)
+}
+
+fn describe_page_target(doc: &Document, dest: &Object) -> String {
+    resolve_destination_page(doc, dest)
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "unresolved".to_string())
+}
+
+/// Where `rewrite_uris` found a URI action to rewrite.
+enum UriActionSlot {
+    /// An action object of its own, referenced from an annotation's /A
+    Object(ObjectId),
+    /// The /A dictionary of an annotation object
+    Annotation(ObjectId),
+    /// The /A dictionary of an annotation written inline in /Annots, by its index in
+    /// the array held by `holder`: the page itself, or a separate array object.
+    InlineAnnotation {
+        holder: ObjectId,
+        in_page: bool,
+        index: usize,
+    },
+}
+
+/// Replace `find` with `replace` in a URI action's /URI. Returns whether it changed.
+fn rewrite_uri_action(action: &mut Dictionary, find: &str, replace: &str) -> bool {
+    let is_uri = action
+        .get(b"S")
+        .and_then(|s| s.as_name())
+        .map(|n| n == b"URI")
+        .unwrap_or(false);
+    if !is_uri {
+        return false;
+    }
+    let current = match action.get(b"URI") {
+        Ok(Object::String(bytes, _)) => String::from_utf8_lossy(bytes).to_string(),
+        _ => return false,
+    };
+    if !current.contains(find) {
+        return false;
+    }
+    action.set(
+        "URI",
+        Object::string_literal(current.replace(find, replace)),
+    );
+    true
+}
+
+#[tauri::command]
+pub fn rewrite_uris(
+    path: &str,
+    find: &str,
+    replace: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<usize, String> {
+    if find.is_empty() {
+        return Err("The text to find cannot be empty.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    // Collect where the URI actions live first, then rewrite them. An action object
+    // shared by several links, or an annotation listed on several pages, is visited
+    // once so it is rewritten and counted once.
+    let mut visited: HashSet<ObjectId> = HashSet::new();
+    let mut slots = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        let (holder, in_page, annots) =
+            match doc.get_dictionary(page_id).and_then(|p| p.get(b"Annots")) {
+                Ok(Object::Reference(id)) => match doc.get_object(*id).and_then(|a| a.as_array()) {
+                    Ok(annots) if visited.insert(*id) => (*id, false, annots),
+                    _ => continue,
+                },
+                Ok(Object::Array(annots)) => (page_id, true, annots),
+                _ => continue,
+            };
+        for (index, entry) in annots.iter().enumerate() {
+            let (annot, slot) = match entry {
+                Object::Reference(annot_id) if visited.insert(*annot_id) => {
+                    match doc.get_dictionary(*annot_id) {
+                        Ok(annot) => (annot, UriActionSlot::Annotation(*annot_id)),
+                        Err(_) => continue,
+                    }
+                }
+                Object::Dictionary(annot) => (
+                    annot,
+                    UriActionSlot::InlineAnnotation {
+                        holder,
+                        in_page,
+                        index,
+                    },
+                ),
+                _ => continue,
+            };
+            match annot.get(b"A") {
+                Ok(Object::Reference(action_id)) if visited.insert(*action_id) => {
+                    slots.push(UriActionSlot::Object(*action_id))
+                }
+                Ok(Object::Dictionary(_)) => slots.push(slot),
+                _ => {}
+            }
+        }
+    }
+
+    let mut changed = 0;
+    for slot in slots {
+        let action = match slot {
+            UriActionSlot::Object(id) => doc.get_dictionary_mut(id).ok(),
+            UriActionSlot::Annotation(id) => doc
+                .get_dictionary_mut(id)
+                .and_then(|annot| annot.get_mut(b"A"))
+                .and_then(|a| a.as_dict_mut())
+                .ok(),
+            UriActionSlot::InlineAnnotation {
+                holder,
+                in_page,
+                index,
+            } => {
+                let annots = if in_page {
+                    doc.get_dictionary_mut(holder)
+                        .and_then(|page| page.get_mut(b"Annots"))
+                } else {
+                    doc.get_object_mut(holder)
+                };
+                annots
+                    .and_then(|a| a.as_array_mut())
+                    .ok()
+                    .and_then(|a| a.get_mut(index))
+                    .and_then(|annot| annot.as_dict_mut().ok())
+                    .and_then(|annot| annot.get_mut(b"A").and_then(|a| a.as_dict_mut()).ok())
+            }
+        };
+        if action.is_some_and(|action| rewrite_uri_action(action, find, replace)) {
+            changed += 1;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(changed)
+}
+
+// Annotation flag bits (PDF 32000-1, table 165)
+const ANNOT_FLAG_HIDDEN: i64 = 1 << 1;
+const ANNOT_FLAG_NO_VIEW: i64 = 1 << 5;
+
+/// Hide (or unhide) every annotation on a page by toggling its Hidden and NoView
+/// flags, leaving the annotations themselves in place.
+#[tauri::command]
+pub fn set_annotation_flags(
+    path: &str,
+    page: u32,
+    hide: bool,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if page == 0 {
+        return Err("Page number must be 1-based.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let page_id = *pages.get(&page).ok_or_else(|| {
+        format!(
+            "Page number {} not found in document ({} pages).",
+            page,
+            pages.len()
+        )
+    })?;
+
+    let mask = ANNOT_FLAG_HIDDEN | ANNOT_FLAG_NO_VIEW;
+    let update = |annot: &mut Dictionary| {
+        let current = annot.get(b"F").and_then(|f| f.as_i64()).unwrap_or(0);
+        let flags = if hide {
+            current | mask
+        } else {
+            current & !mask
+        };
+        annot.set("F", Object::Integer(flags));
+    };
+
+    // /Annots may itself be a reference, and entries may be inline dictionaries
+    let annots_ref = match doc.get_dictionary(page_id).and_then(|p| p.get(b"Annots")) {
+        Ok(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+    let annots = match annots_ref {
+        Some(id) => doc.get_object_mut(id),
+        None => doc
+            .get_dictionary_mut(page_id)
+            .and_then(|p| p.get_mut(b"Annots")),
+    };
+    let mut referenced = Vec::new();
+    if let Ok(Object::Array(items)) = annots {
+        for item in items.iter_mut() {
+            match item {
+                Object::Reference(id) => referenced.push(*id),
+                Object::Dictionary(annot) => update(annot),
+                _ => {}
+            }
+        }
+    }
+    for annot_id in referenced {
+        if let Ok(annot) = doc.get_dictionary_mut(annot_id) {
+            update(annot);
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// An invisible Link annotation over `rect` with a GoTo action to the top-left of
+/// `target_id`'s visible box, keeping the current zoom (`/XYZ left top null`).
+pub(crate) fn goto_link_annotation(
+    doc: &Document,
+    rect: [f64; 4],
+    target_id: ObjectId,
+) -> Dictionary {
+    let (target_bounds, _) = page_geometry(doc, target_id);
+    let destination = vec![
+        Object::Reference(target_id),
+        "XYZ".into(),
+        target_bounds[0].into(),
+        target_bounds[3].into(),
+        Object::Null,
+    ];
+    dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Link",
+        "Rect" => rect.iter().map(|&v| v.into()).collect::<Vec<Object>>(),
+        "Border" => vec![0.into(), 0.into(), 0.into()],
+        "F" => 4_i64,
+        "A" => dictionary! {
+            "S" => "GoTo",
+            "D" => destination,
+        },
+    }
+}
+
+/// Add an invisible Link annotation on `page` covering `rect` that jumps to the top
+/// of `target_page` (a `/XYZ` destination keeping the current zoom), e.g. to make a
+/// table of contents clickable. The rect must lie within the page's visible box.
+#[tauri::command]
+pub fn add_goto_link(
+    path: &str,
+    page: u32,
+    rect: [f64; 4],
+    target_page: u32,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let page_id_for = |number: u32| {
+        pages.get(&number).copied().ok_or_else(|| {
+            format!(
+                "Page number {} not found in document '{}' (which has {} pages).",
+                number,
+                path,
+                pages.len()
+            )
+        })
+    };
+    let page_id = page_id_for(page)?;
+    let target_id = page_id_for(target_page)?;
+
+    let [x1, y1, x2, y2] = rect;
+    let rect = [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)];
+    let (bounds, _) = page_geometry(&doc, page_id);
+    let inside = rect[0] >= bounds[0]
+        && rect[1] >= bounds[1]
+        && rect[2] <= bounds[2]
+        && rect[3] <= bounds[3];
+    if !inside || rect[0] == rect[2] || rect[1] == rect[3] {
+        return Err(format!(
+            "Link rectangle {:?} must be non-empty and lie within page {} ({:?}).",
+            rect, page, bounds
+        ));
+    }
+
+    let link = goto_link_annotation(&doc, rect, target_id);
+    let link_id = doc.add_object(link);
+
+    let page_dict = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| format!("Failed to fetch page object {:?}: {}", page_id, e))?;
+    match page_dict.get_mut(b"Annots") {
+        Ok(Object::Array(annots)) => annots.push(Object::Reference(link_id)),
+        Ok(_) => return Err("Existing Annots entry is not an array".to_string()),
+        Err(_) => page_dict.set("Annots", vec![Object::Reference(link_id)]),
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save linked PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Identity of an annotation for duplicate detection: subtype, rect (to 1/100 pt)
+/// and raw contents.
+type AnnotationKey = (Vec<u8>, Option<[i64; 4]>, Vec<u8>);
+
+fn annotation_key(annot: &Dictionary) -> AnnotationKey {
+    let subtype = annot
+        .get(b"Subtype")
+        .and_then(|s| s.as_name())
+        .map(|s| s.to_vec())
+        .unwrap_or_default();
+    let rect = annot
+        .get(b"Rect")
+        .ok()
+        .and_then(rect_from_object)
+        .map(|r| r.map(|v| (v * 100.0).round() as i64));
+    let contents = annot
+        .get(b"Contents")
+        .and_then(|c| c.as_str())
+        .map(|c| c.to_vec())
+        .unwrap_or_default();
+    (subtype, rect, contents)
+}
+
+/// Per page, how many annotations are redundant copies of another on the same page
+/// (same subtype, rect and contents), as left behind by repeated edits. Pages without
+/// duplicates are not listed.
+#[tauri::command]
+pub fn find_duplicate_annotations(path: &str) -> Result<Vec<(u32, usize)>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut report = Vec::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let Ok(annots) = doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get_deref(b"Annots", &doc))
+            .and_then(|a| a.as_array())
+        else {
+            continue;
+        };
+        let mut seen: HashMap<AnnotationKey, usize> = HashMap::new();
+        for annot in annots {
+            if let Ok(annot) = doc.dereference(annot).and_then(|(_, a)| a.as_dict()) {
+                *seen.entry(annotation_key(annot)).or_default() += 1;
+            }
+        }
+        let duplicates: usize = seen.values().map(|count| count - 1).sum();
+        if duplicates > 0 {
+            report.push((page_num, duplicates));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Drop every annotation that repeats an earlier one on the same page (same subtype,
+/// rect and contents, as reported by `find_duplicate_annotations`), keeping the first.
+/// Returns how many were removed.
+pub(crate) fn remove_duplicate_annotations(doc: &mut Document) -> usize {
+    let mut removed = 0;
+    for page_id in doc.get_pages().into_values() {
+        let Ok(annots) = doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get_deref(b"Annots", doc))
+            .and_then(|a| a.as_array())
+        else {
+            continue;
+        };
+        let mut seen: HashSet<AnnotationKey> = HashSet::new();
+        let keep: Vec<bool> = annots
+            .iter()
+            .map(
+                |annot| match doc.dereference(annot).and_then(|(_, a)| a.as_dict()) {
+                    Ok(annot) => seen.insert(annotation_key(annot)),
+                    Err(_) => true,
+                },
+            )
+            .collect();
+        let duplicates = keep.iter().filter(|k| !**k).count();
+        if duplicates == 0 {
+            continue;
+        }
+
+        let shared_array = doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get(b"Annots"))
+            .and_then(|a| a.as_reference())
+            .ok();
+        let annots = match shared_array {
+            Some(id) => doc.get_object_mut(id),
+            None => doc
+                .get_dictionary_mut(page_id)
+                .and_then(|p| p.get_mut(b"Annots")),
+        }
+        .and_then(|a| a.as_array_mut());
+        if let Ok(annots) = annots {
+            let mut keep = keep.into_iter();
+            annots.retain(|_| keep.next().unwrap_or(true));
+            removed += duplicates;
+        }
+    }
+    removed
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -395,6 +1040,7 @@ mod tests {
             Some("Test highlight".to_string()),
             Some([1.0, 0.9, 0.3]),
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_ok(), "add_annotation failed: {:?}", result.err());
 
@@ -428,6 +1074,7 @@ mod tests {
             None,
             None,
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_ok());
         crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
@@ -452,6 +1099,7 @@ mod tests {
             Some([1.0, 0.0, 0.0]),
             Some(3.0),
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_ok());
         crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
@@ -476,6 +1124,7 @@ mod tests {
             None,
             None,
             output.to_str().unwrap(),
+            None,
         );
         assert!(res1.is_ok(), "underline failed: {:?}", res1.err());
 
@@ -488,6 +1137,7 @@ mod tests {
             Some("strike".to_string()),
             Some([1.0, 0.2, 0.2]),
             output.to_str().unwrap(),
+            None,
         );
         assert!(res2.is_ok(), "strikeout failed: {:?}", res2.err());
     }
@@ -511,6 +1161,7 @@ mod tests {
             Some("Note body".to_string()),
             Some([0.1, 0.6, 1.0]),
             output.to_str().unwrap(),
+            None,
         );
         assert!(res.is_ok(), "note failed: {:?}", res.err());
     }
@@ -534,7 +1185,342 @@ mod tests {
             None,
             None,
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_err());
     }
+
+    fn push_annotation(doc: &mut Document, page_id: lopdf::ObjectId, annot: Dictionary) {
+        let annot_id = doc.add_object(Object::Dictionary(annot));
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        match page.get_mut(b"Annots") {
+            Ok(Object::Array(arr)) => arr.push(Object::Reference(annot_id)),
+            _ => page.set("Annots", Object::Array(vec![Object::Reference(annot_id)])),
+        }
+    }
+
+    #[test]
+    fn test_list_links_uri_and_internal() {
+        let (test_dir, output_dir) = crate::pdf::test_utils::setup_unique_paths("list_links");
+        let input = test_dir.join("links.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Links").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        push_annotation(
+            &mut doc,
+            pages[&1],
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Link",
+                "Rect" => vec![10.into(), 10.into(), 100.into(), 30.into()],
+                "A" => dictionary! {
+                    "S" => "URI",
+                    "URI" => Object::string_literal("https://example.com/docs"),
+                },
+            },
+        );
+        push_annotation(
+            &mut doc,
+            pages[&2],
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Link",
+                "Rect" => vec![10.into(), 10.into(), 100.into(), 30.into()],
+                "Dest" => vec![Object::Reference(pages[&3]), "Fit".into()],
+            },
+        );
+        doc.save(&input).unwrap();
+
+        let links = list_links(input.to_str().unwrap()).unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].page, 1);
+        assert_eq!(links[0].kind, "uri");
+        assert_eq!(links[0].target, "https://example.com/docs");
+        assert_eq!(links[1].page, 2);
+        assert_eq!(links[1].kind, "goto");
+        assert_eq!(links[1].target, "3");
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_rewrite_uris_moves_domain() {
+        let (test_dir, output_dir) = crate::pdf::test_utils::setup_unique_paths("rewrite_uris");
+        let input = test_dir.join("links.pdf");
+        let output = output_dir.join("rewritten.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Links").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        for (page, url) in [(1, "https://old.example.com/a"), (2, "https://old.example.com/b")] {
+            push_annotation(
+                &mut doc,
+                pages[&page],
+                dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => vec![10.into(), 10.into(), 100.into(), 30.into()],
+                    "A" => dictionary! {
+                        "S" => "URI",
+                        "URI" => Object::string_literal(url),
+                    },
+                },
+            );
+        }
+        doc.save(&input).unwrap();
+
+        let changed = rewrite_uris(
+            input.to_str().unwrap(),
+            "old.example.com",
+            "new.example.org",
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(changed, 2);
+
+        let targets: Vec<String> = list_links(output.to_str().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target)
+            .collect();
+        assert_eq!(
+            targets,
+            vec!["https://new.example.org/a", "https://new.example.org/b"]
+        );
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_rewrite_uris_shared_action_and_inline_annotation() {
+        let (test_dir, output_dir) =
+            crate::pdf::test_utils::setup_unique_paths("rewrite_uris_shared");
+        let input = test_dir.join("links.pdf");
+        let output = output_dir.join("rewritten.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Links").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let shared_action = doc.add_object(dictionary! {
+            "S" => "URI",
+            "URI" => Object::string_literal("https://old.example.com/shared"),
+        });
+        for page in [1, 2] {
+            push_annotation(
+                &mut doc,
+                pages[&page],
+                dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => vec![10.into(), 10.into(), 100.into(), 30.into()],
+                    "A" => Object::Reference(shared_action),
+                },
+            );
+        }
+        // An annotation written directly into the /Annots array
+        let inline = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![10.into(), 40.into(), 100.into(), 60.into()],
+            "A" => dictionary! {
+                "S" => "URI",
+                "URI" => Object::string_literal("https://old.example.com/inline"),
+            },
+        };
+        doc.get_dictionary_mut(pages[&2])
+            .unwrap()
+            .get_mut(b"Annots")
+            .and_then(|a| a.as_array_mut())
+            .unwrap()
+            .push(Object::Dictionary(inline));
+        doc.save(&input).unwrap();
+
+        let changed = rewrite_uris(
+            input.to_str().unwrap(),
+            "old.example.com",
+            "old.example.com/archive",
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(changed, 2);
+
+        let targets: Vec<String> = list_links(output.to_str().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|l| l.target)
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                "https://old.example.com/archive/shared",
+                "https://old.example.com/archive/shared",
+            ]
+        );
+        let rewritten = Document::load(&output).unwrap();
+        let page = rewritten.get_dictionary(rewritten.get_pages()[&2]).unwrap();
+        let inline_uri = page
+            .get(b"Annots")
+            .and_then(|a| a.as_array())
+            .and_then(|a| a[1].as_dict())
+            .and_then(|annot| annot.get(b"A"))
+            .and_then(|a| a.as_dict())
+            .and_then(|a| a.get(b"URI"))
+            .and_then(|uri| uri.as_str())
+            .unwrap();
+        assert_eq!(inline_uri, b"https://old.example.com/archive/inline");
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_set_annotation_flags_hide_and_show() {
+        let (test_dir, output_dir) = crate::pdf::test_utils::setup_unique_paths("annot_flags");
+        let input = test_dir.join("annots.pdf");
+        let hidden = output_dir.join("hidden.pdf");
+        let shown = output_dir.join("shown.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Flags").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        push_annotation(
+            &mut doc,
+            page_id,
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Text",
+                "Rect" => vec![10.into(), 10.into(), 30.into(), 30.into()],
+                // Print flag must survive the toggling
+                "F" => 4,
+            },
+        );
+        doc.save(&input).unwrap();
+
+        let flags_of = |path: &std::path::Path| -> i64 {
+            let doc = Document::load(path).unwrap();
+            let page_id = doc.get_pages()[&1];
+            let annots = doc
+                .get_dictionary(page_id)
+                .unwrap()
+                .get(b"Annots")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .clone();
+            let annot = doc
+                .get_dictionary(annots[0].as_reference().unwrap())
+                .unwrap();
+            annot.get(b"F").unwrap().as_i64().unwrap()
+        };
+
+        set_annotation_flags(
+            input.to_str().unwrap(),
+            1,
+            true,
+            hidden.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let flags = flags_of(&hidden);
+        assert_eq!(flags & ANNOT_FLAG_HIDDEN, ANNOT_FLAG_HIDDEN);
+        assert_eq!(flags & ANNOT_FLAG_NO_VIEW, ANNOT_FLAG_NO_VIEW);
+        assert_eq!(flags & 4, 4);
+
+        set_annotation_flags(
+            hidden.to_str().unwrap(),
+            1,
+            false,
+            shown.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(flags_of(&shown), 4);
+
+        let invalid = set_annotation_flags(
+            input.to_str().unwrap(),
+            5,
+            true,
+            shown.to_str().unwrap(),
+            None,
+        );
+        assert!(invalid.unwrap_err().contains("Page number 5 not found"));
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_add_goto_link_to_page_three() {
+        let (test_dir, output_dir) = crate::pdf::test_utils::setup_unique_paths("goto_link");
+        let input = test_dir.join("toc.pdf");
+        let output = output_dir.join("linked.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Toc").unwrap();
+
+        add_goto_link(
+            input.to_str().unwrap(),
+            1,
+            [100.0, 690.0, 300.0, 710.0],
+            3,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let links = list_links(output.to_str().unwrap()).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].page, 1);
+        assert_eq!(links[0].kind, "goto");
+        assert_eq!(links[0].target, "3");
+
+        let outside = add_goto_link(
+            input.to_str().unwrap(),
+            1,
+            [500.0, 700.0, 700.0, 720.0],
+            3,
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(outside.unwrap_err().contains("within page 1"));
+        let bad_target = add_goto_link(
+            input.to_str().unwrap(),
+            1,
+            [100.0, 690.0, 300.0, 710.0],
+            4,
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(bad_target.unwrap_err().contains("Page number 4 not found"));
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_find_duplicate_annotations_reports_repeated_link() {
+        let (test_dir, output_dir) = crate::pdf::test_utils::setup_unique_paths("dup_annots");
+        let input = test_dir.join("edited.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Dup").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let link = goto_link_annotation(&doc, [100.0, 690.0, 300.0, 710.0], pages[&3]);
+        let first = doc.add_object(link.clone());
+        let second = doc.add_object(link);
+        doc.get_dictionary_mut(pages[&1]).unwrap().set(
+            "Annots",
+            vec![Object::Reference(first), Object::Reference(second)],
+        );
+        let other = goto_link_annotation(&doc, [100.0, 650.0, 300.0, 670.0], pages[&3]);
+        let single = doc.add_object(other);
+        doc.get_dictionary_mut(pages[&2]).unwrap().set(
+            "Annots",
+            vec![Object::Reference(first), Object::Reference(single)],
+        );
+        doc.save(&input).unwrap();
+
+        let report = find_duplicate_annotations(input.to_str().unwrap()).unwrap();
+        assert_eq!(report, vec![(1, 1)]);
+
+        crate::pdf::test_utils::teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
@@ -0,0 +1,252 @@
+use crate::pdf::cancel::CancellationToken;
+use crate::pdf::merger::merge_pdfs_cancellable;
+use crate::pdf::watermarker::add_text_watermark;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn list_pdfs_in_dir(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+
+    let mut pdfs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .map(|ext| ext.eq_ignore_ascii_case("pdf"))
+                    .unwrap_or(false)
+        })
+        .collect();
+    pdfs.sort();
+    Ok(pdfs)
+}
+
+/// Watermark every PDF in `input_dir` with `add_text_watermark`, writing same-named
+/// files into `output_dir`.
+/// A failing file does not stop the batch: the returned summary holds the output path
+/// for each success and an `"Error: <file>: <reason>"` line for each failure.
+#[tauri::command]
+pub fn batch_watermark(
+    input_dir: &str,
+    text: &str,
+    opacity: f32,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let input = Path::new(input_dir);
+    if !input.exists() {
+        return Err(format!("Input directory not found: {}", input_dir));
+    }
+    if !input.is_dir() {
+        return Err(format!("Input path is not a directory: {}", input_dir));
+    }
+    if text.trim().is_empty() {
+        return Err("Watermark text cannot be empty.".to_string());
+    }
+
+    let output = Path::new(output_dir);
+    if !output.exists() {
+        fs::create_dir_all(output)
+            .map_err(|e| format!("Failed to create output directory '{}': {}", output_dir, e))?;
+    }
+
+    let mut summary = Vec::new();
+    for pdf in list_pdfs_in_dir(input)? {
+        let file_name = match pdf.file_name() {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        let out_path = output.join(&file_name);
+        let (in_str, out_str) = match (pdf.to_str(), out_path.to_str()) {
+            (Some(i), Some(o)) => (i, o),
+            _ => {
+                summary.push(format!(
+                    "Error: {}: path is not valid UTF-8",
+                    file_name.to_string_lossy()
+                ));
+                continue;
+            }
+        };
+
+        match add_text_watermark(in_str, text, opacity, out_str, clean) {
+            Ok(()) => summary.push(out_str.to_string()),
+            Err(e) => summary.push(format!("Error: {}: {}", file_name.to_string_lossy(), e)),
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Merge the PDFs named in `list_file`, one path per line, in listed order. Blank lines
+/// and lines starting with `#` are ignored; relative paths are resolved against the
+/// list file's directory. Returns the resolved paths in the order they were merged.
+#[tauri::command]
+pub fn merge_from_list(
+    list_file: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let list_path = Path::new(list_file);
+    if !list_path.is_file() {
+        return Err(format!("List file not found: {}", list_file));
+    }
+    let listing = fs::read_to_string(list_path)
+        .map_err(|e| format!("Failed to read list file '{}': {}", list_file, e))?;
+    let base_dir = list_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut paths = Vec::new();
+    for (index, line) in listing.lines().enumerate() {
+        let entry = line.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let resolved = base_dir.join(entry);
+        if !resolved.is_file() {
+            return Err(format!(
+                "Input file not found: {} (line {} of '{}')",
+                resolved.display(),
+                index + 1,
+                list_file
+            ));
+        }
+        let resolved = resolved
+            .to_str()
+            .ok_or_else(|| format!("Path on line {} is not valid UTF-8", index + 1))?;
+        paths.push(resolved.to_string());
+    }
+    if paths.is_empty() {
+        return Err(format!("List file '{}' does not name any PDFs.", list_file));
+    }
+
+    merge_pdfs_cancellable(
+        paths.iter().map(String::as_str).collect(),
+        output_path,
+        None,
+        false,
+        None,
+        &CancellationToken::default(),
+        clean,
+    )?;
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Document;
+
+    #[test]
+    fn test_batch_watermark_folder() {
+        let (test_dir, output_dir) = setup_unique_paths("batch_watermark");
+        let input_dir = test_dir.join("inbox");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_minimal_pdf(input_dir.join("a.pdf").to_str().unwrap(), 1, "A").unwrap();
+        create_minimal_pdf(input_dir.join("b.pdf").to_str().unwrap(), 2, "B").unwrap();
+        fs::write(input_dir.join("notes.txt"), "not a pdf").unwrap();
+
+        let result = batch_watermark(
+            input_dir.to_str().unwrap(),
+            "DRAFT",
+            0.3,
+            output_dir.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "batch_watermark failed: {:?}", result.err());
+        let summary = result.unwrap();
+        assert_eq!(summary.len(), 2);
+        assert!(summary.iter().all(|line| !line.starts_with("Error")));
+
+        let out_a = Document::load(output_dir.join("a.pdf")).expect("a.pdf should load");
+        let out_b = Document::load(output_dir.join("b.pdf")).expect("b.pdf should load");
+        assert_eq!(out_a.get_pages().len(), 1);
+        assert_eq!(out_b.get_pages().len(), 2);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_batch_watermark_reports_bad_file() {
+        let (test_dir, output_dir) = setup_unique_paths("batch_watermark_bad");
+        let input_dir = test_dir.join("inbox");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_minimal_pdf(input_dir.join("good.pdf").to_str().unwrap(), 1, "Good").unwrap();
+        fs::write(input_dir.join("broken.pdf"), "definitely not a pdf").unwrap();
+
+        let summary = batch_watermark(
+            input_dir.to_str().unwrap(),
+            "CONFIDENTIAL",
+            0.5,
+            output_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(summary.len(), 2);
+        assert!(summary.iter().any(|l| l.starts_with("Error: broken.pdf")));
+        assert!(output_dir.join("good.pdf").exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_batch_watermark_text_with_parentheses() {
+        let (test_dir, output_dir) = setup_unique_paths("batch_watermark_parens");
+        let input_dir = test_dir.join("inbox");
+        fs::create_dir_all(&input_dir).unwrap();
+        create_minimal_pdf(input_dir.join("a.pdf").to_str().unwrap(), 1, "A").unwrap();
+
+        let summary = batch_watermark(
+            input_dir.to_str().unwrap(),
+            "DRAFT (v2)",
+            0.3,
+            output_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.len(), 1);
+        assert!(!summary[0].starts_with("Error"), "{}", summary[0]);
+
+        let out = Document::load(output_dir.join("a.pdf")).unwrap();
+        let text = out.extract_text(&[1]).unwrap();
+        assert!(text.contains("A-Page 1") && text.contains("DRAFT (v2)"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_from_list_follows_file_order() {
+        let (test_dir, output_dir) = setup_unique_paths("merge_from_list");
+        let parts_dir = test_dir.join("parts");
+        fs::create_dir_all(&parts_dir).unwrap();
+        let first = parts_dir.join("first.pdf");
+        create_minimal_pdf(first.to_str().unwrap(), 1, "First").unwrap();
+        create_minimal_pdf(parts_dir.join("second.pdf").to_str().unwrap(), 2, "Second").unwrap();
+        let list_file = test_dir.join("merge.txt");
+        fs::write(
+            &list_file,
+            format!(
+                "# cover letter last\n\nparts/second.pdf\n  {}\n",
+                first.canonicalize().unwrap().display()
+            ),
+        )
+        .unwrap();
+        let output = output_dir.join("merged.pdf");
+
+        let merged =
+            merge_from_list(list_file.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert!(merged[0].ends_with("second.pdf") && merged[1].ends_with("first.pdf"));
+
+        let doc = Document::load(&output).unwrap();
+        assert_eq!(doc.get_pages().len(), 3);
+        assert!(doc.extract_text(&[1]).unwrap().contains("Second-Page 1"));
+        assert!(doc.extract_text(&[3]).unwrap().contains("First-Page 1"));
+
+        fs::write(&list_file, "parts/missing.pdf\n").unwrap();
+        let missing = merge_from_list(list_file.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert!(missing.unwrap_err().contains("line 1"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
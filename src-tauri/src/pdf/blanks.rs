@@ -0,0 +1,70 @@
+use crate::pdf::utils::load_document;
+use lopdf::{content::Content, Document, ObjectId};
+
+/// Whether a page has no drawing instructions at all: no `/Contents`, empty
+/// streams, or streams that decode to zero operators. Pages whose content paints
+/// nothing visible (white rectangles, clipped text) are not considered empty here.
+fn page_is_structurally_empty(doc: &Document, page_id: ObjectId) -> bool {
+    let has_contents = doc
+        .get_dictionary(page_id)
+        .map(|p| p.has(b"Contents"))
+        .unwrap_or(false);
+    if !has_contents {
+        return true;
+    }
+    match doc.get_page_content(page_id) {
+        Ok(raw) if raw.iter().all(|b| b.is_ascii_whitespace()) => true,
+        Ok(raw) => Content::decode(&raw)
+            .map(|content| content.operations.is_empty())
+            .unwrap_or(false),
+        // Unreadable content is a different defect; don't report it as empty
+        Err(_) => false,
+    }
+}
+
+#[tauri::command]
+pub fn find_empty_pages(path: &str) -> Result<Vec<u32>, String> {
+    let doc = load_document(path, None)?;
+
+    Ok(doc
+        .get_pages()
+        .into_iter()
+        .filter(|(_, page_id)| page_is_structurally_empty(&doc, *page_id))
+        .map(|(page_num, _)| page_num)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::{dictionary, Object, Stream};
+
+    #[test]
+    fn test_find_empty_pages() {
+        let (test_dir, output_dir) = setup_unique_paths("find_empty_pages");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 4, "Empty").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        // Page 2: empty stream, page 3: only whitespace/comments, page 4: no /Contents
+        let empty = doc.add_object(Stream::new(dictionary! {}, Vec::new()));
+        let comment = doc.add_object(Stream::new(dictionary! {}, b"  % nothing\n".to_vec()));
+        doc.get_dictionary_mut(pages[&2])
+            .unwrap()
+            .set("Contents", Object::Reference(empty));
+        doc.get_dictionary_mut(pages[&3])
+            .unwrap()
+            .set("Contents", Object::Reference(comment));
+        doc.get_dictionary_mut(pages[&4])
+            .unwrap()
+            .remove(b"Contents");
+        doc.save(&input).unwrap();
+
+        let empty_pages = find_empty_pages(input.to_str().unwrap()).unwrap();
+        assert_eq!(empty_pages, vec![2, 3, 4]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
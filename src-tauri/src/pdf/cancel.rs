@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::State;
+
+/// Error returned by an operation stopped through `cancel_operation`.
+pub const CANCELLED_ERROR: &str = "Operation cancelled";
+
+/// Flag shared through Tauri state between long-running commands and
+/// `cancel_operation`. Cancellable commands run as async commands so a cancel request
+/// is handled while they work; they clear the flag when they start and poll it
+/// between files or pages.
+#[derive(Default)]
+pub struct CancellationToken(AtomicBool);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// `Err` with `CANCELLED_ERROR` once cancellation has been requested.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err(CANCELLED_ERROR.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Ask the running merge or split to stop at its next checkpoint.
+#[tauri::command]
+pub fn cancel_operation(token: State<'_, CancellationToken>) {
+    token.cancel();
+}
+
+/// Clear a previous cancellation request before starting a new operation.
+#[tauri::command]
+pub fn reset_cancellation(token: State<'_, CancellationToken>) {
+    token.reset();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token_cancel_and_reset() {
+        let token = CancellationToken::default();
+        assert_eq!(token.check(), Ok(()));
+
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(token.check(), Err("Operation cancelled".to_string()));
+
+        token.reset();
+        assert_eq!(token.check(), Ok(()));
+    }
+}
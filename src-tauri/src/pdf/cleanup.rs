@@ -0,0 +1,491 @@
+use crate::pdf::cropper::{
+    matrix_from_operands, multiply, page_content_bbox, transform, Matrix, IDENTITY,
+};
+use crate::pdf::utils::{get_inherited_attribute, load_document, rebuild_clean, rect_from_object};
+use image::GrayImage;
+use lopdf::{content::Content, Document, Object, ObjectId, Stream};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// Luma below this counts as ink on a scanned page
+const INK_THRESHOLD: u8 = 160;
+// A row or column needs this share of dark pixels before it counts, so dust and
+// scanner speckles don't stretch the crop
+const MIN_INK_SHARE: f64 = 0.005;
+// Column profile must be this much "stripier" than the row profile to call the
+// text sideways
+const ROTATION_BIAS: f64 = 1.5;
+// Breathing room kept around the trimmed content, in points
+const CLEANUP_MARGIN: f64 = 12.0;
+
+#[derive(serde::Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    pub auto_rotate: bool,
+    pub auto_crop: bool,
+    pub grayscale: bool,
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        CleanupOptions {
+            auto_rotate: true,
+            auto_crop: true,
+            grayscale: false,
+        }
+    }
+}
+
+pub(crate) fn stream_filters(stream: &Stream) -> Vec<Vec<u8>> {
+    match stream.dict.get(b"Filter") {
+        Ok(Object::Name(name)) => vec![name.clone()],
+        Ok(Object::Array(items)) => items
+            .iter()
+            .filter_map(|o| o.as_name().ok().map(|n| n.to_vec()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub(crate) fn color_components(doc: &Document, stream: &Stream) -> Option<usize> {
+    match stream.dict.get_deref(b"ColorSpace", doc).ok()? {
+        Object::Name(name) => match name.as_slice() {
+            b"DeviceGray" | b"CalGray" => Some(1),
+            b"DeviceRGB" | b"CalRGB" => Some(3),
+            b"DeviceCMYK" => Some(4),
+            _ => None,
+        },
+        Object::Array(items)
+            if items.first().and_then(|o| o.as_name().ok()) == Some(b"ICCBased") =>
+        {
+            let profile = items.get(1).and_then(|o| doc.dereference(o).ok())?.1;
+            profile
+                .as_stream()
+                .ok()?
+                .dict
+                .get(b"N")
+                .and_then(|n| n.as_i64())
+                .ok()
+                .map(|n| n as usize)
+        }
+        _ => None,
+    }
+}
+
+/// Decode an image XObject to 8-bit grayscale. Handles JPEG scans and 8-bit
+/// Flate/uncompressed images; anything else returns `None` and is left alone.
+fn decode_image_luma(doc: &Document, stream: &Stream) -> Option<GrayImage> {
+    let filters = stream_filters(stream);
+    if filters.iter().any(|f| f == b"DCTDecode") {
+        return image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg)
+            .ok()
+            .map(|img| img.to_luma8());
+    }
+    if filters.iter().any(|f| f != b"FlateDecode") {
+        return None;
+    }
+    let width = stream.dict.get(b"Width").and_then(|o| o.as_i64()).ok()? as u32;
+    let height = stream.dict.get(b"Height").and_then(|o| o.as_i64()).ok()? as u32;
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(|o| o.as_i64())
+        .ok()?;
+    let components = color_components(doc, stream)?;
+    if bits != 8 {
+        return None;
+    }
+    let data = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        // lopdf refuses to decompress image streams directly
+        let mut plain = stream.clone();
+        plain.dict.remove(b"Subtype");
+        plain.decompressed_content().ok()?
+    };
+    if data.len() < (width * height) as usize * components {
+        return None;
+    }
+
+    let luma = data
+        .chunks_exact(components)
+        .take((width * height) as usize)
+        .map(|px| match px {
+            [g] => *g,
+            [r, g, b] => (0.299 * *r as f64 + 0.587 * *g as f64 + 0.114 * *b as f64) as u8,
+            [c, m, y, k] => {
+                let ink =
+                    (0.3 * *c as f64 + 0.59 * *m as f64 + 0.11 * *y as f64 + *k as f64).min(255.0);
+                (255.0 - ink) as u8
+            }
+            _ => 255,
+        })
+        .collect();
+    GrayImage::from_raw(width, height, luma)
+}
+
+fn ink_profiles(img: &GrayImage, [x0, y0, x1, y1]: [u32; 4]) -> (Vec<f64>, Vec<f64>) {
+    let mut rows = vec![0.0; (y1 - y0) as usize];
+    let mut cols = vec![0.0; (x1 - x0) as usize];
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if img.get_pixel(x, y).0[0] < INK_THRESHOLD {
+                rows[(y - y0) as usize] += 1.0;
+                cols[(x - x0) as usize] += 1.0;
+            }
+        }
+    }
+    (rows, cols)
+}
+
+/// Pixel bounds `[x0, y0, x1, y1)` of the inked area, ignoring sparse noise.
+fn ink_bounds(img: &GrayImage) -> Option<[u32; 4]> {
+    let (width, height) = img.dimensions();
+    let (rows, cols) = ink_profiles(img, [0, 0, width, height]);
+    let row_min = (width as f64 * MIN_INK_SHARE).max(1.0);
+    let col_min = (height as f64 * MIN_INK_SHARE).max(1.0);
+    let first_last = |profile: &[f64], min: f64| {
+        let first = profile.iter().position(|v| *v >= min)?;
+        let last = profile.iter().rposition(|v| *v >= min)?;
+        Some((first as u32, last as u32 + 1))
+    };
+    let (y0, y1) = first_last(&rows, row_min)?;
+    let (x0, x1) = first_last(&cols, col_min)?;
+    Some([x0, y0, x1, y1])
+}
+
+/// Squared coefficient of variation: how uneven a projection profile is.
+fn unevenness(profile: &[f64]) -> f64 {
+    if profile.is_empty() {
+        return 0.0;
+    }
+    let mean = profile.iter().sum::<f64>() / profile.len() as f64;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = profile.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / profile.len() as f64;
+    variance / (mean * mean)
+}
+
+/// Lines of text make the row projection alternate between ink and gaps. When the
+/// column projection alternates much more strongly, the lines run vertically and
+/// the page was scanned sideways.
+fn text_runs_vertically(img: &GrayImage, bounds: [u32; 4]) -> bool {
+    let (rows, cols) = ink_profiles(img, bounds);
+    unevenness(&cols) > unevenness(&rows) * ROTATION_BIAS
+}
+
+/// The largest image drawn on a page (by placed area), with its placement matrix.
+fn dominant_image(doc: &Document, page_id: ObjectId) -> Option<(ObjectId, Matrix)> {
+    let raw = doc.get_page_content(page_id).ok()?;
+    let content = Content::decode(&raw).ok()?;
+    let xobjects = get_inherited_attribute(doc, page_id, b"Resources")
+        .and_then(|r| r.as_dict().ok())
+        .and_then(|r| r.get_deref(b"XObject", doc).ok())
+        .and_then(|x| x.as_dict().ok())?;
+
+    let mut ctm = IDENTITY;
+    let mut stack = Vec::new();
+    let mut best: Option<(ObjectId, Matrix, f64)> = None;
+    for op in &content.operations {
+        match op.operator.as_str() {
+            "q" => stack.push(ctm),
+            "Q" => ctm = stack.pop().unwrap_or(IDENTITY),
+            "cm" => {
+                if let Some(m) = matrix_from_operands(&op.operands) {
+                    ctm = multiply(&m, &ctm);
+                }
+            }
+            "Do" => {
+                let image_id = op
+                    .operands
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .and_then(|name| xobjects.get(name).ok())
+                    .and_then(|o| o.as_reference().ok());
+                let image_id = match image_id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                let is_image = doc
+                    .get_object(image_id)
+                    .and_then(|o| o.as_stream())
+                    .and_then(|s| s.dict.get(b"Subtype"))
+                    .and_then(|o| o.as_name())
+                    .map(|n| n == b"Image")
+                    .unwrap_or(false);
+                let area = (ctm[0] * ctm[3] - ctm[1] * ctm[2]).abs();
+                if is_image && best.as_ref().map(|(_, _, a)| area > *a).unwrap_or(true) {
+                    best = Some((image_id, ctm, area));
+                }
+            }
+            _ => {}
+        }
+    }
+    best.map(|(id, m, _)| (id, m))
+}
+
+/// Map pixel bounds inside an image onto the page through its placement matrix.
+/// Images fill the unit square with their first row at the top.
+fn pixel_rect_to_page(img: &GrayImage, [x0, y0, x1, y1]: [u32; 4], ctm: &Matrix) -> [f64; 4] {
+    let (w, h) = (img.width() as f64, img.height() as f64);
+    let (u0, u1) = (x0 as f64 / w, x1 as f64 / w);
+    let (v0, v1) = (1.0 - y1 as f64 / h, 1.0 - y0 as f64 / h);
+    let corners = [(u0, v0), (u1, v0), (u0, v1), (u1, v1)].map(|(u, v)| transform(ctm, u, v));
+    let xs = corners.iter().map(|c| c.0);
+    let ys = corners.iter().map(|c| c.1);
+    [
+        xs.clone().fold(f64::INFINITY, f64::min),
+        ys.clone().fold(f64::INFINITY, f64::min),
+        xs.fold(f64::NEG_INFINITY, f64::max),
+        ys.fold(f64::NEG_INFINITY, f64::max),
+    ]
+}
+
+fn grayscale_stream(original: &Stream, img: &GrayImage) -> Stream {
+    let mut dict = original.dict.clone();
+    for key in [&b"Filter"[..], b"DecodeParms", b"Decode", b"Length"] {
+        dict.remove(key);
+    }
+    dict.set("ColorSpace", "DeviceGray");
+    dict.set("BitsPerComponent", 8);
+    dict.set("Width", img.width() as i64);
+    dict.set("Height", img.height() as i64);
+    let mut stream = Stream::new(dict, img.as_raw().clone());
+    // An uncompressed image is still valid if Flate fails
+    let _ = stream.compress();
+    stream
+}
+
+/// One-click tidy-up for scanned pages: turn sideways pages upright, trim the
+/// scanner margins with a CropBox, and optionally convert the scans to grayscale.
+/// Each step can be switched off through `options`; by default rotation and
+/// cropping are on and grayscale is off. Pages without a decodable image are
+/// cropped to their vector content instead and are never rotated.
+#[tauri::command]
+pub fn cleanup_scan(
+    path: &str,
+    output_path: &str,
+    options: Option<CleanupOptions>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let mut doc = load_document(path, None)?;
+    let mut converted: HashSet<ObjectId> = HashSet::new();
+
+    for (page_num, page_id) in doc.get_pages() {
+        let media_box = get_inherited_attribute(&doc, page_id, b"MediaBox")
+            .and_then(rect_from_object)
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+
+        let scan = dominant_image(&doc, page_id).and_then(|(image_id, ctm)| {
+            let stream = doc.get_object(image_id).ok()?.as_stream().ok()?;
+            let img = decode_image_luma(&doc, stream)?;
+            Some((image_id, ctm, img))
+        });
+
+        let mut content_rect = None;
+        let mut rotate = false;
+        if let Some((image_id, ctm, img)) = &scan {
+            if let Some(bounds) = ink_bounds(img) {
+                content_rect = Some(pixel_rect_to_page(img, bounds, ctm));
+                rotate = options.auto_rotate && text_runs_vertically(img, bounds);
+            }
+            if options.grayscale && converted.insert(*image_id) {
+                let original = doc
+                    .get_object(*image_id)
+                    .and_then(|o| o.as_stream())
+                    .map_err(|e| format!("Page {}: failed to read image: {}", page_num, e))?;
+                let gray = grayscale_stream(original, img);
+                doc.objects.insert(*image_id, Object::Stream(gray));
+            }
+        } else if options.auto_crop {
+            content_rect = page_content_bbox(&doc, page_id)
+                .map_err(|e| format!("Page {}: {}", page_num, e))?;
+        }
+
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|e| format!("Failed to access page {}: {}", page_num, e))?;
+        if options.auto_crop {
+            if let Some([x0, y0, x1, y1]) = content_rect {
+                let crop = [
+                    (x0 - CLEANUP_MARGIN).max(media_box[0]),
+                    (y0 - CLEANUP_MARGIN).max(media_box[1]),
+                    (x1 + CLEANUP_MARGIN).min(media_box[2]),
+                    (y1 + CLEANUP_MARGIN).min(media_box[3]),
+                ];
+                page.set(
+                    "CropBox",
+                    Object::Array(crop.iter().map(|v| Object::Real(*v as f32)).collect()),
+                );
+            }
+        }
+        if rotate {
+            let current = page.get(b"Rotate").and_then(|o| o.as_i64()).unwrap_or(0);
+            page.set("Rotate", Object::Integer((current + 90).rem_euclid(360)));
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save cleaned PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    /// A 200x100 RGB "scan" with sideways text: vertical ink stripes confined to
+    /// x 50..150, y 20..80, surrounded by white margins.
+    fn write_sideways_scan(path: &str) {
+        let (w, h) = (200u32, 100u32);
+        let mut pixels = vec![255u8; (w * h * 3) as usize];
+        for y in 20..80 {
+            for x in 50..150 {
+                if (x - 50) % 10 < 4 {
+                    let i = ((y * w + x) * 3) as usize;
+                    pixels[i..i + 3].copy_from_slice(&[0, 0, 0]);
+                }
+            }
+        }
+        let mut image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => w as i64,
+                "Height" => h as i64,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            pixels,
+        );
+        image.compress().unwrap();
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let image_id = doc.add_object(image);
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"q 612 0 0 792 0 0 cm /Im1 Do Q".to_vec(),
+        ));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => Object::Reference(pages_id),
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Contents" => Object::Reference(content_id),
+            "Resources" => dictionary! {
+                "XObject" => dictionary! { "Im1" => Object::Reference(image_id) },
+            },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![Object::Reference(page_id)],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => Object::Reference(pages_id),
+        });
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_scan_rotates_and_crops() {
+        let (test_dir, output_dir) = setup_unique_paths("cleanup_scan");
+        let input = test_dir.join("scan.pdf");
+        let output = output_dir.join("clean.pdf");
+        write_sideways_scan(input.to_str().unwrap());
+
+        let result = cleanup_scan(
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(result.is_ok(), "cleanup_scan failed: {:?}", result.err());
+
+        let doc = Document::load(&output).unwrap();
+        let page = doc.get_dictionary(doc.get_pages()[&1]).unwrap();
+        assert_eq!(page.get(b"Rotate").unwrap().as_i64().unwrap(), 90);
+
+        // Ink spans x 153..459 and y 158.4..633.6 in page space
+        let [x0, y0, x1, y1] = rect_from_object(page.get(b"CropBox").unwrap()).unwrap();
+        let expected = [153.0, 158.4, 459.0, 633.6];
+        for (actual, target) in [x0, y0, x1, y1].iter().zip(expected) {
+            assert!(
+                (actual - target).abs() <= CLEANUP_MARGIN + 4.0,
+                "crop {:?} too far from {:?}",
+                [x0, y0, x1, y1],
+                expected
+            );
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_cleanup_scan_grayscale_toggle() {
+        let (test_dir, output_dir) = setup_unique_paths("cleanup_scan_gray");
+        let input = test_dir.join("scan.pdf");
+        let output = output_dir.join("gray.pdf");
+        write_sideways_scan(input.to_str().unwrap());
+
+        let options = CleanupOptions {
+            auto_rotate: false,
+            auto_crop: false,
+            grayscale: true,
+        };
+        cleanup_scan(
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+            Some(options),
+            None,
+        )
+        .unwrap();
+
+        let doc = Document::load(&output).unwrap();
+        let page = doc.get_dictionary(doc.get_pages()[&1]).unwrap();
+        assert!(!page.has(b"Rotate"));
+        assert!(!page.has(b"CropBox"));
+        let image = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_stream().ok())
+            .find(|s| s.dict.get(b"Subtype").and_then(|o| o.as_name()).ok() == Some(b"Image"))
+            .unwrap();
+        assert_eq!(
+            image.dict.get(b"ColorSpace").unwrap().as_name().unwrap(),
+            b"DeviceGray"
+        );
+        let mut plain = image.clone();
+        plain.dict.remove(b"Subtype");
+        assert_eq!(plain.decompressed_content().unwrap().len(), 200 * 100);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,661 @@
+use crate::pdf::cleanup::stream_filters;
+use crate::pdf::utils::{file_size, get_inherited_attribute, load_document, rebuild_clean};
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// How much of the document `rgb_to_cmyk` rewrote, as a sanity check that the
+/// conversion actually touched something.
+#[derive(serde::Serialize, Debug)]
+pub struct CmykConversion {
+    pub operators_converted: usize,
+    pub images_converted: usize,
+}
+
+/// Naive RGB → CMYK with full black generation; components are in 0..=1.
+pub(crate) fn naive_cmyk(r: f64, g: f64, b: f64) -> [f64; 4] {
+    let k = 1.0 - r.max(g).max(b);
+    if k >= 1.0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    [
+        (1.0 - r - k) / (1.0 - k),
+        (1.0 - g - k) / (1.0 - k),
+        (1.0 - b - k) / (1.0 - k),
+        k,
+    ]
+}
+
+fn cmyk_operands(operands: &[Object]) -> Option<Vec<Object>> {
+    let rgb: Vec<f64> = operands
+        .iter()
+        .map(|o| o.as_float().ok().map(|v| (v as f64).clamp(0.0, 1.0)))
+        .collect::<Option<_>>()?;
+    let [r, g, b] = rgb[..] else {
+        return None;
+    };
+    Some(
+        naive_cmyk(r, g, b)
+            .iter()
+            .map(|v| Object::Real(((v * 10000.0).round() / 10000.0) as f32))
+            .collect(),
+    )
+}
+
+/// Names in a resource dictionary's `/ColorSpace` entry that are plain DeviceRGB aliases.
+fn rgb_color_space_names(doc: &Document, resources: Option<&Dictionary>) -> HashSet<Vec<u8>> {
+    let mut names = HashSet::from([b"DeviceRGB".to_vec(), b"RGB".to_vec()]);
+    if let Some(spaces) = resources
+        .and_then(|r| r.get_deref(b"ColorSpace", doc).ok())
+        .and_then(|c| c.as_dict().ok())
+    {
+        for (name, space) in spaces.iter() {
+            let is_rgb = doc
+                .dereference(space)
+                .ok()
+                .and_then(|(_, s)| s.as_name().ok())
+                .is_some_and(|s| s == b"DeviceRGB");
+            if is_rgb {
+                names.insert(name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Rewrite RGB color operators in place, returning how many were converted.
+/// `rg`/`RG` become `k`/`K`; `cs`/`CS` selecting an RGB space switch to DeviceCMYK so
+/// the `sc`/`scn` operators that follow are converted too.
+fn convert_operations(operations: &mut [Operation], rgb_names: &HashSet<Vec<u8>>) -> usize {
+    let mut converted = 0;
+    let (mut fill_rgb, mut stroke_rgb) = (false, false);
+    for operation in operations.iter_mut() {
+        let operator = operation.operator.clone();
+        match operator.as_str() {
+            "rg" | "RG" => {
+                if let Some(cmyk) = cmyk_operands(&operation.operands) {
+                    operation.operator = if operator == "rg" { "k" } else { "K" }.to_string();
+                    operation.operands = cmyk;
+                    converted += 1;
+                }
+            }
+            "cs" | "CS" => {
+                let is_rgb = operation
+                    .operands
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .is_some_and(|n| rgb_names.contains(n));
+                if is_rgb {
+                    operation.operands = vec![Object::Name(b"DeviceCMYK".to_vec())];
+                    converted += 1;
+                }
+                if operator == "cs" {
+                    fill_rgb = is_rgb;
+                } else {
+                    stroke_rgb = is_rgb;
+                }
+            }
+            "g" | "k" => fill_rgb = false,
+            "G" | "K" => stroke_rgb = false,
+            "sc" | "scn" | "SC" | "SCN" => {
+                let in_rgb = if operator.starts_with('s') {
+                    fill_rgb
+                } else {
+                    stroke_rgb
+                };
+                if in_rgb {
+                    if let Some(cmyk) = cmyk_operands(&operation.operands) {
+                        operation.operands = cmyk;
+                        converted += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    converted
+}
+
+/// Re-encode an 8-bit DeviceRGB image (JPEG, Flate or uncompressed) as Flate-compressed
+/// DeviceCMYK. Other images are left alone and return `None`.
+fn cmyk_image(stream: &Stream) -> Option<Stream> {
+    let is_rgb = stream
+        .dict
+        .get(b"ColorSpace")
+        .and_then(|c| c.as_name())
+        .ok()
+        .is_some_and(|c| c == b"DeviceRGB");
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(|b| b.as_i64())
+        .ok();
+    if !is_rgb || bits != Some(8) {
+        return None;
+    }
+    let filters = stream_filters(stream);
+    let rgb = if filters.iter().any(|f| f == b"DCTDecode") {
+        image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg)
+            .ok()?
+            .to_rgb8()
+            .into_raw()
+    } else if filters.iter().all(|f| f == b"FlateDecode") && !stream.dict.has(b"DecodeParms") {
+        // lopdf refuses to decompress image streams directly
+        let mut plain = stream.clone();
+        plain.dict.remove(b"Subtype");
+        if filters.is_empty() {
+            plain.content
+        } else {
+            plain.decompressed_content().ok()?
+        }
+    } else {
+        return None;
+    };
+
+    let cmyk: Vec<u8> = rgb
+        .chunks_exact(3)
+        .flat_map(|px| {
+            naive_cmyk(
+                px[0] as f64 / 255.0,
+                px[1] as f64 / 255.0,
+                px[2] as f64 / 255.0,
+            )
+            .map(|v| (v * 255.0).round() as u8)
+        })
+        .collect();
+    let mut dict = stream.dict.clone();
+    for key in [&b"Filter"[..], b"DecodeParms", b"Decode", b"Length"] {
+        dict.remove(key);
+    }
+    dict.set("ColorSpace", "DeviceCMYK");
+    let mut converted = Stream::new(dict, cmyk);
+    // An uncompressed image is still valid if Flate fails
+    let _ = converted.compress();
+    Some(converted)
+}
+
+/// Convert DeviceRGB colors on every page, in form XObjects, and in 8-bit RGB images
+/// to DeviceCMYK for print. Without an ICC profile this uses the naive
+/// `k = 1 - max(r, g, b)` formula, so colors may visibly shift on press; use a
+/// color-managed tool where accurate proofs matter.
+#[tauri::command]
+pub fn rgb_to_cmyk(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<CmykConversion, String> {
+    let mut doc = load_document(path, None)?;
+    let mut report = CmykConversion {
+        operators_converted: 0,
+        images_converted: 0,
+    };
+
+    for (page_num, page_id) in doc.get_pages() {
+        let resources =
+            get_inherited_attribute(&doc, page_id, b"Resources").and_then(|r| r.as_dict().ok());
+        let rgb_names = rgb_color_space_names(&doc, resources);
+        let data = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        let mut content = Content::decode(&data)
+            .map_err(|e| format!("Failed to parse content of page {}: {}", page_num, e))?;
+        let converted = convert_operations(&mut content.operations, &rgb_names);
+        if converted > 0 {
+            let encoded = content
+                .encode()
+                .map_err(|e| format!("Failed to encode content of page {}: {}", page_num, e))?;
+            doc.change_page_content(page_id, encoded)
+                .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))?;
+            report.operators_converted += converted;
+        }
+    }
+
+    let xobject_ids: Vec<ObjectId> = doc
+        .objects
+        .iter()
+        .filter(|(_, o)| {
+            o.as_stream()
+                .and_then(|s| s.dict.get(b"Subtype"))
+                .and_then(|s| s.as_name())
+                .is_ok_and(|s| s == b"Form" || s == b"Image")
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    for id in xobject_ids {
+        let Ok(stream) = doc.get_object(id).and_then(|o| o.as_stream()) else {
+            continue;
+        };
+        let is_form = stream.dict.get(b"Subtype").and_then(|s| s.as_name()).ok() == Some(b"Form");
+        let replacement = if is_form {
+            let resources = stream
+                .dict
+                .get_deref(b"Resources", &doc)
+                .and_then(|r| r.as_dict())
+                .ok();
+            let rgb_names = rgb_color_space_names(&doc, resources);
+            let data = stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone());
+            let Ok(mut content) = Content::decode(&data) else {
+                continue;
+            };
+            let converted = convert_operations(&mut content.operations, &rgb_names);
+            if converted == 0 {
+                continue;
+            }
+            let encoded = content
+                .encode()
+                .map_err(|e| format!("Failed to encode form XObject {:?}: {}", id, e))?;
+            report.operators_converted += converted;
+            let mut form = Stream::new(stream.dict.clone(), encoded);
+            form.dict.remove(b"Filter");
+            form.dict.remove(b"DecodeParms");
+            form
+        } else {
+            match cmyk_image(stream) {
+                Some(image) => {
+                    report.images_converted += 1;
+                    image
+                }
+                None => continue,
+            }
+        };
+        doc.objects.insert(id, Object::Stream(replacement));
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save CMYK PDF to '{}': {}", output_path, e))?;
+
+    Ok(report)
+}
+
+// --- Color detection ---
+
+/// True if color operands describe a chromatic color: RGB with unequal channels or
+/// CMYK with any cyan, magenta or yellow. Gray and single-tint values are neutral.
+fn operands_are_chromatic(operands: &[Object]) -> bool {
+    let values: Vec<f32> = operands.iter().filter_map(|o| o.as_float().ok()).collect();
+    match values[..] {
+        [r, g, b] => (r - g).abs() > 0.01 || (g - b).abs() > 0.01,
+        [c, m, y, _] => c > 0.01 || m > 0.01 || y > 0.01,
+        _ => false,
+    }
+}
+
+/// True if an image XObject's color space has three or more components, directly or
+/// as the base of an Indexed palette.
+fn image_is_color(doc: &Document, image: &Stream) -> bool {
+    let Ok((_, space)) = image
+        .dict
+        .get(b"ColorSpace")
+        .and_then(|c| doc.dereference(c))
+    else {
+        return false;
+    };
+    let space = match space {
+        Object::Array(items)
+            if items.first().and_then(|o| o.as_name().ok()) == Some(b"Indexed") =>
+        {
+            match items.get(1).map(|b| doc.dereference(b)) {
+                Some(Ok((_, base))) => base,
+                _ => return false,
+            }
+        }
+        other => other,
+    };
+    match space {
+        Object::Name(name) => matches!(
+            name.as_slice(),
+            b"DeviceRGB" | b"CalRGB" | b"DeviceCMYK" | b"Lab"
+        ),
+        Object::Array(items) => match items.first().and_then(|o| o.as_name().ok()) {
+            Some(b"CalRGB") | Some(b"Lab") | Some(b"DeviceN") => true,
+            Some(b"ICCBased") => items
+                .get(1)
+                .and_then(|p| doc.dereference(p).ok())
+                .and_then(|(_, p)| p.as_stream().ok())
+                .and_then(|p| p.dict.get(b"N").and_then(|n| n.as_i64()).ok())
+                .is_some_and(|n| n >= 3),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn content_is_color(
+    doc: &Document,
+    data: &[u8],
+    resources: Option<&Dictionary>,
+    visited: &mut HashSet<ObjectId>,
+) -> bool {
+    let Ok(content) = Content::decode(data) else {
+        return false;
+    };
+    let xobjects = resources
+        .and_then(|r| r.get_deref(b"XObject", doc).ok())
+        .and_then(|x| x.as_dict().ok());
+    content
+        .operations
+        .iter()
+        .any(|op| match op.operator.as_str() {
+            "rg" | "RG" | "k" | "K" | "sc" | "SC" | "scn" | "SCN" => {
+                operands_are_chromatic(&op.operands)
+            }
+            "Do" => {
+                let Some(Ok(Object::Reference(id))) = op
+                    .operands
+                    .first()
+                    .and_then(|n| n.as_name().ok())
+                    .and_then(|n| xobjects.map(|x| x.get(n)))
+                else {
+                    return false;
+                };
+                if !visited.insert(*id) {
+                    return false;
+                }
+                let Ok(stream) = doc.get_object(*id).and_then(|o| o.as_stream()) else {
+                    return false;
+                };
+                match stream.dict.get(b"Subtype").and_then(|s| s.as_name()) {
+                    Ok(b"Image") => image_is_color(doc, stream),
+                    Ok(b"Form") => {
+                        let form_resources = stream
+                            .dict
+                            .get_deref(b"Resources", doc)
+                            .and_then(|r| r.as_dict())
+                            .ok();
+                        let data = stream
+                            .decompressed_content()
+                            .unwrap_or_else(|_| stream.content.clone());
+                        content_is_color(doc, &data, form_resources, visited)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        })
+}
+
+/// Whether a page paints anything in color: chromatic fill/stroke colors in its
+/// content (or in forms it draws) or color images. Used to tell color pages from
+/// grayscale ones.
+pub(crate) fn page_is_color(doc: &Document, page_id: ObjectId) -> bool {
+    let Ok(data) = doc.get_page_content(page_id) else {
+        return false;
+    };
+    let resources =
+        get_inherited_attribute(doc, page_id, b"Resources").and_then(|r| r.as_dict().ok());
+    content_is_color(doc, &data, resources, &mut HashSet::new())
+}
+
+// --- ICC profile stripping ---
+
+#[derive(serde::Serialize, Debug)]
+pub struct IccRemoval {
+    /// Negative if the output grew.
+    pub bytes_saved: i64,
+    pub profiles_removed: usize,
+}
+
+/// The ICC stream behind an `[/ICCBased stream]` color space array.
+fn icc_profile_id(object: &Object) -> Option<ObjectId> {
+    match object.as_array().ok()?.as_slice() {
+        [Object::Name(family), Object::Reference(id)] if family == b"ICCBased" => Some(*id),
+        _ => None,
+    }
+}
+
+fn collect_icc_profiles(object: &Object, profiles: &mut HashSet<ObjectId>) {
+    if let Some(id) = icc_profile_id(object) {
+        profiles.insert(id);
+        return;
+    }
+    match object {
+        Object::Array(items) => items.iter().for_each(|o| collect_icc_profiles(o, profiles)),
+        Object::Dictionary(dict) => dict
+            .iter()
+            .for_each(|(_, o)| collect_icc_profiles(o, profiles)),
+        Object::Stream(stream) => stream
+            .dict
+            .iter()
+            .for_each(|(_, o)| collect_icc_profiles(o, profiles)),
+        _ => {}
+    }
+}
+
+/// Replace every ICCBased color space with the device space of the same component
+/// count, wherever it appears (resource dictionaries, images, Indexed bases).
+fn replace_icc_spaces(object: &mut Object, device_spaces: &HashMap<ObjectId, &[u8]>) {
+    if let Some(device) = icc_profile_id(object).and_then(|id| device_spaces.get(&id)) {
+        *object = Object::Name(device.to_vec());
+        return;
+    }
+    match object {
+        Object::Array(items) => items
+            .iter_mut()
+            .for_each(|o| replace_icc_spaces(o, device_spaces)),
+        Object::Dictionary(dict) => dict
+            .iter_mut()
+            .for_each(|(_, o)| replace_icc_spaces(o, device_spaces)),
+        Object::Stream(stream) => stream
+            .dict
+            .iter_mut()
+            .for_each(|(_, o)| replace_icc_spaces(o, device_spaces)),
+        _ => {}
+    }
+}
+
+/// Swap embedded ICC color spaces for DeviceGray/RGB/CMYK (chosen by the profile's
+/// `/N`) and drop the profile streams. Colors may shift slightly; profiles still used
+/// elsewhere (e.g. by a PDF/A OutputIntent) are kept.
+#[tauri::command]
+pub fn strip_icc_profiles(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<IccRemoval, String> {
+    let mut doc = load_document(path, None)?;
+
+    let original_size = file_size(path)?;
+
+    let mut profiles = HashSet::new();
+    for object in doc.objects.values() {
+        collect_icc_profiles(object, &mut profiles);
+    }
+    let device_spaces: HashMap<ObjectId, &[u8]> = profiles
+        .into_iter()
+        .filter_map(|id| {
+            let components = doc
+                .get_object(id)
+                .and_then(|o| o.as_stream())
+                .and_then(|s| s.dict.get(b"N"))
+                .and_then(|n| n.as_i64())
+                .ok()?;
+            let device: &[u8] = match components {
+                1 => b"DeviceGray",
+                3 => b"DeviceRGB",
+                4 => b"DeviceCMYK",
+                _ => return None,
+            };
+            Some((id, device))
+        })
+        .collect();
+
+    for object in doc.objects.values_mut() {
+        replace_icc_spaces(object, &device_spaces);
+    }
+    doc.prune_objects();
+    let profiles_removed = device_spaces
+        .keys()
+        .filter(|id| !doc.objects.contains_key(id))
+        .count();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(IccRemoval {
+        bytes_saved: original_size as i64 - file_size(output_path)? as i64,
+        profiles_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_rgb_to_cmyk_rewrites_fill_operators() {
+        let (test_dir, output_dir) = setup_unique_paths("rgb_to_cmyk");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("cmyk.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Color").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        doc.change_page_content(
+            page_id,
+            b"1 0 0 rg 0 0 1 RG 10 10 100 100 re B /DeviceRGB cs 0 1 0 sc 0 0 50 50 re f".to_vec(),
+        )
+        .unwrap();
+        let pixels = [255u8, 0, 0, 255, 255, 255];
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 1,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            pixels.to_vec(),
+        ));
+        doc.save(&input).unwrap();
+
+        let report = rgb_to_cmyk(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert_eq!(report.operators_converted, 4);
+        assert_eq!(report.images_converted, 1);
+
+        let converted = Document::load(&output).unwrap();
+        let page_id = converted.get_pages()[&1];
+        let content = Content::decode(&converted.get_page_content(page_id).unwrap()).unwrap();
+        let operators: Vec<&str> = content
+            .operations
+            .iter()
+            .map(|o| o.operator.as_str())
+            .collect();
+        assert!(!operators.contains(&"rg") && !operators.contains(&"RG"));
+        let fill = content
+            .operations
+            .iter()
+            .find(|o| o.operator == "k")
+            .unwrap();
+        let cmyk: Vec<f32> = fill
+            .operands
+            .iter()
+            .map(|o| o.as_float().unwrap())
+            .collect();
+        assert_eq!(cmyk, vec![0.0, 1.0, 1.0, 0.0]);
+        assert!(operators.contains(&"K"));
+
+        let mut image = converted
+            .get_object(image_id)
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .clone();
+        assert_eq!(
+            image.dict.get(b"ColorSpace").unwrap().as_name().unwrap(),
+            b"DeviceCMYK"
+        );
+        image.dict.remove(b"Subtype");
+        let data = image.decompressed_content().unwrap_or(image.content);
+        assert_eq!(data, vec![0, 255, 255, 0, 0, 0, 0, 0]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_strip_icc_profiles_uses_device_space() {
+        let (test_dir, output_dir) = setup_unique_paths("strip_icc");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("stripped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Icc").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let icc_id = doc.add_object(Stream::new(
+            dictionary! { "N" => 3, "Alternate" => "DeviceRGB" },
+            vec![0x42; 4096],
+        ));
+        let resources_id = doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get(b"Resources"))
+            .and_then(|r| r.as_reference())
+            .unwrap();
+        doc.get_dictionary_mut(resources_id).unwrap().set(
+            "ColorSpace",
+            dictionary! {
+                "CS0" => vec![Object::Name(b"ICCBased".to_vec()), Object::Reference(icc_id)],
+            },
+        );
+        doc.change_page_content(page_id, b"/CS0 cs 1 0 0 sc 0 0 50 50 re f".to_vec())
+            .unwrap();
+        doc.save(&input).unwrap();
+
+        let report =
+            strip_icc_profiles(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert_eq!(report.profiles_removed, 1);
+        assert!(report.bytes_saved > 0);
+
+        let stripped = Document::load(&output).unwrap();
+        assert!(stripped.get_object(icc_id).is_err());
+        let page_id = stripped.get_pages()[&1];
+        let space = get_inherited_attribute(&stripped, page_id, b"Resources")
+            .and_then(|r| r.as_dict().ok())
+            .and_then(|r| r.get_deref(b"ColorSpace", &stripped).ok())
+            .and_then(|c| c.as_dict().ok())
+            .and_then(|c| c.get(b"CS0").ok())
+            .unwrap();
+        assert_eq!(space.as_name().unwrap(), b"DeviceRGB");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,502 @@
+use crate::pdf::utils::{file_size, load_document, rebuild_clean};
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct CompressionReport {
+    /// Size of the input file in bytes.
+    pub original_size: u64,
+    /// Size of the written file in bytes.
+    pub compressed_size: u64,
+}
+
+/// Compress `path` into `output_path` and report both file sizes. `preset` picks
+/// "web", "print" (prune only) or "min" (also drops metadata); without one,
+/// unused objects are pruned and streams are compressed.
+#[tauri::command]
+pub fn compress_pdf(
+    path: &str,
+    output_path: &str,
+    preset: Option<String>,
+    clean: Option<bool>,
+) -> Result<CompressionReport, String> {
+    let original_size = file_size(path)?;
+    let mut doc = load_document(path, None)?;
+    
+    match preset.unwrap_or_default().to_lowercase().as_str() {
+        "web" => {
+            // High compression: compress streams and prune unused objects
+            doc.prune_objects();
+            doc.compress();
+        }
+        "print" => {
+            // Lossless: only prune unused objects, no re-compression of streams
+            doc.prune_objects();
+        }
+        "min" | "minimal" => {
+            // Extreme: prune, compress, and remove non-essential document metadata
+            doc.prune_objects();
+            doc.compress();
+            
+            // Remove Metadata from Root
+            if let Ok(root_id) = doc.trailer.get(b"Root").and_then(|obj| obj.as_reference()) {
+                if let Ok(mut root) = doc.get_object(root_id).and_then(|obj| obj.as_dict()).cloned() {
+                    root.remove(b"Metadata");
+                    root.remove(b"PieceInfo");
+                    doc.objects.insert(root_id, lopdf::Object::Dictionary(root));
+                }
+            }
+            // Remove Info dictionary
+            doc.trailer.remove(b"Info");
+        }
+        _ => {
+            doc.prune_objects();
+            doc.compress();
+        }
+    }
+    
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path).map_err(|e| format!("Failed to save compressed PDF: {}", e))?;
+    Ok(CompressionReport {
+        original_size,
+        compressed_size: file_size(output_path)?,
+    })
+}
+
+// --- prune_page_resources ---
+
+/// Resource categories that are pruned, with the content operator that names an entry.
+const PRUNABLE_RESOURCES: [(&[u8], &str); 3] =
+    [(b"Font", "Tf"), (b"XObject", "Do"), (b"ExtGState", "gs")];
+
+/// Where a `/Resources` dictionary lives: its own object, or inline in a page tree node.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum ResourcesAt {
+    Object(ObjectId),
+    InlineIn(ObjectId),
+}
+
+/// Where a resource category dictionary (e.g. `/Font`) lives.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum CategoryAt {
+    Object(ObjectId),
+    InlineIn(ResourcesAt, &'static [u8]),
+}
+
+/// Find the page tree node whose `/Resources` applies to a page (the page itself or
+/// the nearest ancestor) and return the node id with the resources location.
+fn locate_resources(doc: &Document, page_id: ObjectId) -> Option<(ObjectId, ResourcesAt)> {
+    let mut node = page_id;
+    let mut visited = HashSet::new();
+    while visited.insert(node) {
+        let dict = doc.get_dictionary(node).ok()?;
+        match dict.get(b"Resources") {
+            Ok(Object::Reference(id)) => return Some((node, ResourcesAt::Object(*id))),
+            Ok(Object::Dictionary(_)) => return Some((node, ResourcesAt::InlineIn(node))),
+            _ => node = dict.get(b"Parent").and_then(|p| p.as_reference()).ok()?,
+        }
+    }
+    None
+}
+
+fn resources_dict(doc: &Document, at: ResourcesAt) -> Option<&Dictionary> {
+    match at {
+        ResourcesAt::Object(id) => doc.get_dictionary(id).ok(),
+        ResourcesAt::InlineIn(holder) => doc
+            .get_dictionary(holder)
+            .and_then(|d| d.get(b"Resources"))
+            .and_then(|r| r.as_dict())
+            .ok(),
+    }
+}
+
+fn category_dict_mut(doc: &mut Document, at: CategoryAt) -> Option<&mut Dictionary> {
+    match at {
+        CategoryAt::Object(id) => doc.get_dictionary_mut(id).ok(),
+        CategoryAt::InlineIn(resources, key) => {
+            let resources = match resources {
+                ResourcesAt::Object(id) => doc.get_dictionary_mut(id).ok()?,
+                ResourcesAt::InlineIn(holder) => doc
+                    .get_dictionary_mut(holder)
+                    .and_then(|d| d.get_mut(b"Resources"))
+                    .and_then(|r| r.as_dict_mut())
+                    .ok()?,
+            };
+            resources.get_mut(key).and_then(|c| c.as_dict_mut()).ok()
+        }
+    }
+}
+
+fn collect_resource_names(
+    operations: &[Operation],
+    names: &mut HashMap<&'static [u8], HashSet<Vec<u8>>>,
+) {
+    for operation in operations {
+        for (category, operator) in PRUNABLE_RESOURCES {
+            if operation.operator == operator {
+                if let Some(Object::Name(name)) = operation.operands.first() {
+                    names.entry(category).or_default().insert(name.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Resource names a page's content refers to, by category. Form XObjects without their
+/// own `/Resources` draw with the page's resources, so their content is scanned too.
+/// Returns `None` when some content cannot be decoded.
+fn page_resource_names(
+    doc: &Document,
+    page_id: ObjectId,
+    resources: &Dictionary,
+) -> Option<HashMap<&'static [u8], HashSet<Vec<u8>>>> {
+    let raw = doc.get_page_content(page_id).ok()?;
+    let mut names = HashMap::new();
+    collect_resource_names(&Content::decode(&raw).ok()?.operations, &mut names);
+
+    let xobjects = resources
+        .get_deref(b"XObject", doc)
+        .and_then(|x| x.as_dict())
+        .ok();
+    let mut pending: Vec<Vec<u8>> = names
+        .get(b"XObject".as_slice())
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+    let mut scanned = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if !scanned.insert(name.clone()) {
+            continue;
+        }
+        let Some(form) = xobjects
+            .and_then(|x| x.get_deref(&name, doc).ok())
+            .and_then(|o| o.as_stream().ok())
+        else {
+            continue;
+        };
+        let is_form = form
+            .dict
+            .get(b"Subtype")
+            .and_then(|s| s.as_name())
+            .is_ok_and(|s| s == b"Form");
+        if !is_form || form.dict.has(b"Resources") {
+            continue;
+        }
+        let data = form
+            .decompressed_content()
+            .unwrap_or_else(|_| form.content.clone());
+        let mut form_names = HashMap::new();
+        collect_resource_names(&Content::decode(&data).ok()?.operations, &mut form_names);
+        for (category, found) in form_names {
+            if category == b"XObject" {
+                pending.extend(found.iter().cloned());
+            }
+            names.entry(category).or_default().extend(found);
+        }
+    }
+    Some(names)
+}
+
+fn collect_references(object: &Object, out: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => out.push(*id),
+        Object::Array(items) => items.iter().for_each(|item| collect_references(item, out)),
+        Object::Dictionary(dict) => dict
+            .iter()
+            .for_each(|(_, value)| collect_references(value, out)),
+        Object::Stream(stream) => stream
+            .dict
+            .iter()
+            .for_each(|(_, value)| collect_references(value, out)),
+        _ => {}
+    }
+}
+
+/// Remove `/Font`, `/XObject` and `/ExtGState` entries that no page's content refers to,
+/// then prune objects that became unreachable. Returns the bytes saved.
+///
+/// Resources shared between pages (by reference or through page tree inheritance) keep
+/// every name any of those pages uses. Dictionaries also referenced from outside the
+/// page tree (e.g. form field appearances) and pages whose content cannot be decoded
+/// are left untouched.
+#[tauri::command]
+pub fn prune_page_resources(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<i64, String> {
+    let mut doc = load_document(path, None)?;
+
+    let original_size = file_size(path)?;
+
+    let mut used: HashMap<CategoryAt, HashSet<Vec<u8>>> = HashMap::new();
+    let mut keep_all: HashSet<CategoryAt> = HashSet::new();
+    let mut page_tree_holders: HashSet<ObjectId> = HashSet::new();
+    for page_id in doc.get_pages().into_values() {
+        let Some((holder, resources_at)) = locate_resources(&doc, page_id) else {
+            continue;
+        };
+        let Some(resources) = resources_dict(&doc, resources_at) else {
+            continue;
+        };
+        page_tree_holders.insert(holder);
+        if let ResourcesAt::Object(id) = resources_at {
+            page_tree_holders.insert(id);
+        }
+
+        let categories: Vec<(&[u8], CategoryAt)> = PRUNABLE_RESOURCES
+            .iter()
+            .filter_map(|&(key, _)| match resources.get(key) {
+                Ok(Object::Reference(id)) => Some((key, CategoryAt::Object(*id))),
+                Ok(Object::Dictionary(_)) => Some((key, CategoryAt::InlineIn(resources_at, key))),
+                _ => None,
+            })
+            .collect();
+        match page_resource_names(&doc, page_id, resources) {
+            Some(mut names) => {
+                for (key, at) in categories {
+                    used.entry(at)
+                        .or_default()
+                        .extend(names.remove(key).unwrap_or_default());
+                }
+            }
+            None => keep_all.extend(categories.into_iter().map(|(_, at)| at)),
+        }
+    }
+
+    // Only prune dictionaries that nothing outside the page tree's resources refers to
+    let mut referrers: HashMap<ObjectId, HashSet<ObjectId>> = HashMap::new();
+    for (&id, object) in &doc.objects {
+        let mut targets = Vec::new();
+        collect_references(object, &mut targets);
+        for target in targets {
+            referrers.entry(target).or_default().insert(id);
+        }
+    }
+    let page_tree_only = |id: &ObjectId| {
+        referrers
+            .get(id)
+            .is_none_or(|from| from.is_subset(&page_tree_holders))
+    };
+
+    for (at, names) in used {
+        let owner = match at {
+            CategoryAt::Object(id) | CategoryAt::InlineIn(ResourcesAt::Object(id), _) => Some(id),
+            CategoryAt::InlineIn(ResourcesAt::InlineIn(_), _) => None,
+        };
+        if keep_all.contains(&at) || !owner.is_none_or(|id| page_tree_only(&id)) {
+            continue;
+        }
+        if let Some(dict) = category_dict_mut(&mut doc, at) {
+            let unused: Vec<Vec<u8>> = dict
+                .iter()
+                .map(|(name, _)| name.clone())
+                .filter(|name| !names.contains(name))
+                .collect();
+            for name in unused {
+                dict.remove(&name);
+            }
+        }
+    }
+    doc.prune_objects();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save pruned PDF to '{}': {}", output_path, e))?;
+
+    Ok(original_size as i64 - file_size(output_path)? as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_compress_pdf_success() {
+        let (test_dir, output_dir) = setup_unique_paths("compress");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "CompressTest").unwrap();
+
+        let result = compress_pdf(
+            input_path.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            Some("web".to_string()),
+            None
+        );
+
+        assert!(result.is_ok());
+        assert!(output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_compress_pdf_reports_sizes() {
+        let (test_dir, output_dir) = setup_unique_paths("compress_sizes");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 3, "Sizes").unwrap();
+
+        let report = compress_pdf(
+            input_path.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            report.original_size,
+            fs::metadata(&input_path).unwrap().len()
+        );
+        assert_eq!(
+            report.compressed_size,
+            fs::metadata(&output_path).unwrap().len()
+        );
+        assert!(report.compressed_size > 0);
+        assert_eq!(Document::load(&output_path).unwrap().get_pages().len(), 3);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_prune_page_resources_removes_unused_font() {
+        let (test_dir, output_dir) = setup_unique_paths("prune_resources");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Prune").unwrap();
+
+        let mut doc = Document::load(&input_path).unwrap();
+        let unused_font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let page_id = doc.get_pages()[&1];
+        let resources_id = doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get(b"Resources"))
+            .and_then(|r| r.as_reference())
+            .unwrap();
+        doc.get_dictionary_mut(resources_id)
+            .and_then(|r| r.get_mut(b"Font"))
+            .and_then(|f| f.as_dict_mut())
+            .unwrap()
+            .set("F2", lopdf::Object::Reference(unused_font));
+        doc.save(&input_path).unwrap();
+
+        let saved = prune_page_resources(
+            input_path.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(saved > 0);
+
+        let pruned = Document::load(&output_path).unwrap();
+        for page_id in pruned.get_pages().into_values() {
+            let fonts = pruned
+                .get_dictionary(page_id)
+                .and_then(|p| p.get_deref(b"Resources", &pruned))
+                .and_then(|r| r.as_dict())
+                .and_then(|r| r.get(b"Font"))
+                .and_then(|f| f.as_dict())
+                .unwrap();
+            assert!(fonts.has(b"F1"));
+            assert!(!fonts.has(b"F2"));
+        }
+        assert!(!pruned.objects.values().any(|o| o
+            .as_dict()
+            .and_then(|d| d.get(b"BaseFont"))
+            .is_ok_and(|b| b.as_name().is_ok_and(|n| n == b"Courier"))));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_prune_page_resources_keeps_names_used_by_any_sharing_page() {
+        let (test_dir, output_dir) = setup_unique_paths("prune_resources_shared");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Shared").unwrap();
+
+        // Move the shared resources onto the page tree root and switch page 2 to /F2
+        let mut doc = Document::load(&input_path).unwrap();
+        let pages = doc.get_pages();
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = doc
+            .get_dictionary(pages[&1])
+            .and_then(|p| p.get(b"Resources"))
+            .and_then(|r| r.as_reference())
+            .unwrap();
+        let mut resources = doc.get_dictionary(resources_id).unwrap().clone();
+        resources
+            .get_mut(b"Font")
+            .and_then(|f| f.as_dict_mut())
+            .unwrap()
+            .set("F2", lopdf::Object::Reference(font));
+        let root_id = doc
+            .get_dictionary(pages[&1])
+            .and_then(|p| p.get(b"Parent"))
+            .and_then(|p| p.as_reference())
+            .unwrap();
+        doc.get_dictionary_mut(root_id)
+            .unwrap()
+            .set("Resources", resources);
+        for page_id in pages.values() {
+            doc.get_dictionary_mut(*page_id).unwrap().remove(b"Resources");
+        }
+        doc.change_page_content(pages[&2], b"BT /F2 12 Tf 100 700 Td (Mono) Tj ET".to_vec())
+            .unwrap();
+        doc.save(&input_path).unwrap();
+
+        prune_page_resources(input_path.to_str().unwrap(), output_path.to_str().unwrap(), None)
+            .unwrap();
+
+        let pruned = Document::load(&output_path).unwrap();
+        let root_id = pruned
+            .get_dictionary(pruned.get_pages()[&1])
+            .and_then(|p| p.get(b"Parent"))
+            .and_then(|p| p.as_reference())
+            .unwrap();
+        let fonts = pruned
+            .get_dictionary(root_id)
+            .and_then(|p| p.get(b"Resources"))
+            .and_then(|r| r.as_dict())
+            .and_then(|r| r.get(b"Font"))
+            .and_then(|f| f.as_dict())
+            .unwrap();
+        assert!(fonts.has(b"F1") && fonts.has(b"F2"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::pdf::utils::{load_document, rebuild_clean};
+use crate::pdf::xobject::copy_page_as_form;
+use lopdf::{dictionary, Dictionary, Document, Object};
+use std::fs;
+use std::path::Path;
+
+// --- concatenate_pages_vertically: one continuous "scroll" page ---
+
+/// Stack every page top to bottom onto a single tall page, each drawn as a Form
+/// XObject. The page is as wide as the widest input; narrower pages are centered.
+/// Page rotation is not applied. Note that many viewers cap page sizes at 200 inches
+/// (14400 points), so very long documents may be clipped there.
+#[tauri::command]
+pub fn concatenate_pages_vertically(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    if pages.is_empty() {
+        return Err(format!("Document '{}' has no pages to concatenate.", path));
+    }
+
+    let mut new_doc = Document::with_version(doc.version.clone());
+    let mut forms = Vec::with_capacity(pages.len());
+    for (page_num, page_id) in &pages {
+        let form = copy_page_as_form(&doc, *page_id, &mut new_doc)
+            .map_err(|e| format!("Page {}: {}", page_num, e))?;
+        forms.push(form);
+    }
+
+    let width = forms
+        .iter()
+        .map(|(_, b)| b[2] - b[0])
+        .fold(0.0_f64, f64::max);
+    let height: f64 = forms.iter().map(|(_, b)| b[3] - b[1]).sum();
+
+    // --- Draw each form below the previous one ---
+    let mut xobjects = Dictionary::new();
+    let mut content = String::new();
+    let mut cursor = height;
+    for (i, (form_id, [x0, y0, x1, y1])) in forms.iter().enumerate() {
+        let name = format!("P{}", i + 1);
+        cursor -= y1 - y0;
+        let tx = (width - (x1 - x0)) / 2.0 - x0;
+        let ty = cursor - y0;
+        content.push_str(&format!(
+            "q 1 0 0 1 {:.3} {:.3} cm /{} Do Q\n",
+            tx, ty, name
+        ));
+        xobjects.set(name, Object::Reference(*form_id));
+    }
+
+    let pages_id = new_doc.new_object_id();
+    let content_id = new_doc.add_object(lopdf::Stream::new(dictionary! {}, content.into_bytes()));
+    let page_id = new_doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => Object::Reference(pages_id),
+        "MediaBox" => vec![0.into(), 0.into(), Object::Real(width as f32), Object::Real(height as f32)],
+        "Contents" => Object::Reference(content_id),
+        "Resources" => dictionary! { "XObject" => xobjects },
+    });
+    new_doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => Object::Array(vec![Object::Reference(page_id)]),
+            "Count" => Object::Integer(1),
+        }),
+    );
+    let catalog_id = new_doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => Object::Reference(pages_id),
+    });
+    new_doc.trailer.set("Root", Object::Reference(catalog_id));
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
+    new_doc.save(output_path).map_err(|e| {
+        format!(
+            "Failed to save concatenated PDF to '{}': {}",
+            output_path, e
+        )
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use crate::pdf::utils::rect_from_object;
+
+    #[test]
+    fn test_concatenate_pages_vertically() {
+        let (test_dir, output_dir) = setup_unique_paths("concat_vertical");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("scroll.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Scroll").unwrap();
+
+        let result =
+            concatenate_pages_vertically(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert!(result.is_ok(), "concatenate failed: {:?}", result.err());
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 1);
+        let page = doc.get_dictionary(pages[&1]).unwrap();
+        let media_box = rect_from_object(page.get(b"MediaBox").unwrap()).unwrap();
+        assert!((media_box[2] - 612.0).abs() < 0.01);
+        assert!((media_box[3] - 3.0 * 792.0).abs() < 0.01);
+
+        let xobjects = page
+            .get(b"Resources")
+            .and_then(|r| r.as_dict())
+            .and_then(|r| r.get(b"XObject"))
+            .and_then(|x| x.as_dict())
+            .unwrap();
+        assert_eq!(xobjects.len(), 3);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,769 @@
+use crate::pdf::utils::{get_inherited_attribute, load_document, rebuild_clean, rect_from_object};
+use lopdf::{content::Content, Document, Object, ObjectId};
+use std::fs;
+use std::path::Path;
+
+// Breathing room added around the detected content, in points
+const COMMON_BOX_MARGIN: f64 = 10.0;
+
+pub(crate) type Matrix = [f64; 6];
+
+pub(crate) const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// `m1 × m2` using PDF's row-vector convention, so `multiply(cm, ctm)` applies `cm` first.
+pub(crate) fn multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+pub(crate) fn transform(m: &Matrix, x: f64, y: f64) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+pub(crate) fn translate(tx: f64, ty: f64) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+fn operand_numbers(operands: &[Object]) -> Vec<f64> {
+    operands
+        .iter()
+        .filter_map(|o| o.as_float().ok().map(|v| v as f64))
+        .collect()
+}
+
+pub(crate) fn matrix_from_operands(operands: &[Object]) -> Option<Matrix> {
+    let n = operand_numbers(operands);
+    if n.len() != 6 {
+        return None;
+    }
+    Some([n[0], n[1], n[2], n[3], n[4], n[5]])
+}
+
+#[derive(Default)]
+struct Bounds(Option<[f64; 4]>);
+
+impl Bounds {
+    fn add_point(&mut self, (x, y): (f64, f64)) {
+        self.0 = Some(match self.0 {
+            Some([x0, y0, x1, y1]) => [x0.min(x), y0.min(y), x1.max(x), y1.max(y)],
+            None => [x, y, x, y],
+        });
+    }
+
+    /// Add a rectangle given in a local coordinate system mapped through `m`.
+    fn add_rect(&mut self, m: &Matrix, [x0, y0, x1, y1]: [f64; 4]) {
+        for (x, y) in [(x0, y0), (x1, y0), (x0, y1), (x1, y1)] {
+            self.add_point(transform(m, x, y));
+        }
+    }
+
+    fn merge(&mut self, other: &Bounds) {
+        if let Some([x0, y0, x1, y1]) = other.0 {
+            self.add_point((x0, y0));
+            self.add_point((x1, y1));
+        }
+    }
+}
+
+pub(crate) fn string_byte_len(operands: &[Object]) -> usize {
+    operands
+        .iter()
+        .map(|o| match o {
+            Object::String(bytes, _) => bytes.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Approximate the area painted by a page's content stream. Text is estimated from
+/// the font size (half an em per byte) since glyph widths are not looked up, and
+/// stroke widths are ignored, so the result is a close but not exact fit.
+pub(crate) fn page_content_bbox(
+    doc: &Document,
+    page_id: ObjectId,
+) -> Result<Option<[f64; 4]>, String> {
+    let raw = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read page content: {}", e))?;
+    let content =
+        Content::decode(&raw).map_err(|e| format!("Failed to decode page content: {}", e))?;
+    let xobjects = get_inherited_attribute(doc, page_id, b"Resources")
+        .and_then(|r| r.as_dict().ok())
+        .and_then(|r| r.get_deref(b"XObject", doc).ok())
+        .and_then(|x| x.as_dict().ok());
+
+    let mut bounds = Bounds::default();
+    let mut path = Bounds::default();
+    let mut ctm = IDENTITY;
+    let mut stack: Vec<Matrix> = Vec::new();
+    let (mut tm, mut tlm) = (IDENTITY, IDENTITY);
+    let (mut font_size, mut leading) = (0.0, 0.0);
+
+    for op in &content.operations {
+        let nums = operand_numbers(&op.operands);
+        match op.operator.as_str() {
+            "q" => stack.push(ctm),
+            "Q" => ctm = stack.pop().unwrap_or(IDENTITY),
+            "cm" => {
+                if let Some(m) = matrix_from_operands(&op.operands) {
+                    ctm = multiply(&m, &ctm);
+                }
+            }
+            // --- Paths ---
+            "m" | "l" if nums.len() == 2 => path.add_point(transform(&ctm, nums[0], nums[1])),
+            "c" | "v" | "y" => {
+                for pair in nums.chunks_exact(2) {
+                    path.add_point(transform(&ctm, pair[0], pair[1]));
+                }
+            }
+            "re" if nums.len() == 4 => path.add_rect(
+                &ctm,
+                [nums[0], nums[1], nums[0] + nums[2], nums[1] + nums[3]],
+            ),
+            "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+                bounds.merge(&path);
+                path = Bounds::default();
+            }
+            "n" => path = Bounds::default(),
+            // --- Text ---
+            "BT" => {
+                tm = IDENTITY;
+                tlm = IDENTITY;
+            }
+            "Tf" if nums.len() == 1 => font_size = nums[0],
+            "TL" if nums.len() == 1 => leading = nums[0],
+            "Td" | "TD" if nums.len() == 2 => {
+                if op.operator == "TD" {
+                    leading = -nums[1];
+                }
+                tlm = multiply(&translate(nums[0], nums[1]), &tlm);
+                tm = tlm;
+            }
+            "Tm" => {
+                if let Some(m) = matrix_from_operands(&op.operands) {
+                    tm = m;
+                    tlm = m;
+                }
+            }
+            "T*" => {
+                tlm = multiply(&translate(0.0, -leading), &tlm);
+                tm = tlm;
+            }
+            "Tj" | "'" | "\"" | "TJ" => {
+                if op.operator == "'" || op.operator == "\"" {
+                    tlm = multiply(&translate(0.0, -leading), &tlm);
+                    tm = tlm;
+                }
+                let byte_len = match op.operands.first() {
+                    Some(Object::Array(items)) if op.operator == "TJ" => string_byte_len(items),
+                    _ => string_byte_len(&op.operands),
+                };
+                let width = byte_len as f64 * font_size * 0.5;
+                if width > 0.0 {
+                    let text_to_page = multiply(&tm, &ctm);
+                    bounds.add_rect(&text_to_page, [0.0, -0.2 * font_size, width, font_size]);
+                    tm = multiply(&translate(width, 0.0), &tm);
+                }
+            }
+            // --- Images and forms ---
+            "Do" => {
+                let name = op.operands.first().and_then(|o| o.as_name().ok());
+                let xobject = name
+                    .and_then(|n| xobjects.and_then(|x| x.get_deref(n, doc).ok()))
+                    .and_then(|o| o.as_stream().ok());
+                let is_form = xobject
+                    .and_then(|s| s.dict.get(b"Subtype").and_then(|o| o.as_name()).ok())
+                    .map(|n| n == b"Form")
+                    .unwrap_or(false);
+                match xobject {
+                    Some(form) if is_form => {
+                        let matrix = form
+                            .dict
+                            .get(b"Matrix")
+                            .ok()
+                            .and_then(|m| m.as_array().ok())
+                            .and_then(|m| matrix_from_operands(m))
+                            .unwrap_or(IDENTITY);
+                        if let Some(bbox) = form.dict.get(b"BBox").ok().and_then(rect_from_object) {
+                            bounds.add_rect(&multiply(&matrix, &ctm), bbox);
+                        }
+                    }
+                    // Images are drawn into the unit square
+                    _ => bounds.add_rect(&ctm, [0.0, 0.0, 1.0, 1.0]),
+                }
+            }
+            "BI" => bounds.add_rect(&ctm, [0.0, 0.0, 1.0, 1.0]),
+            _ => {}
+        }
+    }
+
+    Ok(bounds.0)
+}
+
+// --- crop_to_common_box: one CropBox covering the content of every page ---
+
+/// Set the same CropBox on every page: the union of all pages' content plus a
+/// `COMMON_BOX_MARGIN` margin, clipped to each page's MediaBox.
+#[tauri::command]
+pub fn crop_to_common_box(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+
+    let mut union = Bounds::default();
+    for (page_num, page_id) in &pages {
+        let page_bounds =
+            page_content_bbox(&doc, *page_id).map_err(|e| format!("Page {}: {}", page_num, e))?;
+        union.merge(&Bounds(page_bounds));
+    }
+    let [x0, y0, x1, y1] = union
+        .0
+        .ok_or_else(|| format!("No visible content found in '{}' to crop to.", path))?;
+
+    let common = [
+        x0 - COMMON_BOX_MARGIN,
+        y0 - COMMON_BOX_MARGIN,
+        x1 + COMMON_BOX_MARGIN,
+        y1 + COMMON_BOX_MARGIN,
+    ];
+    for (page_num, page_id) in &pages {
+        // A CropBox may not extend past the MediaBox, so clip it to each page
+        let media = get_inherited_attribute(&doc, *page_id, b"MediaBox")
+            .and_then(rect_from_object)
+            .map(normalize_rect)
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let clipped = [
+            common[0].max(media[0]),
+            common[1].max(media[1]),
+            common[2].min(media[2]),
+            common[3].min(media[3]),
+        ];
+        // Content entirely outside this page: leave the whole page visible
+        let crop_box = if clipped[0] < clipped[2] && clipped[1] < clipped[3] {
+            clipped
+        } else {
+            media
+        };
+        doc.get_dictionary_mut(*page_id)
+            .map_err(|e| format!("Failed to access page {}: {}", page_num, e))?
+            .set(
+                "CropBox",
+                Object::Array(crop_box.iter().map(|v| Object::Real(*v as f32)).collect()),
+            );
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save cropped PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+// --- get_print_boxes: prepress boxes for each page ---
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct PrintBoxes {
+    pub page: u32,
+    pub trimbox: Option<[f64; 4]>,
+    pub bleedbox: Option<[f64; 4]>,
+    pub artbox: Option<[f64; 4]>,
+}
+
+/// Report the TrimBox, BleedBox and ArtBox of every page, following inheritance through
+/// the page tree. Boxes the page does not define are `None` rather than their defaults.
+#[tauri::command]
+pub fn get_print_boxes(path: &str) -> Result<Vec<PrintBoxes>, String> {
+    let doc = load_document(path, None)?;
+    let read_box = |page_id: ObjectId, key: &[u8]| {
+        get_inherited_attribute(&doc, page_id, key)
+            .and_then(|o| doc.dereference(o).ok())
+            .and_then(|(_, o)| rect_from_object(o))
+    };
+
+    Ok(doc
+        .get_pages()
+        .into_iter()
+        .map(|(page, page_id)| PrintBoxes {
+            page,
+            trimbox: read_box(page_id, b"TrimBox"),
+            bleedbox: read_box(page_id, b"BleedBox"),
+            artbox: read_box(page_id, b"ArtBox"),
+        })
+        .collect())
+}
+
+// --- set_print_boxes: write TrimBox/BleedBox for prepress ---
+
+fn normalize_rect([x0, y0, x1, y1]: [f64; 4]) -> [f64; 4] {
+    [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)]
+}
+
+fn rect_contains(outer: [f64; 4], inner: [f64; 4]) -> bool {
+    let (outer, inner) = (normalize_rect(outer), normalize_rect(inner));
+    inner[0] >= outer[0] && inner[1] >= outer[1] && inner[2] <= outer[2] && inner[3] <= outer[3]
+}
+
+/// Set the TrimBox and/or BleedBox on the given pages (all pages when `pages` is
+/// empty). Each page must satisfy trim ⊆ bleed ⊆ media, where a box that is not being
+/// set is checked as currently defined on the page.
+#[tauri::command]
+pub fn set_print_boxes(
+    path: &str,
+    pages: Vec<u32>,
+    trim: Option<[f64; 4]>,
+    bleed: Option<[f64; 4]>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if trim.is_none() && bleed.is_none() {
+        return Err("Specify a trim box, a bleed box or both.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+    let all_pages = doc.get_pages();
+    let targets: Vec<u32> = if pages.is_empty() {
+        all_pages.keys().copied().collect()
+    } else {
+        pages
+    };
+
+    let mut updates = Vec::with_capacity(targets.len());
+    for page_num in targets {
+        let page_id = *all_pages.get(&page_num).ok_or_else(|| {
+            format!(
+                "Page number {} not found in document '{}' (which has {} pages).",
+                page_num,
+                path,
+                all_pages.len()
+            )
+        })?;
+        let current = |key: &[u8]| {
+            get_inherited_attribute(&doc, page_id, key)
+                .and_then(|o| doc.dereference(o).ok())
+                .and_then(|(_, o)| rect_from_object(o))
+        };
+        let media = current(b"MediaBox").unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let effective_bleed = bleed.or_else(|| current(b"BleedBox"));
+        let effective_trim = trim.or_else(|| current(b"TrimBox"));
+
+        if let Some(bleed_box) = effective_bleed {
+            if !rect_contains(media, bleed_box) {
+                return Err(format!(
+                    "BleedBox {:?} on page {} extends beyond the MediaBox {:?}.",
+                    bleed_box, page_num, media
+                ));
+            }
+        }
+        if let Some(trim_box) = effective_trim {
+            let outer = effective_bleed.unwrap_or(media);
+            if !rect_contains(outer, trim_box) {
+                return Err(format!(
+                    "TrimBox {:?} on page {} extends beyond the {} {:?}.",
+                    trim_box,
+                    page_num,
+                    if effective_bleed.is_some() {
+                        "BleedBox"
+                    } else {
+                        "MediaBox"
+                    },
+                    outer
+                ));
+            }
+        }
+        updates.push(page_id);
+    }
+
+    let as_array = |rect: [f64; 4]| {
+        Object::Array(
+            normalize_rect(rect)
+                .iter()
+                .map(|v| Object::Real(*v as f32))
+                .collect(),
+        )
+    };
+    for page_id in updates {
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|e| format!("Failed to access page {:?}: {}", page_id, e))?;
+        if let Some(trim_box) = trim {
+            page.set("TrimBox", as_array(trim_box));
+        }
+        if let Some(bleed_box) = bleed {
+            page.set("BleedBox", as_array(bleed_box));
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+// --- crop_pages: set the CropBox of selected pages ---
+
+/// Set the CropBox of the given pages (all pages when `pages` is empty) to
+/// `crop_box` as `[x0, y0, x1, y1]`. With `set_media_box` the MediaBox is set to the
+/// same rectangle, which discards the cropped area instead of only hiding it.
+#[tauri::command]
+pub fn crop_pages(
+    path: &str,
+    pages: Vec<u32>,
+    crop_box: [f64; 4],
+    output_path: &str,
+    set_media_box: Option<bool>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let [x0, y0, x1, y1] = crop_box;
+    if x1 <= x0 || y1 <= y0 {
+        return Err(format!(
+            "Invalid crop box {:?}: x1 must be greater than x0 and y1 greater than y0.",
+            crop_box
+        ));
+    }
+
+    let mut doc = load_document(path, None)?;
+    let all_pages = doc.get_pages();
+    let targets: Vec<ObjectId> = if pages.is_empty() {
+        all_pages.values().copied().collect()
+    } else {
+        pages
+            .iter()
+            .map(|page_num| {
+                all_pages.get(page_num).copied().ok_or_else(|| {
+                    format!(
+                        "Page number {} not found in document '{}' (which has {} pages).",
+                        page_num,
+                        path,
+                        all_pages.len()
+                    )
+                })
+            })
+            .collect::<Result<_, String>>()?
+    };
+
+    let rect = Object::Array(crop_box.iter().map(|v| Object::Real(*v as f32)).collect());
+    for page_id in targets {
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|e| format!("Failed to access page {:?}: {}", page_id, e))?;
+        page.set("CropBox", rect.clone());
+        if set_media_box.unwrap_or(false) {
+            page.set("MediaBox", rect.clone());
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save cropped PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::{Dictionary, Stream};
+
+    fn set_page_content(doc: &mut Document, page_id: ObjectId, content: &str) {
+        let content_id =
+            doc.add_object(Stream::new(Dictionary::new(), content.as_bytes().to_vec()));
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Contents", Object::Reference(content_id));
+    }
+
+    #[test]
+    fn test_page_content_bbox_follows_ctm() {
+        let (test_dir, output_dir) = setup_unique_paths("content_bbox");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Box").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        set_page_content(&mut doc, page_id, "q 2 0 0 2 50 50 cm 0 0 100 50 re f Q");
+
+        let bbox = page_content_bbox(&doc, page_id).unwrap().unwrap();
+        assert_eq!(bbox, [50.0, 50.0, 250.0, 150.0]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_crop_to_common_box_shares_crop_box() {
+        let (test_dir, output_dir) = setup_unique_paths("crop_common_box");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("cropped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Crop").unwrap();
+
+        // Page 2 has content far from the text on the other pages
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&2).unwrap();
+        set_page_content(&mut doc, page_id, "72 72 100 100 re f");
+        doc.save(&input).unwrap();
+
+        let result = crop_to_common_box(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert!(
+            result.is_ok(),
+            "crop_to_common_box failed: {:?}",
+            result.err()
+        );
+
+        let out = Document::load(&output).unwrap();
+        let boxes: Vec<[f64; 4]> = out
+            .get_pages()
+            .values()
+            .map(|id| {
+                rect_from_object(out.get_dictionary(*id).unwrap().get(b"CropBox").unwrap()).unwrap()
+            })
+            .collect();
+        assert_eq!(boxes.len(), 3);
+        assert!(boxes.iter().all(|b| *b == boxes[0]));
+
+        // Covers both the rectangle on page 2 and the text at (100, 700)
+        let [x0, y0, x1, y1] = boxes[0];
+        assert!(x0 <= 72.0 - COMMON_BOX_MARGIN + 0.01 && y0 <= 72.0 - COMMON_BOX_MARGIN + 0.01);
+        assert!(x1 > 172.0 && y1 >= 712.0);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_crop_to_common_box_stays_within_media_box() {
+        let (test_dir, output_dir) = setup_unique_paths("crop_common_box_media");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("cropped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Crop").unwrap();
+
+        // Page 2 draws from just inside the lower-left corner, so the margin would
+        // push the box past the MediaBox
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&2).unwrap();
+        set_page_content(&mut doc, page_id, "2 2 100 100 re f");
+        doc.save(&input).unwrap();
+
+        crop_to_common_box(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+
+        let out = Document::load(&output).unwrap();
+        for id in out.get_pages().values() {
+            let crop = rect_from_object(out.get_dictionary(*id).unwrap().get(b"CropBox").unwrap())
+                .unwrap();
+            assert_eq!((crop[0], crop[1]), (0.0, 0.0));
+            assert!(rect_contains([0.0, 0.0, 612.0, 792.0], crop));
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_get_print_boxes_reads_trim_box() {
+        let (test_dir, output_dir) = setup_unique_paths("print_boxes");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Press").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&2).unwrap();
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("TrimBox", vec![9.into(), 9.into(), 603.into(), 783.into()]);
+        doc.save(&input).unwrap();
+
+        let boxes = get_print_boxes(input.to_str().unwrap()).unwrap();
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].trimbox, None);
+        assert_eq!(
+            boxes[1],
+            PrintBoxes {
+                page: 2,
+                trimbox: Some([9.0, 9.0, 603.0, 783.0]),
+                bleedbox: None,
+                artbox: None,
+            }
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_set_print_boxes_round_trips() {
+        let (test_dir, output_dir) = setup_unique_paths("set_print_boxes");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("press.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Press").unwrap();
+
+        let result = set_print_boxes(
+            input.to_str().unwrap(),
+            vec![1],
+            Some([18.0, 18.0, 594.0, 774.0]),
+            Some([9.0, 9.0, 603.0, 783.0]),
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "set_print_boxes failed: {:?}", result.err());
+
+        let boxes = get_print_boxes(output.to_str().unwrap()).unwrap();
+        assert_eq!(boxes[0].trimbox, Some([18.0, 18.0, 594.0, 774.0]));
+        assert_eq!(boxes[0].bleedbox, Some([9.0, 9.0, 603.0, 783.0]));
+        assert_eq!(boxes[1].trimbox, None);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_set_print_boxes_rejects_trim_outside_bleed() {
+        let (test_dir, output_dir) = setup_unique_paths("set_print_boxes_invalid");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("press.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Press").unwrap();
+
+        let err = set_print_boxes(
+            input.to_str().unwrap(),
+            vec![],
+            Some([0.0, 0.0, 612.0, 792.0]),
+            Some([9.0, 9.0, 603.0, 783.0]),
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap_err();
+        assert!(err.contains("TrimBox"), "unexpected error: {}", err);
+        assert!(!output.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_crop_pages_sets_crop_box() {
+        let (test_dir, output_dir) = setup_unique_paths("crop_pages");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("cropped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Crop").unwrap();
+
+        crop_pages(
+            input.to_str().unwrap(),
+            vec![1],
+            [36.0, 36.0, 576.0, 756.0],
+            output.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        let page_box = |page: u32, key: &[u8]| {
+            doc.get_dictionary(pages[&page])
+                .unwrap()
+                .get(key)
+                .ok()
+                .and_then(rect_from_object)
+        };
+        assert_eq!(page_box(1, b"CropBox"), Some([36.0, 36.0, 576.0, 756.0]));
+        assert_eq!(page_box(2, b"CropBox"), None);
+        assert_eq!(
+            get_inherited_attribute(&doc, pages[&1], b"MediaBox").and_then(rect_from_object),
+            Some([0.0, 0.0, 612.0, 792.0])
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_crop_pages_media_box_and_validation() {
+        let (test_dir, output_dir) = setup_unique_paths("crop_pages_media");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("cropped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Crop").unwrap();
+
+        let err = crop_pages(
+            input.to_str().unwrap(),
+            vec![],
+            [100.0, 0.0, 50.0, 100.0],
+            output.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            err.contains("Invalid crop box"),
+            "unexpected error: {}",
+            err
+        );
+        assert!(!output.exists());
+
+        crop_pages(
+            input.to_str().unwrap(),
+            vec![],
+            [0.0, 0.0, 300.0, 400.0],
+            output.to_str().unwrap(),
+            Some(true),
+            None,
+        )
+        .unwrap();
+        let doc = Document::load(&output).unwrap();
+        for page_id in doc.get_pages().into_values() {
+            let page = doc.get_dictionary(page_id).unwrap();
+            for key in [b"CropBox".as_slice(), b"MediaBox"] {
+                assert_eq!(
+                    page.get(key).ok().and_then(rect_from_object),
+                    Some([0.0, 0.0, 300.0, 400.0])
+                );
+            }
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::pdf::utils::load_document;
+use lopdf::{Document, Object};
+
+/// The permanent (first) element of the trailer `/ID` array, hex-encoded.
+pub(crate) fn document_id(doc: &Document) -> Option<String> {
+    let id = doc.trailer.get(b"ID").ok()?.as_array().ok()?.first()?;
+    match id {
+        Object::String(bytes, _) if !bytes.is_empty() => {
+            Some(bytes.iter().map(|b| format!("{:02X}", b)).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Group files that share the same permanent `/ID`, i.e. copies or later revisions of
+/// the same original document. Groups keep the order in which files were given; a file
+/// without an ID is placed in a group of its own.
+#[tauri::command]
+pub fn match_by_id(paths: Vec<&str>) -> Result<Vec<Vec<String>>, String> {
+    let mut groups: Vec<(Option<String>, Vec<String>)> = Vec::new();
+    for path in paths {
+        let doc = load_document(path, None)?;
+
+        match document_id(&doc) {
+            Some(id) => match groups.iter_mut().find(|(key, _)| key.as_ref() == Some(&id)) {
+                Some((_, files)) => files.push(path.to_string()),
+                None => groups.push((Some(id), vec![path.to_string()])),
+            },
+            None => groups.push((None, vec![path.to_string()])),
+        }
+    }
+
+    Ok(groups.into_iter().map(|(_, files)| files).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    fn set_id(path: &str, permanent: &[u8], changing: &[u8]) {
+        let mut doc = Document::load(path).unwrap();
+        doc.trailer.set(
+            "ID",
+            vec![
+                Object::String(permanent.to_vec(), lopdf::StringFormat::Hexadecimal),
+                Object::String(changing.to_vec(), lopdf::StringFormat::Hexadecimal),
+            ],
+        );
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_match_by_id_groups_shared_ids() {
+        let (test_dir, output_dir) = setup_unique_paths("match_by_id");
+        let original = test_dir.join("original.pdf");
+        let revision = test_dir.join("revision.pdf");
+        let unrelated = test_dir.join("unrelated.pdf");
+        let no_id = test_dir.join("no_id.pdf");
+        create_minimal_pdf(original.to_str().unwrap(), 1, "Orig").unwrap();
+        create_minimal_pdf(revision.to_str().unwrap(), 2, "Rev").unwrap();
+        create_minimal_pdf(unrelated.to_str().unwrap(), 1, "Other").unwrap();
+        create_minimal_pdf(no_id.to_str().unwrap(), 1, "NoId").unwrap();
+        // The revision keeps the permanent ID but has a new changing ID
+        set_id(original.to_str().unwrap(), b"\x01\x02\x03\x04", b"\xAA\xAA");
+        set_id(revision.to_str().unwrap(), b"\x01\x02\x03\x04", b"\xBB\xBB");
+        set_id(
+            unrelated.to_str().unwrap(),
+            b"\x09\x09\x09\x09",
+            b"\xAA\xAA",
+        );
+
+        let paths = vec![
+            original.to_str().unwrap(),
+            unrelated.to_str().unwrap(),
+            no_id.to_str().unwrap(),
+            revision.to_str().unwrap(),
+        ];
+        let groups = match_by_id(paths).unwrap();
+
+        assert_eq!(
+            groups,
+            vec![
+                vec![
+                    original.to_str().unwrap().to_string(),
+                    revision.to_str().unwrap().to_string()
+                ],
+                vec![unrelated.to_str().unwrap().to_string()],
+                vec![no_id.to_str().unwrap().to_string()],
+            ]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::pdf::annotations::resolve_destination_page;
+use crate::pdf::parser::decode_pdf_string;
+use crate::pdf::utils::load_document;
+use lopdf::{Dictionary, Document, Object};
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct DestInfo {
+    /// Destination name, or `None` for a plain page target.
+    pub name: Option<String>,
+    pub page: u32,
+}
+
+/// Collect every key/value pair of a `/Names` name tree, descending through `/Kids`.
+fn collect_name_tree<'a>(
+    doc: &'a Document,
+    node: &'a Dictionary,
+    depth: u32,
+    out: &mut Vec<(String, &'a Object)>,
+) {
+    if depth > 32 {
+        return;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(|o| o.as_array()) {
+        for pair in names.chunks(2) {
+            if let [Object::String(name, _), value] = pair {
+                out.push((decode_pdf_string(name), value));
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(|o| o.as_array()) {
+        for kid in kids {
+            if let Ok(kid_dict) = kid.as_reference().and_then(|id| doc.get_dictionary(id)) {
+                collect_name_tree(doc, kid_dict, depth + 1, out);
+            }
+        }
+    }
+}
+
+/// Resolve a named destination's value (the destination array, or a dictionary
+/// wrapping it in `/D`) to a 1-based page number.
+fn named_destination_page(doc: &Document, value: &Object) -> Option<u32> {
+    let value = doc.dereference(value).map(|(_, o)| o).ok()?;
+    match value {
+        Object::Dictionary(d) => resolve_destination_page(doc, d.get(b"D").ok()?),
+        other => resolve_destination_page(doc, other),
+    }
+}
+
+/// List every destination an internal link can point to: the named destinations (from
+/// the `/Names /Dests` tree and the older catalog `/Dests` dictionary, sorted by name)
+/// followed by one unnamed entry per page. Named destinations that do not resolve to a
+/// page are skipped.
+#[tauri::command]
+pub fn list_destinations(path: &str) -> Result<Vec<DestInfo>, String> {
+    let doc = load_document(path, None)?;
+    let catalog = doc
+        .catalog()
+        .map_err(|e| format!("Failed to read document catalog: {}", e))?;
+
+    let mut named = Vec::new();
+    if let Ok(tree) = catalog
+        .get_deref(b"Names", &doc)
+        .and_then(|o| o.as_dict())
+        .and_then(|names| names.get_deref(b"Dests", &doc))
+        .and_then(|o| o.as_dict())
+    {
+        collect_name_tree(&doc, tree, 0, &mut named);
+    }
+    if let Ok(dests) = catalog.get_deref(b"Dests", &doc).and_then(|o| o.as_dict()) {
+        for (name, value) in dests.iter() {
+            named.push((String::from_utf8_lossy(name).to_string(), value));
+        }
+    }
+
+    let mut destinations: Vec<DestInfo> = named
+        .into_iter()
+        .filter_map(|(name, value)| {
+            named_destination_page(&doc, value).map(|page| DestInfo {
+                name: Some(name),
+                page,
+            })
+        })
+        .collect();
+    destinations.sort_by(|a, b| a.name.cmp(&b.name));
+    destinations.dedup_by(|a, b| a.name == b.name);
+
+    destinations.extend(
+        doc.get_pages()
+            .into_keys()
+            .map(|page| DestInfo { name: None, page }),
+    );
+
+    Ok(destinations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_list_destinations_named_and_pages() {
+        let (test_dir, output_dir) = setup_unique_paths("list_destinations");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Dest").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let tree = doc.add_object(dictionary! {
+            "Names" => vec![
+                Object::string_literal("chapter2"),
+                vec![Object::Reference(pages[&2]), "Fit".into()].into(),
+            ],
+        });
+        doc.catalog_mut()
+            .unwrap()
+            .set("Names", dictionary! { "Dests" => Object::Reference(tree) });
+        doc.save(&input).unwrap();
+
+        let destinations = list_destinations(input.to_str().unwrap()).unwrap();
+        assert_eq!(
+            destinations,
+            vec![
+                DestInfo {
+                    name: Some("chapter2".to_string()),
+                    page: 2,
+                },
+                DestInfo {
+                    name: None,
+                    page: 1
+                },
+                DestInfo {
+                    name: None,
+                    page: 2
+                },
+                DestInfo {
+                    name: None,
+                    page: 3
+                },
+            ]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
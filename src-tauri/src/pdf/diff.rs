@@ -0,0 +1,108 @@
+use crate::pdf::hash::page_content_hash;
+use crate::pdf::utils::load_document;
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct PageDiff {
+    pub page: u32,
+    pub identical: bool,
+    /// `Some("a")` or `Some("b")` when the page only exists in that document.
+    pub only_in: Option<String>,
+}
+
+/// Compare two versions of a document page by page. Pages are aligned by number and
+/// compared by normalized content hash, so re-saving without edits reports no change.
+/// Pages beyond the shorter document are reported as non-identical with `only_in` set.
+#[tauri::command]
+pub fn diff_pages(path_a: &str, path_b: &str) -> Result<Vec<PageDiff>, String> {
+    let doc_a = load_document(path_a, None)?;
+    let doc_b = load_document(path_b, None)?;
+    let pages_a = doc_a.get_pages();
+    let pages_b = doc_b.get_pages();
+
+    let page_count = pages_a.len().max(pages_b.len()) as u32;
+    let mut diffs = Vec::with_capacity(page_count as usize);
+    for page in 1..=page_count {
+        let diff = match (pages_a.get(&page), pages_b.get(&page)) {
+            (Some(&id_a), Some(&id_b)) => {
+                let hash_a = page_content_hash(&doc_a, id_a, false)
+                    .map_err(|e| format!("Page {} of '{}': {}", page, path_a, e))?;
+                let hash_b = page_content_hash(&doc_b, id_b, false)
+                    .map_err(|e| format!("Page {} of '{}': {}", page, path_b, e))?;
+                PageDiff {
+                    page,
+                    identical: hash_a == hash_b,
+                    only_in: None,
+                }
+            }
+            (Some(_), None) => PageDiff {
+                page,
+                identical: false,
+                only_in: Some("a".to_string()),
+            },
+            _ => PageDiff {
+                page,
+                identical: false,
+                only_in: Some("b".to_string()),
+            },
+        };
+        diffs.push(diff);
+    }
+
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Document;
+
+    #[test]
+    fn test_diff_pages_flags_changed_page() {
+        let (test_dir, output_dir) = setup_unique_paths("diff_pages");
+        let a = test_dir.join("a.pdf");
+        let b = test_dir.join("b.pdf");
+        create_minimal_pdf(a.to_str().unwrap(), 3, "Draft").unwrap();
+        create_minimal_pdf(b.to_str().unwrap(), 3, "Draft").unwrap();
+
+        let mut doc = Document::load(&b).unwrap();
+        let page_id = doc.get_pages()[&2];
+        doc.change_page_content(page_id, b"BT /F1 12 Tf 100 700 Td (Edited) Tj ET".to_vec())
+            .unwrap();
+        doc.save(&b).unwrap();
+
+        let diffs = diff_pages(a.to_str().unwrap(), b.to_str().unwrap()).unwrap();
+        let changed: Vec<u32> = diffs
+            .iter()
+            .filter(|d| !d.identical)
+            .map(|d| d.page)
+            .collect();
+        assert_eq!(changed, vec![2]);
+        assert!(diffs.iter().all(|d| d.only_in.is_none()));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_diff_pages_reports_extra_pages() {
+        let (test_dir, output_dir) = setup_unique_paths("diff_pages_extra");
+        let a = test_dir.join("a.pdf");
+        let b = test_dir.join("b.pdf");
+        create_minimal_pdf(a.to_str().unwrap(), 2, "Draft").unwrap();
+        create_minimal_pdf(b.to_str().unwrap(), 3, "Draft").unwrap();
+
+        let diffs = diff_pages(a.to_str().unwrap(), b.to_str().unwrap()).unwrap();
+        assert_eq!(diffs.len(), 3);
+        assert!(diffs[0].identical && diffs[1].identical);
+        assert_eq!(
+            diffs[2],
+            PageDiff {
+                page: 3,
+                identical: false,
+                only_in: Some("b".to_string()),
+            }
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
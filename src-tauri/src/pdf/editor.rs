@@ -1,6 +1,8 @@
-use lopdf::{Document, Object, content::Content};
+use crate::pdf::utils::{load_document, page_geometry, rebuild_clean};
+use lopdf::{content::Content, dictionary, Document, Object, ObjectId, Stream};
 use docx_rs::*;
-use std::fs::File;
+use std::fs::{self, File};
+use std::path::Path;
 
 #[tauri::command]
 pub fn replace_text_block(
@@ -9,6 +11,7 @@ pub fn replace_text_block(
     old_text: &str,
     new_text: &str,
     output_path: &str,
+    clean: Option<bool>,
 ) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     let pages = doc.get_pages();
@@ -55,6 +58,10 @@ pub fn replace_text_block(
     let encoded_content = content.encode().map_err(|e| e.to_string())?;
     doc.change_page_content(page_id, encoded_content).map_err(|e| e.to_string())?;
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -78,6 +85,367 @@ pub fn pdf_to_docx(path: &str, output_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub(crate) fn format_matrix_value(value: f64) -> String {
+    let formatted = format!("{:.6}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Wrap each target page's content in `q <matrix> cm ... Q`, applying an arbitrary
+/// affine transform (scale, rotate, skew, shift) to everything drawn on the page.
+/// An empty `pages` list means every page.
+#[tauri::command]
+pub fn transform_content(
+    path: &str,
+    pages: Vec<u32>,
+    matrix: [f64; 6],
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if matrix.iter().any(|v| !v.is_finite()) {
+        return Err("Transform matrix values must be finite numbers.".to_string());
+    }
+    let determinant = matrix[0] * matrix[3] - matrix[1] * matrix[2];
+    if determinant.abs() < f64::EPSILON {
+        return Err("Transform matrix is degenerate (determinant is 0).".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+
+    let page_ids = doc.get_pages();
+    let targets: Vec<(u32, ObjectId)> = if pages.is_empty() {
+        page_ids.into_iter().collect()
+    } else {
+        pages
+            .iter()
+            .map(|p| {
+                page_ids.get(p).map(|id| (*p, *id)).ok_or_else(|| {
+                    format!(
+                        "Page number {} not found in document '{}' (which has {} pages).",
+                        p,
+                        path,
+                        page_ids.len()
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+
+    let cm = matrix
+        .iter()
+        .map(|v| format_matrix_value(*v))
+        .collect::<Vec<_>>()
+        .join(" ");
+    for (page_num, page_id) in targets {
+        let original = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        let mut content = format!("q {} cm\n", cm).into_bytes();
+        content.extend_from_slice(&original);
+        content.extend_from_slice(b"\nQ");
+        doc.change_page_content(page_id, content)
+            .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))?;
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Page size for newly created pages, in PostScript points. `MatchPage` copies the
+/// size of the neighbouring page.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PaperSize {
+    A3,
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Custom { width: f64, height: f64 },
+    MatchPage,
+}
+
+impl PaperSize {
+    fn dimensions(self) -> Option<(f64, f64)> {
+        match self {
+            PaperSize::A3 => Some((842.0, 1191.0)),
+            PaperSize::A4 => Some((595.0, 842.0)),
+            PaperSize::A5 => Some((420.0, 595.0)),
+            PaperSize::Letter => Some((612.0, 792.0)),
+            PaperSize::Legal => Some((612.0, 1008.0)),
+            PaperSize::Custom { width, height } => Some((width, height)),
+            PaperSize::MatchPage => None,
+        }
+    }
+
+    /// MediaBox for a new page; `neighbour` is the page whose size `MatchPage` copies.
+    pub(crate) fn media_box(
+        self,
+        doc: &Document,
+        neighbour: Option<ObjectId>,
+    ) -> Result<[f64; 4], String> {
+        if let Some((width, height)) = self.dimensions() {
+            if !(width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite()) {
+                return Err(format!(
+                    "Invalid page size {}x{}: width and height must be positive.",
+                    width, height
+                ));
+            }
+            return Ok([0.0, 0.0, width, height]);
+        }
+        neighbour
+            .map(|page_id| page_geometry(doc, page_id).0)
+            .ok_or_else(|| "Cannot match the page size of an empty document.".to_string())
+    }
+}
+
+/// Insert an empty page after the 1-based `after_page` (0 prepends), placing it in the
+/// same Pages node as its neighbour and updating `/Count` up the tree.
+pub(crate) fn insert_empty_page(
+    doc: &mut Document,
+    after_page: u32,
+    media_box: [f64; 4],
+) -> Result<ObjectId, String> {
+    let pages = doc.get_pages();
+    let (parent_id, index) = match pages.get(&after_page.max(1)) {
+        Some(&neighbour_id) => {
+            let parent_id = doc
+                .get_dictionary(neighbour_id)
+                .and_then(|page| page.get(b"Parent"))
+                .and_then(|p| p.as_reference())
+                .map_err(|e| format!("Page {} has no parent node: {}", after_page, e))?;
+            let position = doc
+                .get_dictionary(parent_id)
+                .and_then(|node| node.get(b"Kids"))
+                .and_then(|kids| kids.as_array())
+                .map_err(|e| format!("Failed to read page tree: {}", e))?
+                .iter()
+                .position(|kid| kid.as_reference().ok() == Some(neighbour_id))
+                .ok_or_else(|| format!("Page {} is missing from its parent's Kids.", after_page))?;
+            (
+                parent_id,
+                if after_page == 0 {
+                    position
+                } else {
+                    position + 1
+                },
+            )
+        }
+        None if pages.is_empty() => {
+            let root_id = doc
+                .catalog()
+                .and_then(|c| c.get(b"Pages"))
+                .and_then(|p| p.as_reference())
+                .map_err(|e| format!("Failed to find page tree: {}", e))?;
+            (root_id, 0)
+        }
+        None => {
+            return Err(format!(
+                "Page number {} not found in document (which has {} pages).",
+                after_page,
+                pages.len()
+            ))
+        }
+    };
+
+    let content_id = doc.add_object(Stream::new(dictionary! {}, Vec::new()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => Object::Reference(parent_id),
+        "MediaBox" => media_box.iter().map(|v| Object::Real(*v as f32)).collect::<Vec<_>>(),
+        "Contents" => Object::Reference(content_id),
+        "Resources" => dictionary! {},
+    });
+
+    doc.get_dictionary_mut(parent_id)
+        .and_then(|node| node.get_mut(b"Kids"))
+        .and_then(|kids| kids.as_array_mut())
+        .map_err(|e| format!("Failed to read page tree: {}", e))?
+        .insert(index, Object::Reference(page_id));
+
+    let mut node_id = Some(parent_id);
+    while let Some(id) = node_id {
+        let node = doc
+            .get_dictionary_mut(id)
+            .map_err(|e| format!("Failed to read page tree: {}", e))?;
+        let count = node.get(b"Count").and_then(|c| c.as_i64()).unwrap_or(0);
+        node.set("Count", count + 1);
+        node_id = node.get(b"Parent").and_then(|p| p.as_reference()).ok();
+    }
+
+    Ok(page_id)
+}
+
+/// Insert one empty page after `after_page` (0 prepends), e.g. to leave room for notes
+/// or to even out a document for duplex printing.
+#[tauri::command]
+pub fn insert_blank_page(
+    path: &str,
+    after_page: u32,
+    size: PaperSize,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    if after_page as usize > pages.len() {
+        return Err(format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            after_page,
+            path,
+            pages.len()
+        ));
+    }
+    let neighbour = pages.get(&after_page.max(1)).copied();
+    let media_box = size.media_box(&doc, neighbour)?;
+    insert_empty_page(&mut doc, after_page, media_box)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Insert a `width` × `height` empty page after each listed page (0 prepends).
+/// Positions refer to the original document, so `[1, 3]` puts blanks after what were
+/// pages 1 and 3; a position listed twice gets two blanks.
+#[tauri::command]
+pub fn insert_blank_pages(
+    path: &str,
+    after_pages: Vec<u32>,
+    width: f64,
+    height: f64,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if after_pages.is_empty() {
+        return Err("The list of positions for blank pages cannot be empty.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+    let media_box = PaperSize::Custom { width, height }.media_box(&doc, None)?;
+
+    let page_count = doc.get_pages().len();
+    let mut positions = after_pages;
+    if let Some(&after_page) = positions.iter().find(|&&p| p as usize > page_count) {
+        return Err(format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            after_page, path, page_count
+        ));
+    }
+    // Inserting from the back keeps the earlier positions pointing at original pages
+    positions.sort_unstable_by(|a, b| b.cmp(a));
+    for after_page in positions {
+        insert_empty_page(&mut doc, after_page, media_box)?;
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Append blank pages until the page count is a multiple of `multiple` (e.g. 2 for
+/// duplex, 4 for booklet imposition). `PaperSize::MatchPage` sizes the blanks like the
+/// last page. Returns how many blanks were added.
+#[tauri::command]
+pub fn pad_to_multiple(
+    path: &str,
+    multiple: u32,
+    size: PaperSize,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<u32, String> {
+    if multiple < 1 {
+        return Err("Page multiple must be at least 1.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let page_count = pages.len() as u32;
+    let missing = (multiple - page_count % multiple) % multiple;
+    let last_page = pages.values().next_back().copied();
+    if missing > 0 {
+        let media_box = size.media_box(&doc, last_page)?;
+        for offset in 0..missing {
+            insert_empty_page(&mut doc, page_count + offset, media_box)?;
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(missing)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,4 +465,243 @@ mod tests {
 
         teardown_unique_paths(&test_dir, &output_dir);
     }
+
+    #[test]
+    fn test_transform_content_prepends_cm() {
+        let (test_dir, output_dir) = setup_unique_paths("transform_content");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("scaled.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Scale").unwrap();
+
+        let result = transform_content(
+            input.to_str().unwrap(),
+            vec![2],
+            [0.5, 0.0, 0.0, 0.5, 10.0, 20.0],
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "transform_content failed: {:?}", result.err());
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        let scaled = Content::decode(&doc.get_page_content(pages[&2]).unwrap()).unwrap();
+        assert_eq!(scaled.operations[0].operator, "q");
+        assert_eq!(scaled.operations[1].operator, "cm");
+        let operands: Vec<f32> = scaled.operations[1]
+            .operands
+            .iter()
+            .map(|o| o.as_float().unwrap())
+            .collect();
+        assert_eq!(operands, vec![0.5, 0.0, 0.0, 0.5, 10.0, 20.0]);
+        assert_eq!(scaled.operations.last().unwrap().operator, "Q");
+
+        // Untargeted pages keep their original content
+        let untouched = Content::decode(&doc.get_page_content(pages[&1]).unwrap()).unwrap();
+        assert_eq!(untouched.operations[0].operator, "BT");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_transform_content_rejects_degenerate_matrix() {
+        let (test_dir, output_dir) = setup_unique_paths("transform_content_degenerate");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("flat.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Flat").unwrap();
+
+        let result = transform_content(
+            input.to_str().unwrap(),
+            vec![],
+            [1.0, 2.0, 2.0, 4.0, 0.0, 0.0],
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("degenerate"));
+        assert!(!output.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_insert_blank_page_after_first() {
+        let (test_dir, output_dir) = setup_unique_paths("insert_blank_page");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("padded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Blank").unwrap();
+
+        let result = insert_blank_page(
+            input.to_str().unwrap(),
+            1,
+            PaperSize::A4,
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "insert_blank_page failed: {:?}", result.err());
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 3);
+        let blank = Content::decode(&doc.get_page_content(pages[&2]).unwrap()).unwrap();
+        assert!(blank.operations.is_empty());
+        assert_eq!(page_geometry(&doc, pages[&2]).0, [0.0, 0.0, 595.0, 842.0]);
+        let last = Content::decode(&doc.get_page_content(pages[&3]).unwrap()).unwrap();
+        assert_eq!(last.operations[0].operator, "BT");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_insert_blank_page_prepends_matching_size() {
+        let (test_dir, output_dir) = setup_unique_paths("insert_blank_page_prepend");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("padded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Blank").unwrap();
+
+        insert_blank_page(
+            input.to_str().unwrap(),
+            0,
+            PaperSize::MatchPage,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 3);
+        assert!(doc.get_page_content(pages[&1]).unwrap().is_empty());
+        assert_eq!(page_geometry(&doc, pages[&1]).0, [0.0, 0.0, 612.0, 792.0]);
+
+        let result = insert_blank_page(
+            input.to_str().unwrap(),
+            3,
+            PaperSize::A4,
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("not found"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_insert_blank_pages_at_several_positions() {
+        let (test_dir, output_dir) = setup_unique_paths("insert_blank_pages");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("blanks.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Blank").unwrap();
+
+        insert_blank_pages(
+            input.to_str().unwrap(),
+            vec![1],
+            300.0,
+            400.0,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 3);
+        assert!(doc.get_page_content(pages[&2]).unwrap().is_empty());
+        assert_eq!(page_geometry(&doc, pages[&2]).0, [0.0, 0.0, 300.0, 400.0]);
+        assert!(doc.extract_text(&[3]).unwrap().contains("Blank-Page 2"));
+
+        insert_blank_pages(
+            input.to_str().unwrap(),
+            vec![2, 0, 1],
+            300.0,
+            400.0,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 5);
+        for blank in [1, 3, 5] {
+            assert!(doc.get_page_content(pages[&blank]).unwrap().is_empty());
+        }
+        assert!(doc.extract_text(&[2]).unwrap().contains("Blank-Page 1"));
+        assert!(doc.extract_text(&[4]).unwrap().contains("Blank-Page 2"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_insert_blank_pages_validates_input() {
+        let (test_dir, output_dir) = setup_unique_paths("insert_blank_pages_invalid");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("blanks.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Blank").unwrap();
+        let input = input.to_str().unwrap();
+        let output = output.to_str().unwrap();
+
+        let err = insert_blank_pages(input, vec![3], 300.0, 400.0, output, None).unwrap_err();
+        assert!(err.contains("Page number 3 not found"), "{}", err);
+        let err = insert_blank_pages(input, vec![1], 0.0, 400.0, output, None).unwrap_err();
+        assert!(err.contains("Invalid page size"), "{}", err);
+        assert!(insert_blank_pages(input, vec![], 300.0, 400.0, output, None).is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_pad_to_multiple_appends_blanks() {
+        let (test_dir, output_dir) = setup_unique_paths("pad_to_multiple");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("booklet.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 5, "Pad").unwrap();
+
+        let added = pad_to_multiple(
+            input.to_str().unwrap(),
+            4,
+            PaperSize::MatchPage,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(added, 3);
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 8);
+        let fifth = Content::decode(&doc.get_page_content(pages[&5]).unwrap()).unwrap();
+        assert_eq!(fifth.operations[0].operator, "BT");
+        for page_num in 6..=8 {
+            assert!(doc.get_page_content(pages[&page_num]).unwrap().is_empty());
+            assert_eq!(page_geometry(&doc, pages[&page_num]).0, [0.0, 0.0, 612.0, 792.0]);
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_pad_to_multiple_validates_multiple() {
+        let (test_dir, output_dir) = setup_unique_paths("pad_to_multiple_zero");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("padded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 4, "Pad").unwrap();
+
+        let result = pad_to_multiple(
+            input.to_str().unwrap(),
+            0,
+            PaperSize::A4,
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("at least 1"));
+
+        let added = pad_to_multiple(
+            input.to_str().unwrap(),
+            2,
+            PaperSize::A4,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(added, 0);
+        assert_eq!(Document::load(&output).unwrap().get_pages().len(), 4);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
@@ -1,23 +1,36 @@
-use crate::pdf::utils::manual_deep_copy;
-use lopdf::{dictionary, Document, Object};
+use crate::pdf::merger::assemble_page_sequence;
+use crate::pdf::outline::{read_outline_entries, write_outline_entries, OutlineEntry};
+use crate::pdf::utils::{load_document, manual_deep_copy, page_geometry, rebuild_clean};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
 use std::fs;
 use std::path::Path;
 
 // --- extract_pdf_page Function using Manual Deep Copy ---
 
+/// Copy a single page into a new PDF. `rotation`, when given, turns the extracted page
+/// by that many degrees (a multiple of 90) on top of its current rotation.
 #[tauri::command]
-pub fn extract_pdf_page(path: &str, page_number: u32, output_path: &str) -> Result<(), String> {
+pub fn extract_pdf_page(
+    path: &str,
+    page_number: u32,
+    output_path: &str,
+    rotation: Option<i32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
     // --- Input Validation & Dir Creation ---
     if page_number == 0 {
         return Err("Page number must be 1-based (greater than 0).".to_string());
     }
-    let input_path = Path::new(path);
-    if !input_path.exists() {
-        return Err(format!("Input file not found: {}", path));
-    }
-    if !input_path.is_file() {
-        return Err(format!("Input path is not a file: {}", path));
+    if let Some(rotation) = rotation {
+        if rotation % 90 != 0 {
+            return Err(format!(
+                "Invalid rotation angle {}. Must be a multiple of 90.",
+                rotation
+            ));
+        }
     }
+    let doc = load_document(path, None)?;
+
     if let Some(parent_dir) = Path::new(output_path).parent() {
         if !parent_dir.exists() {
             fs::create_dir_all(parent_dir).map_err(|e| {
@@ -30,10 +43,6 @@ pub fn extract_pdf_page(path: &str, page_number: u32, output_path: &str) -> Resu
         }
     }
 
-    // --- Load Original Document ---
-    // Map potential LopdfError to String for the command's return type
-    let doc = Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
-
     // --- Find Target Page Object ID ---
     let source_pages_map = doc.get_pages();
     let target_page_id = *source_pages_map.get(&page_number).ok_or_else(|| {
@@ -88,6 +97,13 @@ pub fn extract_pdf_page(path: &str, page_number: u32, output_path: &str) -> Resu
 
         page_dict.set("Parent", Object::Reference(new_pages_id));
 
+        // Start from the effective rotation, which may have been inherited from the
+        // source page tree and is not carried over by the copy
+        if let Some(rotation) = rotation {
+            let current = page_geometry(&doc, target_page_id).1;
+            page_dict.set("Rotate", (current + rotation as i64).rem_euclid(360));
+        }
+
         // Note: We rely on the second pass of manual_deep_copy (update_references_recursive)
         // to have correctly updated the /Resources reference (if it was a reference)
         // or the references *within* the /Resources dictionary (if it was inline).
@@ -131,8 +147,15 @@ pub fn extract_pdf_page(path: &str, page_number: u32, output_path: &str) -> Resu
         .trailer
         .set("Root", Object::Reference(new_catalog_id));
 
-    // --- Compress and Save ---
+    // --- Drop Orphans, Compress and Save ---
+    // The deep copy followed the page's old /Parent into the source page tree, pulling
+    // in every other page; nothing references them once Parent is rewritten
+    new_doc.prune_objects();
     new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
     new_doc.save(output_path).map_err(|e| {
         format!(
             "Failed to save extracted page PDF to '{}': {}",
@@ -143,6 +166,238 @@ pub fn extract_pdf_page(path: &str, page_number: u32, output_path: &str) -> Resu
     Ok(())
 }
 
+// --- extract_pdf_range: a contiguous block of pages ---
+
+/// Extract pages `start..=end` (1-based, inclusive) into a new document, in order.
+/// Unlike `extract_range_complete`, only the pages and what they reference are copied.
+#[tauri::command]
+pub fn extract_pdf_range(
+    path: &str,
+    start: u32,
+    end: u32,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if start == 0 {
+        return Err("Start page must be 1-based (greater than 0).".to_string());
+    }
+    if end < start {
+        return Err(format!(
+            "Invalid page range {}-{}: the end page must not be before the start page.",
+            start, end
+        ));
+    }
+    let doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    if end as usize > pages.len() {
+        return Err(format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            end,
+            path,
+            pages.len()
+        ));
+    }
+    let sequence: Vec<(usize, ObjectId)> = (start..=end).map(|p| (0, pages[&p])).collect();
+    let mut new_doc = assemble_page_sequence(std::slice::from_ref(&doc), &sequence, max_pages)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
+    new_doc
+        .save(output_path)
+        .map_err(|e| format!("Failed to save extracted range to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+// --- extract_range_complete: chapter extraction keeping outline, labels and Info ---
+
+/// Flatten a page-label number tree into (0-based start index, label dictionary) pairs.
+fn collect_page_labels(
+    doc: &Document,
+    node: &Object,
+    depth: u32,
+    out: &mut Vec<(i64, Dictionary)>,
+) {
+    let Some(node) = doc
+        .dereference(node)
+        .ok()
+        .and_then(|(_, n)| n.as_dict().ok())
+    else {
+        return;
+    };
+    if depth > 32 {
+        return;
+    }
+    if let Ok(nums) = node.get_deref(b"Nums", doc).and_then(|n| n.as_array()) {
+        for pair in nums.chunks_exact(2) {
+            let label = doc
+                .dereference(&pair[1])
+                .ok()
+                .and_then(|(_, l)| l.as_dict().ok());
+            if let (Ok(start), Some(label)) = (pair[0].as_i64(), label) {
+                out.push((start, label.clone()));
+            }
+        }
+    }
+    if let Ok(kids) = node.get_deref(b"Kids", doc).and_then(|k| k.as_array()) {
+        for kid in kids {
+            collect_page_labels(doc, kid, depth + 1, out);
+        }
+    }
+}
+
+/// The `/PageLabels` tree for pages `start..=end` of `doc` renumbered from 0, so each
+/// page keeps the label it had in the full document. `None` if the source has none.
+fn subset_page_labels(doc: &Document, start: u32, end: u32) -> Option<Dictionary> {
+    let root = doc.catalog().ok()?.get(b"PageLabels").ok()?;
+    let mut ranges = Vec::new();
+    collect_page_labels(doc, root, 0, &mut ranges);
+    ranges.sort_by_key(|(index, _)| *index);
+
+    let (first, last) = (start as i64 - 1, end as i64 - 1);
+    let mut nums = Vec::new();
+    for (i, (index, label)) in ranges.iter().enumerate() {
+        let next = ranges.get(i + 1).map_or(i64::MAX, |(n, _)| *n);
+        if next <= first || *index > last {
+            continue;
+        }
+        let mut label = label.clone();
+        if *index < first {
+            // The range began before the subset: continue its numbering
+            let st = label.get(b"St").and_then(|s| s.as_i64()).unwrap_or(1);
+            label.set("St", st + (first - index));
+        }
+        nums.push(Object::Integer(index.max(&first) - first));
+        nums.push(Object::Dictionary(label));
+    }
+    if nums.is_empty() {
+        return None;
+    }
+    Some(dictionary! { "Nums" => nums })
+}
+
+/// Keep outline entries that point into `start..=end`, renumbered to the subset.
+/// Children of a dropped entry are promoted to its level.
+fn outline_in_range(entries: Vec<OutlineEntry>, start: u32, end: u32) -> Vec<OutlineEntry> {
+    let mut kept = Vec::new();
+    for mut entry in entries {
+        let children = outline_in_range(std::mem::take(&mut entry.children), start, end);
+        match entry.page {
+            Some(page) if (start..=end).contains(&page) => {
+                entry.page = Some(page - start + 1);
+                entry.children = children;
+                kept.push(entry);
+            }
+            _ => kept.extend(children),
+        }
+    }
+    kept
+}
+
+/// Extract pages `start..=end` (1-based, inclusive) as a new document that keeps the
+/// bookmarks pointing into the range, the page labels of those pages, and the
+/// document information (Title, Author, ...).
+#[tauri::command]
+pub fn extract_range_complete(
+    path: &str,
+    start: u32,
+    end: u32,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if start == 0 || end < start {
+        return Err(format!(
+            "Invalid page range {}-{}: pages are 1-based and the start must not exceed the end.",
+            start, end
+        ));
+    }
+    let doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    if end as usize > pages.len() {
+        return Err(format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            end,
+            path,
+            pages.len()
+        ));
+    }
+    let sequence: Vec<(usize, ObjectId)> = (start..=end).map(|p| (0, pages[&p])).collect();
+    let mut new_doc = assemble_page_sequence(std::slice::from_ref(&doc), &sequence, max_pages)?;
+
+    let new_pages = new_doc.get_pages();
+    let outline = outline_in_range(read_outline_entries(&doc), start, end);
+    write_outline_entries(&mut new_doc, &outline, &new_pages)?;
+
+    if let Some(labels) = subset_page_labels(&doc, start, end) {
+        new_doc
+            .catalog_mut()
+            .map_err(|e| format!("Failed to access document catalog: {}", e))?
+            .set("PageLabels", labels);
+    }
+
+    if let Ok(info) = doc
+        .trailer
+        .get_deref(b"Info", &doc)
+        .and_then(|i| i.as_dict())
+    {
+        let mut copied = Dictionary::new();
+        for (key, value) in info.iter() {
+            if let Ok((_, value)) = doc.dereference(value) {
+                if !matches!(
+                    value,
+                    Object::Dictionary(_) | Object::Array(_) | Object::Stream(_)
+                ) {
+                    copied.set(key.clone(), value.clone());
+                }
+            }
+        }
+        let info_id = new_doc.add_object(copied);
+        new_doc.trailer.set("Info", Object::Reference(info_id));
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
+    new_doc
+        .save(output_path)
+        .map_err(|e| format!("Failed to save extracted range to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
 // --- Tests ---
 #[cfg(test)]
 mod tests {
@@ -212,6 +467,8 @@ mod tests {
             env.input_path_str(),
             page_to_extract,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(
@@ -243,6 +500,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extract_pdf_page_drops_other_pages() {
+        let env = TestEnvironment::new("extract_prune");
+        let output_path = env.output_path("page_3.pdf");
+
+        extract_pdf_page(
+            env.input_path_str(),
+            3,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let source_doc = Document::load(env.input_path_str()).unwrap();
+        let output_doc = Document::load(&output_path).unwrap();
+        assert_eq!(output_doc.get_pages().len(), 1);
+        // A copy that kept the source page tree would hold at least as many objects
+        assert!(output_doc.objects.len() < source_doc.objects.len());
+        assert_eq!(
+            output_doc.extract_text(&[1]).unwrap().trim(),
+            "Sample-Page 3"
+        );
+    }
+
     #[test]
     fn test_extract_pdf_page_not_found() {
         let env = TestEnvironment::new("extract_not_found");
@@ -253,6 +535,8 @@ mod tests {
             env.input_path_str(),
             page_to_extract,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_err());
@@ -277,6 +561,8 @@ mod tests {
             env.input_path_str(),
             page_to_extract,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_err());
@@ -304,6 +590,8 @@ mod tests {
             bad_input_path.to_str().unwrap(),
             page_to_extract,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_err());
@@ -330,6 +618,8 @@ mod tests {
             not_pdf_path.to_str().unwrap(),
             page_to_extract,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_err());
@@ -344,4 +634,199 @@ mod tests {
         }
         assert!(!output_path.exists() || Document::load(&output_path).is_err());
     }
+
+    #[test]
+    fn test_extract_range_complete_keeps_labels_outline_and_title() {
+        use crate::pdf::outline::{get_pdf_outline, set_pdf_outline, OutlineItem};
+        use lopdf::dictionary;
+
+        let env = TestEnvironment::new("extract_range_complete");
+        let plain = env.test_dir.join("plain.pdf");
+        let book = env.test_dir.join("book.pdf");
+        create_minimal_pdf(plain.to_str().unwrap(), 5, "Book").unwrap();
+        let items = vec![
+            OutlineItem {
+                title: "Preface".to_string(),
+                page: Some(1),
+                children: vec![],
+            },
+            OutlineItem {
+                title: "Chapter 1".to_string(),
+                page: Some(3),
+                children: vec![],
+            },
+        ];
+        set_pdf_outline(plain.to_str().unwrap(), items, book.to_str().unwrap(), None).unwrap();
+
+        // Roman numerals for the front matter, then 1, 2, 3
+        let mut doc = Document::load(&book).unwrap();
+        let info_id =
+            doc.add_object(dictionary! { "Title" => Object::string_literal("The Book") });
+        doc.trailer.set("Info", Object::Reference(info_id));
+        doc.catalog_mut().unwrap().set(
+            "PageLabels",
+            dictionary! {
+                "Nums" => vec![
+                    0.into(), dictionary! { "S" => "r" }.into(),
+                    2.into(), dictionary! { "S" => "D" }.into(),
+                ],
+            },
+        );
+        doc.save(&book).unwrap();
+
+        let output_path = env.output_path("chapter.pdf");
+        let result = extract_range_complete(
+            book.to_str().unwrap(),
+            2,
+            4,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "extract_range_complete failed: {:?}",
+            result.err()
+        );
+
+        let extracted = Document::load(&output_path).unwrap();
+        assert_eq!(extracted.get_pages().len(), 3);
+
+        let labels = extracted
+            .catalog()
+            .unwrap()
+            .get_deref(b"PageLabels", &extracted)
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let nums = labels.get(b"Nums").unwrap().as_array().unwrap();
+        assert_eq!(nums.len(), 4);
+        // Page ii continues the roman range, then decimal from the next page
+        assert_eq!(nums[0].as_i64().unwrap(), 0);
+        let roman = nums[1].as_dict().unwrap();
+        assert_eq!(roman.get(b"S").unwrap().as_name().unwrap(), b"r");
+        assert_eq!(roman.get(b"St").unwrap().as_i64().unwrap(), 2);
+        assert_eq!(nums[2].as_i64().unwrap(), 1);
+
+        let outline = get_pdf_outline(output_path.to_str().unwrap()).unwrap();
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].title, "Chapter 1");
+        let dest_page = extracted
+            .catalog()
+            .and_then(|c| c.get_deref(b"Outlines", &extracted))
+            .and_then(|o| o.as_dict())
+            .and_then(|o| o.get_deref(b"First", &extracted))
+            .and_then(|f| f.as_dict())
+            .and_then(|f| f.get(b"Dest"))
+            .and_then(|d| d.as_array())
+            .map(|d| d[0].as_reference().unwrap())
+            .unwrap();
+        assert_eq!(dest_page, extracted.get_pages()[&2]);
+
+        let info = extracted
+            .trailer
+            .get_deref(b"Info", &extracted)
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        assert_eq!(info.get(b"Title").unwrap().as_str().unwrap(), b"The Book");
+    }
+
+    #[test]
+    fn test_extract_range_complete_invalid_range() {
+        let env = TestEnvironment::new("extract_range_invalid");
+        let output_path = env.output_path("range.pdf");
+
+        let output = output_path.to_str().unwrap();
+        let reversed = extract_range_complete(env.input_path_str(), 3, 2, output, None, None);
+        assert!(reversed.unwrap_err().contains("Invalid page range"));
+        let past_end = extract_range_complete(env.input_path_str(), 2, 4, output, None, None);
+        assert!(past_end.unwrap_err().contains("Page number 4 not found"));
+        assert!(!output_path.exists());
+    }
+
+    #[test]
+    fn test_extract_pdf_page_with_rotation() {
+        let env = TestEnvironment::new("extract_rotated");
+        let output_path = env.output_path("page_1_rotated.pdf");
+
+        extract_pdf_page(env.input_path_str(), 1, output_path.to_str().unwrap(), Some(90), None)
+            .unwrap();
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let page_id = output_doc.get_pages()[&1];
+        let rotate = output_doc
+            .get_dictionary(page_id)
+            .and_then(|p| p.get(b"Rotate"))
+            .and_then(|r| r.as_i64())
+            .unwrap();
+        assert_eq!(rotate, 90);
+
+        let invalid = extract_pdf_page(
+            env.input_path_str(),
+            1,
+            env.output_path("bad.pdf").to_str().unwrap(),
+            Some(45),
+            None,
+        );
+        assert!(invalid.unwrap_err().contains("multiple of 90"));
+    }
+
+    #[test]
+    fn test_extract_pdf_range_keeps_order() {
+        let env = TestEnvironment::new("extract_range");
+        let output_path = env.output_path("pages_2_3.pdf");
+
+        extract_pdf_range(
+            env.input_path_str(),
+            2,
+            3,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let output_doc = Document::load(&output_path).unwrap();
+        assert_eq!(output_doc.get_pages().len(), 2);
+        assert!(output_doc
+            .extract_text(&[1])
+            .unwrap()
+            .contains("Sample-Page 2"));
+        assert!(output_doc
+            .extract_text(&[2])
+            .unwrap()
+            .contains("Sample-Page 3"));
+    }
+
+    #[test]
+    fn test_extract_pdf_range_invalid_bounds() {
+        let env = TestEnvironment::new("extract_range_invalid");
+        let output_path = env.output_path("bad.pdf");
+        let output = output_path.to_str().unwrap();
+
+        assert!(extract_pdf_range(env.input_path_str(), 0, 2, output, None, None)
+            .unwrap_err()
+            .contains("1-based"));
+        assert!(extract_pdf_range(env.input_path_str(), 3, 2, output, None, None)
+            .unwrap_err()
+            .contains("Invalid page range 3-2"));
+        assert!(extract_pdf_range(env.input_path_str(), 2, 4, output, None, None)
+            .unwrap_err()
+            .contains("Page number 4 not found"));
+        assert!(!output_path.exists());
+    }
+
+    #[test]
+    fn test_extract_pdf_range_respects_max_pages() {
+        let env = TestEnvironment::new("extract_range_max_pages");
+        let output_path = env.output_path("capped.pdf");
+        let output = output_path.to_str().unwrap();
+
+        let result = extract_pdf_range(env.input_path_str(), 1, 3, output, Some(2), None);
+        assert_eq!(result.unwrap_err(), "Result would have 3 pages, limit is 2.");
+        let result = extract_range_complete(env.input_path_str(), 1, 3, output, Some(2), None);
+        assert_eq!(result.unwrap_err(), "Result would have 3 pages, limit is 2.");
+        assert!(!output_path.exists());
+    }
 }
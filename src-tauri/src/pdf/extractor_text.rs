@@ -0,0 +1,602 @@
+use crate::pdf::utils::load_document;
+use lopdf::{content::Content, Dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashMap};
+
+// Share of unreadable characters above which a page's text is reported as garbled
+const GARBLED_RATIO: f64 = 0.25;
+
+// Widest bfrange accepted, one full two-byte codespace; larger ranges come from broken
+// or hostile CMaps and would allocate billions of entries
+const MAX_BFRANGE_SPAN: u32 = 0xFFFF;
+
+#[derive(serde::Serialize)]
+pub struct TextIssue {
+    pub page: u32,
+    pub issue: String,
+}
+
+fn is_composite_font(font: &Dictionary) -> bool {
+    font.get(b"Subtype")
+        .and_then(|o| o.as_name())
+        .map(|n| n == b"Type0")
+        .unwrap_or(false)
+}
+
+enum CMapToken {
+    Hex(Vec<u8>),
+    Word(String),
+    ArrayStart,
+    ArrayEnd,
+}
+
+fn tokenize_cmap(data: &[u8]) -> Vec<CMapToken> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'<' if data.get(i + 1) == Some(&b'<') => i += 2,
+            b'>' if data.get(i + 1) == Some(&b'>') => i += 2,
+            b'<' => {
+                let end = data[i..].iter().position(|&b| b == b'>').map(|p| i + p);
+                let end = match end {
+                    Some(e) => e,
+                    None => break,
+                };
+                let digits: Vec<u8> = data[i + 1..end]
+                    .iter()
+                    .copied()
+                    .filter(|b| b.is_ascii_hexdigit())
+                    .collect();
+                let bytes = digits
+                    .chunks(2)
+                    .filter_map(|pair| {
+                        let s = std::str::from_utf8(pair).ok()?;
+                        // A trailing odd digit is padded with 0 per the spec
+                        u8::from_str_radix(&format!("{:0<2}", s), 16).ok()
+                    })
+                    .collect();
+                tokens.push(CMapToken::Hex(bytes));
+                i = end + 1;
+            }
+            b'[' => {
+                tokens.push(CMapToken::ArrayStart);
+                i += 1;
+            }
+            b']' => {
+                tokens.push(CMapToken::ArrayEnd);
+                i += 1;
+            }
+            b'%' => {
+                while i < data.len() && data[i] != b'\n' && data[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                // Literal strings only appear in the CIDSystemInfo header
+                let mut depth = 0;
+                while i < data.len() {
+                    match data[i] {
+                        b'\\' => i += 1,
+                        b'(' => depth += 1,
+                        b')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                while i < data.len()
+                    && !data[i].is_ascii_whitespace()
+                    && !b"<>[]()%".contains(&data[i])
+                {
+                    i += 1;
+                }
+                tokens.push(CMapToken::Word(
+                    String::from_utf8_lossy(&data[start..i]).to_string(),
+                ));
+            }
+        }
+    }
+    tokens
+}
+
+fn code_from_bytes(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+}
+
+fn utf16_from_bytes(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| ((pair[0] as u16) << 8) | pair.get(1).copied().unwrap_or(0) as u16)
+        .collect()
+}
+
+/// Read the `bfchar` and `bfrange` mappings of a ToUnicode CMap into code → text.
+pub(crate) fn parse_tounicode_cmap(data: &[u8]) -> HashMap<u32, String> {
+    let tokens = tokenize_cmap(data);
+    let mut map = HashMap::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            CMapToken::Word(w) if w == "beginbfchar" => {
+                i += 1;
+                while let (Some(CMapToken::Hex(src)), Some(CMapToken::Hex(dst))) =
+                    (tokens.get(i), tokens.get(i + 1))
+                {
+                    map.insert(
+                        code_from_bytes(src),
+                        String::from_utf16_lossy(&utf16_from_bytes(dst)),
+                    );
+                    i += 2;
+                }
+            }
+            CMapToken::Word(w) if w == "beginbfrange" => {
+                i += 1;
+                while let (Some(CMapToken::Hex(lo)), Some(CMapToken::Hex(hi))) =
+                    (tokens.get(i), tokens.get(i + 1))
+                {
+                    let (lo, hi) = (code_from_bytes(lo), code_from_bytes(hi));
+                    let valid = hi >= lo && hi - lo <= MAX_BFRANGE_SPAN;
+                    match tokens.get(i + 2) {
+                        Some(CMapToken::Hex(_)) if !valid => i += 3,
+                        Some(CMapToken::Hex(dst)) => {
+                            let base = utf16_from_bytes(dst);
+                            for (offset, code) in (lo..=hi).enumerate() {
+                                let mut units = base.clone();
+                                if let Some(last) = units.last_mut() {
+                                    *last = last.wrapping_add(offset as u16);
+                                }
+                                map.insert(code, String::from_utf16_lossy(&units));
+                            }
+                            i += 3;
+                        }
+                        Some(CMapToken::ArrayStart) => {
+                            i += 3;
+                            let mut code = lo;
+                            while let Some(CMapToken::Hex(dst)) = tokens.get(i) {
+                                if valid && code <= hi {
+                                    map.insert(
+                                        code,
+                                        String::from_utf16_lossy(&utf16_from_bytes(dst)),
+                                    );
+                                }
+                                code = code.saturating_add(1);
+                                i += 1;
+                            }
+                            if let Some(CMapToken::ArrayEnd) = tokens.get(i) {
+                                i += 1;
+                            }
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    map
+}
+
+/// What to emit for codes the font gives no text for.
+#[derive(Clone, Copy)]
+enum Unmapped {
+    /// One U+FFFD per code, so unreadable text can be detected.
+    Replacement,
+    /// The code's bytes read as Latin-1.
+    RawBytes,
+}
+
+fn unmapped_text(unmapped: Unmapped, bytes: &[u8], code_width: usize) -> String {
+    match unmapped {
+        Unmapped::Replacement => "\u{FFFD}".repeat(bytes.len().div_ceil(code_width)),
+        Unmapped::RawBytes => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Decode a shown string, preferring the font's ToUnicode map over its encoding.
+/// Codes that cannot be mapped come back as `unmapped` says.
+fn decode_shown_string(
+    doc: &Document,
+    font: Option<&Dictionary>,
+    to_unicode: Option<&HashMap<u32, String>>,
+    bytes: &[u8],
+    unmapped: Unmapped,
+) -> String {
+    let font = match font {
+        Some(f) => f,
+        None => return unmapped_text(unmapped, bytes, 1),
+    };
+    let code_width = if is_composite_font(font) { 2 } else { 1 };
+    if let Some(cmap) = to_unicode {
+        return bytes
+            .chunks(code_width)
+            .map(|code| match cmap.get(&code_from_bytes(code)) {
+                Some(text) => text.clone(),
+                None => unmapped_text(unmapped, code, code_width),
+            })
+            .collect();
+    }
+    // CID fonts carry no meaningful text mapping without a ToUnicode CMap
+    if code_width == 2 {
+        return unmapped_text(unmapped, bytes, code_width);
+    }
+    font.get_font_encoding(doc)
+        .and_then(|encoding| Document::decode_text(&encoding, bytes))
+        .unwrap_or_else(|_| unmapped_text(unmapped, bytes, 1))
+}
+
+/// Extract a page's text without giving up on fonts lopdf cannot decode; their
+/// glyphs are replaced with U+FFFD instead of failing the whole page.
+pub(crate) fn extract_page_text(doc: &Document, page_id: ObjectId) -> Result<String, String> {
+    page_text(doc, page_id, Unmapped::Replacement)
+}
+
+/// Walk the page's content streams (concatenated and decompressed by lopdf) and
+/// collect the strings shown by `Tj`, `TJ`, `'` and `"`, one line per text line.
+fn page_text(doc: &Document, page_id: ObjectId, unmapped: Unmapped) -> Result<String, String> {
+    let fonts: BTreeMap<Vec<u8>, &Dictionary> = doc
+        .get_page_fonts(page_id)
+        .map_err(|e| format!("Failed to read page fonts: {}", e))?;
+    let raw = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read page content: {}", e))?;
+    let content =
+        Content::decode(&raw).map_err(|e| format!("Failed to decode page content: {}", e))?;
+
+    let to_unicode: BTreeMap<&[u8], HashMap<u32, String>> = fonts
+        .iter()
+        .filter_map(|(name, font)| {
+            let stream = font.get_deref(b"ToUnicode", doc).ok()?.as_stream().ok()?;
+            let data = stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone());
+            Some((name.as_slice(), parse_tounicode_cmap(&data)))
+        })
+        .collect();
+
+    let mut text = String::new();
+    let mut current_font: Option<&Dictionary> = None;
+    let mut current_cmap: Option<&HashMap<u32, String>> = None;
+    for op in &content.operations {
+        match op.operator.as_str() {
+            "Tf" => {
+                let name = op.operands.first().and_then(|o| o.as_name().ok());
+                current_font = name.and_then(|n| fonts.get(n).copied());
+                current_cmap = name.and_then(|n| to_unicode.get(n));
+            }
+            "Tj" | "'" | "\"" | "TJ" => {
+                if op.operator != "Tj" && op.operator != "TJ" && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                for operand in &op.operands {
+                    match operand {
+                        Object::String(bytes, _) => text.push_str(&decode_shown_string(
+                            doc,
+                            current_font,
+                            current_cmap,
+                            bytes,
+                            unmapped,
+                        )),
+                        Object::Array(items) => {
+                            for item in items {
+                                match item {
+                                    Object::String(bytes, _) => {
+                                        text.push_str(&decode_shown_string(
+                                            doc,
+                                            current_font,
+                                            current_cmap,
+                                            bytes,
+                                            unmapped,
+                                        ))
+                                    }
+                                    // Large negative kerning is how most producers space words
+                                    other
+                                        if other
+                                            .as_float()
+                                            .map(|v| v < -100.0)
+                                            .unwrap_or(false) =>
+                                    {
+                                        text.push(' ')
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "ET" | "T*" | "Td" | "TD" if !text.is_empty() && !text.ends_with('\n') => {
+                text.push('\n')
+            }
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+/// Return the text shown on one page (1-based). Fonts are decoded through their
+/// ToUnicode map or their standard/WinAnsi encoding; bytes neither can map are
+/// returned as Latin-1 characters.
+#[tauri::command]
+pub fn extract_text(path: &str, page_number: u32) -> Result<String, String> {
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    let page_id = *pages.get(&page_number).ok_or_else(|| {
+        format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            page_number,
+            path,
+            pages.len()
+        )
+    })?;
+    page_text(&doc, page_id, Unmapped::RawBytes)
+}
+
+fn is_unreadable(c: char) -> bool {
+    c == '\u{FFFD}' || (c.is_control() && !c.is_whitespace())
+}
+
+#[tauri::command]
+pub fn diagnose_text(path: &str) -> Result<Vec<TextIssue>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut issues = Vec::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let text = match extract_page_text(&doc, page_id) {
+            Ok(t) => t,
+            Err(e) => {
+                issues.push(TextIssue {
+                    page: page_num,
+                    issue: format!("Text could not be extracted: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let visible: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if visible.is_empty() {
+            continue;
+        }
+        let unreadable = visible.iter().filter(|c| is_unreadable(**c)).count();
+        let ratio = unreadable as f64 / visible.len() as f64;
+        if ratio > GARBLED_RATIO {
+            issues.push(TextIssue {
+                page: page_num,
+                issue: format!(
+                    "{:.0}% of extracted characters are unreadable; a font is likely missing its ToUnicode map, so search and copy will be unreliable.",
+                    ratio * 100.0
+                ),
+            });
+        }
+    }
+
+    Ok(issues)
+}
+
+// --- Text direction ---
+
+// Enough pages to judge the document without extracting all of a large file
+const DIRECTION_SAMPLE_PAGES: usize = 20;
+
+/// Hebrew, Arabic, Syriac, Thaana, NKo and their presentation forms.
+fn is_rtl_char(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}')
+}
+
+/// "rtl" when right-to-left letters outnumber other letters in `text`, else "ltr".
+pub(crate) fn dominant_direction(text: &str) -> &'static str {
+    let (rtl, ltr) =
+        text.chars()
+            .filter(|c| c.is_alphabetic())
+            .fold((0usize, 0usize), |(rtl, ltr), c| {
+                if is_rtl_char(c) {
+                    (rtl + 1, ltr)
+                } else {
+                    (rtl, ltr + 1)
+                }
+            });
+    if rtl > ltr {
+        "rtl"
+    } else {
+        "ltr"
+    }
+}
+
+/// Sample the text of the first pages and report whether the document is
+/// predominantly right-to-left ("rtl", Arabic or Hebrew script) or "ltr".
+#[tauri::command]
+pub fn detect_text_direction(path: &str) -> Result<String, String> {
+    let doc = load_document(path, None)?;
+
+    let sample: String = doc
+        .get_pages()
+        .into_values()
+        .take(DIRECTION_SAMPLE_PAGES)
+        // A page whose text can't be read doesn't vote either way
+        .filter_map(|page_id| extract_page_text(&doc, page_id).ok())
+        .collect();
+    Ok(dominant_direction(&sample).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_extract_page_text_clean_page() {
+        let (test_dir, output_dir) = setup_unique_paths("extract_page_text");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Clean").unwrap();
+
+        let doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        assert_eq!(
+            extract_page_text(&doc, page_id).unwrap().trim(),
+            "Clean-Page 1"
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_extract_text_command() {
+        let (test_dir, output_dir) = setup_unique_paths("extract_text_command");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Sample").unwrap();
+
+        let text = extract_text(input.to_str().unwrap(), 1).unwrap();
+        assert_eq!(text.trim(), "Sample-Page 1");
+        let err = extract_text(input.to_str().unwrap(), 3).unwrap_err();
+        assert!(err.contains("(which has 2 pages)"), "{}", err);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_extract_text_split_compressed_content() {
+        let (test_dir, output_dir) = setup_unique_paths("extract_text_split");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Split").unwrap();
+
+        // Replace the page content with two streams, the second Flate-compressed
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let first = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 72 720 Td (Intro) Tj ET".to_vec(),
+        ));
+        let mut second = Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 72 700 Td [(Out) -50 (ro)] TJ ET".to_vec(),
+        );
+        second.compress().unwrap();
+        let second = doc.add_object(second);
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Contents",
+            vec![Object::Reference(first), Object::Reference(second)],
+        );
+        doc.save(&input).unwrap();
+
+        let text = extract_text(input.to_str().unwrap(), 1).unwrap();
+        assert_eq!(text.lines().collect::<Vec<_>>(), vec!["Intro", "Outro"]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_diagnose_text_flags_cid_font_without_tounicode() {
+        let (test_dir, output_dir) = setup_unique_paths("diagnose_text");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Diag").unwrap();
+
+        // Page 2 gets its own resources with an Identity-H font and no ToUnicode
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&2).unwrap();
+        let cid_font = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "ABCDEF+SomeCIDFont",
+            "Encoding" => "Identity-H",
+        });
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F2 12 Tf 100 700 Td <0024004500460047> Tj ET".to_vec(),
+        ));
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("Contents", Object::Reference(content_id));
+        page.set(
+            "Resources",
+            dictionary! { "Font" => dictionary! { "F2" => Object::Reference(cid_font) } },
+        );
+        doc.save(&input).unwrap();
+
+        let issues = diagnose_text(input.to_str().unwrap()).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].page, 2);
+        assert!(issues[0].issue.contains("ToUnicode"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_parse_tounicode_cmap_ranges() {
+        let cmap = b"1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+            1 beginbfchar <0003> <0020> endbfchar\n\
+            2 beginbfrange <0024> <0026> <0041>\n<0030> <0031> [<0066006C> <00E9>] endbfrange";
+        let map = parse_tounicode_cmap(cmap);
+        assert_eq!(map.get(&0x03).map(String::as_str), Some(" "));
+        assert_eq!(map.get(&0x25).map(String::as_str), Some("B"));
+        assert_eq!(map.get(&0x30).map(String::as_str), Some("fl"));
+        assert_eq!(map.get(&0x31).map(String::as_str), Some("é"));
+    }
+
+    #[test]
+    fn test_parse_tounicode_cmap_skips_oversized_ranges() {
+        let cmap = b"3 beginbfrange <00000000> <FFFFFFFF> <0000>\n\
+            <00010000> <00FFFFFF> [<0058> <0059>]\n\
+            <0050> <0040> <0043>\n<0024> <0025> <0041> endbfrange";
+        let map = parse_tounicode_cmap(cmap);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&0x24).map(String::as_str), Some("A"));
+        assert_eq!(map.get(&0x25).map(String::as_str), Some("B"));
+    }
+
+    #[test]
+    fn test_detect_text_direction() {
+        let (test_dir, output_dir) = setup_unique_paths("detect_text_direction");
+        let latin = test_dir.join("latin.pdf");
+        let hebrew = test_dir.join("hebrew.pdf");
+        create_minimal_pdf(latin.to_str().unwrap(), 2, "Latin").unwrap();
+        create_minimal_pdf(hebrew.to_str().unwrap(), 1, "Hebrew").unwrap();
+
+        // Map the codes A-E to Hebrew letters alef to he
+        let mut doc = Document::load(&hebrew).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let cmap_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"1 beginbfrange <41> <45> <05D0> endbfrange".to_vec(),
+        ));
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "ToUnicode" => Object::Reference(cmap_id),
+        });
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set(
+            "Resources",
+            dictionary! { "Font" => dictionary! { "F2" => Object::Reference(font_id) } },
+        );
+        doc.change_page_content(
+            page_id,
+            b"BT /F2 12 Tf 100 700 Td (ABCDE EDCBA) Tj ET".to_vec(),
+        )
+        .unwrap();
+        doc.save(&hebrew).unwrap();
+
+        assert_eq!(
+            detect_text_direction(hebrew.to_str().unwrap()).unwrap(),
+            "rtl"
+        );
+        assert_eq!(
+            detect_text_direction(latin.to_str().unwrap()).unwrap(),
+            "ltr"
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,751 @@
+use crate::pdf::utils::{load_document, rebuild_clean};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+fn font_base_name(font: &Dictionary) -> String {
+    font.get(b"BaseFont")
+        .and_then(|o| o.as_name())
+        .map(|n| String::from_utf8_lossy(n).to_string())
+        .unwrap_or_else(|_| "Unnamed".to_string())
+}
+
+fn descriptor_has_font_file(doc: &Document, font: &Dictionary) -> bool {
+    font.get_deref(b"FontDescriptor", doc)
+        .and_then(|o| o.as_dict())
+        .map(|fd| fd.has(b"FontFile") || fd.has(b"FontFile2") || fd.has(b"FontFile3"))
+        .unwrap_or(false)
+}
+
+/// Whether the glyph program for a font travels with the file. Type3 fonts define
+/// their glyphs inline; Type0 fonts carry the program on their descendant font.
+pub(crate) fn is_font_embedded(doc: &Document, font: &Dictionary) -> bool {
+    match font.get(b"Subtype").and_then(|o| o.as_name()) {
+        Ok(b"Type3") => true,
+        Ok(b"Type0") => font
+            .get_deref(b"DescendantFonts", doc)
+            .and_then(|o| o.as_array())
+            .ok()
+            .and_then(|arr| arr.first())
+            .and_then(|d| doc.dereference(d).ok())
+            .and_then(|(_, d)| d.as_dict().ok())
+            .map(|descendant| descriptor_has_font_file(doc, descendant))
+            .unwrap_or(false),
+        _ => descriptor_has_font_file(doc, font),
+    }
+}
+
+/// Every font referenced from a page's resources (including inherited ones), keyed
+/// by object id so shared fonts are only reported once.
+pub(crate) fn collect_page_fonts(doc: &Document) -> BTreeMap<ObjectId, String> {
+    let mut fonts = BTreeMap::new();
+    for page_id in doc.get_pages().into_values() {
+        let (inline, resource_ids) = match doc.get_page_resources(page_id) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let resources = inline.into_iter().chain(
+            resource_ids
+                .iter()
+                .filter_map(|id| doc.get_dictionary(*id).ok()),
+        );
+        for res in resources {
+            let font_dict = match res.get_deref(b"Font", doc).and_then(|o| o.as_dict()) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            for (_, font_ref) in font_dict.iter() {
+                if let Object::Reference(font_id) = font_ref {
+                    if let Ok(font) = doc.get_dictionary(*font_id) {
+                        fonts.insert(*font_id, font_base_name(font));
+                    }
+                }
+            }
+        }
+    }
+    fonts
+}
+
+#[tauri::command]
+pub fn check_font_embedding(path: &str) -> Result<Vec<String>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut missing: Vec<String> = collect_page_fonts(&doc)
+        .into_iter()
+        .filter(|(id, _)| {
+            doc.get_dictionary(*id)
+                .map(|font| !is_font_embedded(&doc, font))
+                .unwrap_or(false)
+        })
+        .map(|(_, name)| name)
+        .collect();
+    missing.sort();
+    missing.dedup();
+
+    Ok(missing)
+}
+
+// --- embed_standard_fonts: swap base-14 references for embedded substitutes ---
+
+/// Metric-compatible TrueType substitutes for the standard 14 fonts, in order of
+/// preference. Symbol and ZapfDingbats have no freely available equivalent.
+fn substitute_files(base_font: &str) -> &'static [&'static str] {
+    match base_font {
+        "Helvetica" => &["LiberationSans-Regular.ttf", "Arimo-Regular.ttf"],
+        "Helvetica-Bold" => &["LiberationSans-Bold.ttf", "Arimo-Bold.ttf"],
+        "Helvetica-Oblique" => &["LiberationSans-Italic.ttf", "Arimo-Italic.ttf"],
+        "Helvetica-BoldOblique" => &["LiberationSans-BoldItalic.ttf", "Arimo-BoldItalic.ttf"],
+        "Times-Roman" => &["LiberationSerif-Regular.ttf", "Tinos-Regular.ttf"],
+        "Times-Bold" => &["LiberationSerif-Bold.ttf", "Tinos-Bold.ttf"],
+        "Times-Italic" => &["LiberationSerif-Italic.ttf", "Tinos-Italic.ttf"],
+        "Times-BoldItalic" => &["LiberationSerif-BoldItalic.ttf", "Tinos-BoldItalic.ttf"],
+        "Courier" => &["LiberationMono-Regular.ttf", "Cousine-Regular.ttf"],
+        "Courier-Bold" => &["LiberationMono-Bold.ttf", "Cousine-Bold.ttf"],
+        "Courier-Oblique" => &["LiberationMono-Italic.ttf", "Cousine-Italic.ttf"],
+        "Courier-BoldOblique" => &["LiberationMono-BoldItalic.ttf", "Cousine-BoldItalic.ttf"],
+        _ => &[],
+    }
+}
+
+/// Font descriptor values taken from the Adobe AFM files for each family, which the
+/// substitutes are designed to match.
+fn standard_descriptor(base_font: &str, font_name: &str) -> Dictionary {
+    let italic = base_font.contains("Oblique") || base_font.contains("Italic");
+    let (flags, bbox, ascent, descent, cap_height, stem_v, italic_angle) =
+        if base_font.starts_with("Times") {
+            (2, [-168, -218, 1000, 898], 683, -217, 662, 84, -15.5)
+        } else if base_font.starts_with("Courier") {
+            (1 | 2, [-23, -250, 715, 805], 629, -157, 562, 51, -12.0)
+        } else {
+            (0, [-166, -225, 1000, 931], 718, -207, 718, 88, -12.0)
+        };
+    // Bit 6 marks the font as nonsymbolic, bit 7 as italic
+    let flags = flags | 32 | if italic { 64 } else { 0 };
+    dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => Object::Name(font_name.as_bytes().to_vec()),
+        "Flags" => flags,
+        "FontBBox" => Object::Array(bbox.iter().map(|v| Object::Integer(*v)).collect()),
+        "ItalicAngle" => Object::Real(if italic { italic_angle } else { 0.0 }),
+        "Ascent" => ascent,
+        "Descent" => descent,
+        "CapHeight" => cap_height,
+        "StemV" => stem_v,
+    }
+}
+
+/// Directories searched for substitute font programs: the `assets/fonts` folder
+/// bundled as an app resource first, then the usual system font locations.
+fn font_search_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let bundled = app
+        .path()
+        .resource_dir()
+        .ok()
+        .map(|dir| dir.join("assets").join("fonts"));
+    bundled
+        .into_iter()
+        .chain([
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+            PathBuf::from("/Library/Fonts"),
+            PathBuf::from("C:\\Windows\\Fonts"),
+        ])
+        .collect()
+}
+
+fn find_font_file(dir: &Path, file_name: &str, depth: usize) -> Option<PathBuf> {
+    let entries = fs::read_dir(dir).ok()?;
+    let mut subdirs = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            subdirs.push(path);
+        } else if entry
+            .file_name()
+            .to_str()
+            .map(|n| n.eq_ignore_ascii_case(file_name))
+            .unwrap_or(false)
+        {
+            return Some(path);
+        }
+    }
+    if depth == 0 {
+        return None;
+    }
+    subdirs.sort();
+    subdirs
+        .iter()
+        .find_map(|sub| find_font_file(sub, file_name, depth - 1))
+}
+
+/// The first substitute program for `base_font` that parses as a TrueType font, with
+/// its path. Candidates that cannot be read or parsed are skipped, so a truncated or
+/// mislabeled file is never embedded.
+fn load_substitute(base_font: &str, search_dirs: &[PathBuf]) -> Option<(PathBuf, Vec<u8>)> {
+    substitute_files(base_font).iter().find_map(|file_name| {
+        search_dirs
+            .iter()
+            .filter_map(|dir| find_font_file(dir, file_name, 4))
+            .find_map(|font_file| {
+                let data = fs::read(&font_file).ok()?;
+                ttf_parser::Face::parse(&data, 0).ok()?;
+                Some((font_file, data))
+            })
+    })
+}
+
+/// The PostScript name recorded in a TrueType program's `name` table.
+fn postscript_name(face: &ttf_parser::Face) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+        .find_map(|name| name.to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// `/FirstChar`, `/LastChar` and `/Widths` for a simple font whose codes map to
+/// Unicode through `code_table`, measured from the program's horizontal advances and
+/// scaled to 1/1000 of text space. Codes the program has no glyph for get the width
+/// of its `.notdef` glyph.
+fn truetype_widths(face: &ttf_parser::Face, code_table: &[Option<u16>]) -> (i64, i64, Vec<Object>) {
+    let first = code_table.iter().position(Option::is_some).unwrap_or(0);
+    let last = code_table.iter().rposition(Option::is_some).unwrap_or(0);
+    let units_per_em = f64::from(face.units_per_em());
+    let widths = (first..=last)
+        .map(|code| {
+            let glyph = code_table
+                .get(code)
+                .copied()
+                .flatten()
+                .and_then(|unicode| char::from_u32(u32::from(unicode)))
+                .and_then(|c| face.glyph_index(c))
+                .unwrap_or(ttf_parser::GlyphId(0));
+            let advance = f64::from(face.glyph_hor_advance(glyph).unwrap_or(0));
+            Object::Integer((advance * 1000.0 / units_per_em).round() as i64)
+        })
+        .collect();
+    (first as i64, last as i64, widths)
+}
+
+pub(crate) fn embed_standard_fonts_from(
+    path: &str,
+    output_path: &str,
+    search_dirs: &[PathBuf],
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let mut doc = load_document(path, None)?;
+
+    // Shared font programs are embedded once and reused by every matching font
+    let mut programs: HashMap<PathBuf, ObjectId> = HashMap::new();
+    let mut embedded = Vec::new();
+
+    for (font_id, base_font) in collect_page_fonts(&doc) {
+        let font = match doc.get_dictionary(font_id) {
+            Ok(f) => f,
+            Err(_) => continue,
+        };
+        let is_type1 = font
+            .get(b"Subtype")
+            .and_then(|o| o.as_name())
+            .map(|n| n == b"Type1")
+            .unwrap_or(false);
+        if !is_type1 || is_font_embedded(&doc, font) {
+            continue;
+        }
+        let (font_file, data) = match load_substitute(&base_font, search_dirs) {
+            Some(found) => found,
+            None => continue,
+        };
+        // load_substitute only returns programs that parse
+        let face = ttf_parser::Face::parse(&data, 0)
+            .map_err(|e| format!("Failed to parse '{}': {}", font_file.display(), e))?;
+        let font_name = postscript_name(&face).unwrap_or_else(|| base_font.clone());
+        // Fonts without an /Encoding are given WinAnsiEncoding below
+        let code_table = font
+            .has(b"Encoding")
+            .then(|| simple_font_code_table(&doc, font))
+            .flatten()
+            .or_else(|| {
+                simple_font_code_table(&doc, &dictionary! { "Encoding" => "WinAnsiEncoding" })
+            })
+            .unwrap_or_default();
+        let (first_char, last_char, widths) = truetype_widths(&face, &code_table);
+
+        let program_id = match programs.get(&font_file) {
+            Some(id) => *id,
+            None => {
+                let mut stream = Stream::new(dictionary! { "Length1" => data.len() as i64 }, data);
+                // Keep the uncompressed program if Flate fails; it is still valid
+                let _ = stream.compress();
+                let id = doc.add_object(stream);
+                programs.insert(font_file.clone(), id);
+                id
+            }
+        };
+
+        let mut descriptor = standard_descriptor(&base_font, &font_name);
+        descriptor.set("FontFile2", Object::Reference(program_id));
+        let descriptor_id = doc.add_object(descriptor);
+
+        // The widths pin the layout to the substitute's metrics, and BaseFont names
+        // the embedded program rather than the standard font it replaces.
+        let font = doc
+            .get_dictionary_mut(font_id)
+            .map_err(|e| format!("Failed to update font '{}': {}", base_font, e))?;
+        font.set("Subtype", "TrueType");
+        font.set("BaseFont", Object::Name(font_name.into_bytes()));
+        font.set("FirstChar", first_char);
+        font.set("LastChar", last_char);
+        font.set("Widths", Object::Array(widths));
+        font.set("FontDescriptor", Object::Reference(descriptor_id));
+        if !font.has(b"Encoding") {
+            font.set("Encoding", "WinAnsiEncoding");
+        }
+        embedded.push(base_font);
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    embedded.sort();
+    embedded.dedup();
+    Ok(embedded)
+}
+
+/// Embed a metric-compatible substitute (Liberation or the Croscore fonts) for every
+/// standard 14 font the document references without embedding. Fonts with no
+/// substitute available are left untouched. Returns the names of embedded fonts.
+#[tauri::command]
+pub fn embed_standard_fonts(
+    app: AppHandle,
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    embed_standard_fonts_from(path, output_path, &font_search_dirs(&app), clean)
+}
+
+// --- add_tounicode: synthesize ToUnicode CMaps for simple fonts ---
+
+/// The single-byte code table a simple font's `/Encoding` names, if it is one of the
+/// predefined encodings. Fonts with a `/Differences` array are skipped for now since
+/// mapping arbitrary glyph names back to Unicode needs the full glyph list.
+fn simple_font_code_table(doc: &Document, font: &Dictionary) -> Option<Vec<Option<u16>>> {
+    let encoding_name: Vec<u8> = match font.get_deref(b"Encoding", doc) {
+        Ok(Object::Name(name)) => name.clone(),
+        Ok(Object::Dictionary(enc)) => {
+            if enc.has(b"Differences") {
+                return None;
+            }
+            enc.get(b"BaseEncoding")
+                .and_then(|o| o.as_name())
+                .map(|n| n.to_vec())
+                .unwrap_or_else(|_| b"StandardEncoding".to_vec())
+        }
+        // Only Type1 fonts have a well-defined built-in default
+        Err(_) if font_base_name(font) != "Symbol" && font_base_name(font) != "ZapfDingbats" => {
+            let is_type1 = font
+                .get(b"Subtype")
+                .and_then(|o| o.as_name())
+                .map(|n| n == b"Type1")
+                .unwrap_or(false);
+            if !is_type1 {
+                return None;
+            }
+            b"StandardEncoding".to_vec()
+        }
+        _ => return None,
+    };
+    if !matches!(
+        encoding_name.as_slice(),
+        b"StandardEncoding" | b"WinAnsiEncoding" | b"MacRomanEncoding" | b"MacExpertEncoding"
+    ) {
+        return None;
+    }
+
+    // Let lopdf resolve the table for us from a font carrying just the encoding name
+    let probe = dictionary! {
+        "Type" => "Font",
+        "Encoding" => Object::Name(encoding_name),
+    };
+    match probe.get_font_encoding(doc) {
+        Ok(lopdf::Encoding::OneByteEncoding(table)) => Some(table.to_vec()),
+        _ => None,
+    }
+}
+
+/// Serialize a single-byte code → UTF-16 mapping as a ToUnicode CMap stream body.
+fn build_tounicode_cmap(mapping: &[(u8, u16)]) -> Vec<u8> {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<00> <FF>\nendcodespacerange\n",
+    );
+    // bfchar blocks are limited to 100 entries each
+    for chunk in mapping.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (code, unicode) in chunk {
+            cmap.push_str(&format!("<{:02X}> <{:04X}>\n", code, unicode));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap.into_bytes()
+}
+
+/// Attach a ToUnicode CMap to every simple font that lacks one and uses a predefined
+/// encoding, so extraction and copy-paste no longer depend on the reader guessing.
+/// Composite (CID) fonts are not handled yet. Returns the names of augmented fonts.
+#[tauri::command]
+pub fn add_tounicode(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let mut doc = load_document(path, None)?;
+
+    let mut augmented = Vec::new();
+    for (font_id, base_font) in collect_page_fonts(&doc) {
+        let table = match doc.get_dictionary(font_id) {
+            Ok(font) if !font.has(b"ToUnicode") => {
+                let is_simple = matches!(
+                    font.get(b"Subtype").and_then(|o| o.as_name()),
+                    Ok(b"Type1") | Ok(b"TrueType") | Ok(b"MMType1")
+                );
+                if !is_simple {
+                    continue;
+                }
+                match simple_font_code_table(&doc, font) {
+                    Some(t) => t,
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        let mapping: Vec<(u8, u16)> = table
+            .iter()
+            .enumerate()
+            .filter_map(|(code, unicode)| unicode.map(|u| (code as u8, u)))
+            .collect();
+        let cmap_id = doc.add_object(Stream::new(dictionary! {}, build_tounicode_cmap(&mapping)));
+        doc.get_dictionary_mut(font_id)
+            .map_err(|e| format!("Failed to update font '{}': {}", base_font, e))?
+            .set("ToUnicode", Object::Reference(cmap_id));
+        augmented.push(base_font);
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    augmented.sort();
+    augmented.dedup();
+    Ok(augmented)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::extractor_text::extract_page_text;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    #[test]
+    fn test_check_font_embedding_flags_standard_font() {
+        let (test_dir, output_dir) = setup_unique_paths("font_embedding");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Fonts").unwrap();
+
+        let missing = check_font_embedding(input.to_str().unwrap()).unwrap();
+        assert_eq!(missing, vec!["Helvetica".to_string()]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_check_font_embedding_accepts_embedded_font() {
+        let (test_dir, output_dir) = setup_unique_paths("font_embedding_ok");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Fonts").unwrap();
+
+        // Give the shared Helvetica font a (dummy) embedded program
+        let mut doc = Document::load(&input).unwrap();
+        let font_id = *collect_page_fonts(&doc).keys().next().unwrap();
+        let file_id = doc.add_object(Stream::new(dictionary! {}, vec![0u8; 16]));
+        let descriptor_id = doc.add_object(dictionary! {
+            "Type" => "FontDescriptor",
+            "FontName" => "Helvetica",
+            "FontFile2" => Object::Reference(file_id),
+        });
+        doc.get_dictionary_mut(font_id)
+            .unwrap()
+            .set("FontDescriptor", Object::Reference(descriptor_id));
+        doc.save(&input).unwrap();
+
+        let missing = check_font_embedding(input.to_str().unwrap()).unwrap();
+        assert!(missing.is_empty());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    /// A TrueType program named `TestSans-Regular` with 2000 units per em. Glyph
+    /// `g` is 1000 + 2g units wide, so 500 + g in PDF widths; printable ASCII maps to
+    /// glyphs 1..=95 starting at the space.
+    fn minimal_truetype() -> Vec<u8> {
+        const GLYPHS: u16 = 96;
+        let be = |values: &[u16]| {
+            values
+                .iter()
+                .flat_map(|v| v.to_be_bytes())
+                .collect::<Vec<u8>>()
+        };
+
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&2000u16.to_be_bytes());
+        let mut hhea = vec![0u8; 36];
+        hhea[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        hhea[34..36].copy_from_slice(&GLYPHS.to_be_bytes());
+        let mut maxp = 0x0000_5000u32.to_be_bytes().to_vec();
+        maxp.extend_from_slice(&GLYPHS.to_be_bytes());
+        let hmtx: Vec<u8> = (0..GLYPHS).flat_map(|g| be(&[1000 + 2 * g, 0])).collect();
+        // Format 4 subtable with one segment 0x20..=0x7E plus the closing 0xFFFF one
+        let delta = 1u16.wrapping_sub(0x20);
+        let mut cmap = be(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(be(&[4, 32, 0, 4, 4, 1, 0]));
+        cmap.extend(be(&[0x7E, 0xFFFF, 0, 0x20, 0xFFFF, delta, 1, 0, 0]));
+        let ps_name: Vec<u8> = "TestSans-Regular"
+            .encode_utf16()
+            .flat_map(|u| u.to_be_bytes())
+            .collect();
+        let mut name = be(&[0, 1, 18, 3, 1, 0x409, 6, ps_name.len() as u16, 0]);
+        name.extend(ps_name);
+
+        let tables: [(&[u8; 4], Vec<u8>); 6] = [
+            (b"cmap", cmap),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"maxp", maxp),
+            (b"name", name),
+        ];
+        let mut font = 0x0001_0000u32.to_be_bytes().to_vec();
+        font.extend_from_slice(&(tables.len() as u16).to_be_bytes());
+        font.extend_from_slice(&[0u8; 6]);
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, data) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&[0u8; 4]);
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            offset += data.len().next_multiple_of(4);
+        }
+        for (_, data) in &tables {
+            font.extend_from_slice(data);
+            font.resize(font.len().next_multiple_of(4), 0);
+        }
+        font
+    }
+
+    #[test]
+    fn test_embed_standard_fonts_adds_font_file2() {
+        let (test_dir, output_dir) = setup_unique_paths("embed_standard_fonts");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("embedded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Fonts").unwrap();
+
+        let font_dir = test_dir.join("fonts");
+        fs::create_dir_all(&font_dir).unwrap();
+        let program = minimal_truetype();
+        fs::write(font_dir.join("LiberationSans-Regular.ttf"), &program).unwrap();
+
+        let embedded = embed_standard_fonts_from(
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+            &[font_dir],
+            None,
+        )
+        .unwrap();
+        assert_eq!(embedded, vec!["Helvetica".to_string()]);
+
+        let doc = Document::load(&output).unwrap();
+        let font_id = *collect_page_fonts(&doc).keys().next().unwrap();
+        let font = doc.get_dictionary(font_id).unwrap();
+        let descriptor = font
+            .get_deref(b"FontDescriptor", &doc)
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let font_file = descriptor
+            .get_deref(b"FontFile2", &doc)
+            .and_then(|o| o.as_stream())
+            .unwrap();
+        assert_eq!(font_file.decompressed_content().unwrap(), program);
+
+        // Name and widths come from the embedded program, not the standard font
+        assert_eq!(
+            font.get(b"BaseFont").unwrap().as_name().unwrap(),
+            b"TestSans-Regular"
+        );
+        assert_eq!(
+            descriptor.get(b"FontName").unwrap().as_name().unwrap(),
+            b"TestSans-Regular"
+        );
+        let first_char = font.get(b"FirstChar").unwrap().as_i64().unwrap();
+        assert_eq!(first_char, 32);
+        assert_eq!(font.get(b"LastChar").unwrap().as_i64().unwrap(), 255);
+        let widths = font.get(b"Widths").unwrap().as_array().unwrap();
+        assert_eq!(widths.len(), 224);
+        let width_of = |code: u8| {
+            widths[(code as i64 - first_char) as usize]
+                .as_i64()
+                .unwrap()
+        };
+        assert_eq!(width_of(b' '), 501);
+        assert_eq!(width_of(b'F'), 500 + (b'F' - 0x1F) as i64);
+        // No glyph for the code: the .notdef width
+        assert_eq!(width_of(0xE9), 500);
+        assert!(check_font_embedding(output.to_str().unwrap())
+            .unwrap()
+            .is_empty());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_embed_standard_fonts_skips_unparseable_font() {
+        let (test_dir, output_dir) = setup_unique_paths("embed_standard_fonts_corrupt");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("embedded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Fonts").unwrap();
+
+        let corrupt_dir = test_dir.join("corrupt");
+        let valid_dir = test_dir.join("valid");
+        fs::create_dir_all(&corrupt_dir).unwrap();
+        fs::create_dir_all(&valid_dir).unwrap();
+        fs::write(
+            corrupt_dir.join("LiberationSans-Regular.ttf"),
+            vec![0u8; 64],
+        )
+        .unwrap();
+
+        let embedded = embed_standard_fonts_from(
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+            std::slice::from_ref(&corrupt_dir),
+            None,
+        )
+        .unwrap();
+        assert!(embedded.is_empty());
+
+        // A later directory with a usable program is still found
+        fs::write(valid_dir.join("Arimo-Regular.ttf"), minimal_truetype()).unwrap();
+        let embedded = embed_standard_fonts_from(
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+            &[corrupt_dir, valid_dir],
+            None,
+        )
+        .unwrap();
+        assert_eq!(embedded, vec!["Helvetica".to_string()]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_embed_standard_fonts_without_substitute() {
+        let (test_dir, output_dir) = setup_unique_paths("embed_standard_fonts_none");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("embedded.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Fonts").unwrap();
+
+        let embedded =
+            embed_standard_fonts_from(input.to_str().unwrap(), output.to_str().unwrap(), &[], None)
+                .unwrap();
+        assert!(embedded.is_empty());
+        assert_eq!(
+            check_font_embedding(output.to_str().unwrap()).unwrap(),
+            vec!["Helvetica".to_string()]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_add_tounicode_improves_extraction() {
+        let (test_dir, output_dir) = setup_unique_paths("add_tounicode");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("tounicode.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Fonts").unwrap();
+
+        // An encoding dictionary hides the WinAnsi base from lopdf, which then falls
+        // back to StandardEncoding and reads 0xE9 as "Ø" instead of "é"
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let font_id = *collect_page_fonts(&doc).keys().next().unwrap();
+        doc.get_dictionary_mut(font_id).unwrap().set(
+            "Encoding",
+            dictionary! { "Type" => "Encoding", "BaseEncoding" => "WinAnsiEncoding" },
+        );
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 100 700 Td (Caf\\351) Tj ET".to_vec(),
+        ));
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Contents", Object::Reference(content_id));
+        doc.save(&input).unwrap();
+        let before = extract_page_text(&doc, page_id).unwrap();
+        assert_ne!(before.trim(), "Café");
+
+        let augmented =
+            add_tounicode(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert_eq!(augmented, vec!["Helvetica".to_string()]);
+
+        let out = Document::load(&output).unwrap();
+        let font = out.get_dictionary(font_id).unwrap();
+        assert!(font
+            .get_deref(b"ToUnicode", &out)
+            .and_then(|o| o.as_stream())
+            .is_ok());
+        let out_page = *out.get_pages().get(&1).unwrap();
+        assert_eq!(extract_page_text(&out, out_page).unwrap().trim(), "Café");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -1,7 +1,21 @@
-use lopdf::{Document, content::Content};
+use crate::pdf::cropper::{
+    matrix_from_operands, multiply, string_byte_len, transform, translate, Matrix, IDENTITY,
+};
+use crate::pdf::utils::{load_document, rebuild_clean, rect_from_object};
+use lopdf::content::{Content, Operation};
+use lopdf::{Document, Object};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
 
 #[tauri::command]
-pub fn forensic_redact(path: &str, page_num: u32, rect: [f32; 4], output_path: &str) -> Result<(), String> {
+pub fn forensic_redact(
+    path: &str,
+    page_num: u32,
+    rect: [f32; 4],
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     
     // Forensic Redaction involves:
@@ -26,6 +40,321 @@ pub fn forensic_redact(path: &str, page_num: u32, rect: [f32; 4], output_path: &
         // that fall within these coordinates.
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// A rectangle to black out on one page, in default user space (points).
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RedactRegion {
+    pub page: u32,
+    pub rect: [f64; 4],
+}
+
+/// Estimated text width per byte, in ems. Wider than the average glyph so a text run
+/// is never measured short of a region it reaches into.
+const REDACT_GLYPH_WIDTH: f64 = 0.6;
+
+fn rects_overlap(a: [f64; 4], b: [f64; 4]) -> bool {
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
+}
+
+/// Page-space bounding box of `rect` in the coordinate system `m`.
+fn mapped_bbox(m: &Matrix, [x0, y0, x1, y1]: [f64; 4]) -> [f64; 4] {
+    let corners = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(x, y)| transform(m, x, y));
+    corners.iter().fold(
+        [f64::MAX, f64::MAX, f64::MIN, f64::MIN],
+        |[a, b, c, d], &(x, y)| [a.min(x), b.min(y), c.max(x), d.max(y)],
+    )
+}
+
+/// Drop every text-showing operator, image and form XObject whose estimated area
+/// touches one of `regions`. Whole text runs are removed, not just the covered glyphs.
+/// Returns the number of operators removed.
+fn strip_covered_operations(content: &mut Content, regions: &[[f64; 4]]) -> usize {
+    let mut ctm = IDENTITY;
+    let mut stack: Vec<Matrix> = Vec::new();
+    let (mut tm, mut tlm) = (IDENTITY, IDENTITY);
+    let (mut font_size, mut leading) = (0.0, 0.0);
+    let covered = |bbox: [f64; 4]| regions.iter().any(|r| rects_overlap(*r, bbox));
+
+    let operations = std::mem::take(&mut content.operations);
+    let before = operations.len();
+    for op in operations {
+        let nums: Vec<f64> = op
+            .operands
+            .iter()
+            .filter_map(|o| o.as_float().ok().map(|v| v as f64))
+            .collect();
+        let mut keep = true;
+        match op.operator.as_str() {
+            "q" => stack.push(ctm),
+            "Q" => ctm = stack.pop().unwrap_or(IDENTITY),
+            "cm" => {
+                if let Some(m) = matrix_from_operands(&op.operands) {
+                    ctm = multiply(&m, &ctm);
+                }
+            }
+            "BT" => {
+                tm = IDENTITY;
+                tlm = IDENTITY;
+            }
+            "Tf" if nums.len() == 1 => font_size = nums[0],
+            "TL" if nums.len() == 1 => leading = nums[0],
+            "Td" | "TD" if nums.len() == 2 => {
+                if op.operator == "TD" {
+                    leading = -nums[1];
+                }
+                tlm = multiply(&translate(nums[0], nums[1]), &tlm);
+                tm = tlm;
+            }
+            "Tm" => {
+                if let Some(m) = matrix_from_operands(&op.operands) {
+                    tm = m;
+                    tlm = m;
+                }
+            }
+            "T*" => {
+                tlm = multiply(&translate(0.0, -leading), &tlm);
+                tm = tlm;
+            }
+            "Tj" | "'" | "\"" | "TJ" => {
+                if op.operator == "'" || op.operator == "\"" {
+                    tlm = multiply(&translate(0.0, -leading), &tlm);
+                    tm = tlm;
+                }
+                let byte_len = match op.operands.first() {
+                    Some(Object::Array(items)) if op.operator == "TJ" => string_byte_len(items),
+                    _ => string_byte_len(&op.operands),
+                };
+                let width = byte_len as f64 * font_size * REDACT_GLYPH_WIDTH;
+                let text_to_page = multiply(&tm, &ctm);
+                keep = !covered(mapped_bbox(
+                    &text_to_page,
+                    [0.0, -0.25 * font_size, width, font_size],
+                ));
+                tm = multiply(&translate(width, 0.0), &tm);
+            }
+            // Images fill the unit square; forms are dropped whole when they overlap
+            "Do" | "BI" => keep = !covered(mapped_bbox(&ctm, [0.0, 0.0, 1.0, 1.0])),
+            _ => {}
+        }
+        if keep {
+            content.operations.push(op);
+        }
+    }
+    before - content.operations.len()
+}
+
+/// Redact `regions` and write a copy that cannot leak what was removed. Text runs,
+/// images and form XObjects touching a region are deleted from the page content (not
+/// just covered), annotations overlapping it are removed, and a black box is drawn
+/// over each region. The document is then rebuilt from its catalog without the Info
+/// dictionary or XMP metadata, which drops orphaned objects and earlier revisions.
+#[tauri::command]
+pub fn secure_export(
+    path: &str,
+    regions: Vec<RedactRegion>,
+    output_path: &str,
+) -> Result<(), String> {
+    if regions.is_empty() {
+        return Err("No regions to redact.".to_string());
+    }
+    let mut doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+
+    let mut by_page: BTreeMap<u32, Vec<[f64; 4]>> = BTreeMap::new();
+    for region in &regions {
+        if !pages.contains_key(&region.page) {
+            return Err(format!(
+                "Page number {} not found in document '{}' (which has {} pages).",
+                region.page,
+                path,
+                pages.len()
+            ));
+        }
+        let [x0, y0, x1, y1] = region.rect;
+        by_page.entry(region.page).or_default().push([
+            x0.min(x1),
+            y0.min(y1),
+            x0.max(x1),
+            y0.max(y1),
+        ]);
+    }
+
+    for (page_num, rects) in &by_page {
+        let page_id = pages[page_num];
+        let data = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        let mut content = Content::decode(&data)
+            .map_err(|e| format!("Failed to parse content of page {}: {}", page_num, e))?;
+        strip_covered_operations(&mut content, rects);
+        // The page's own graphics state must not move or recolour the boxes
+        content.operations.insert(0, Operation::new("q", vec![]));
+        content.operations.push(Operation::new("Q", vec![]));
+        content.operations.push(Operation::new("q", vec![]));
+        content
+            .operations
+            .push(Operation::new("rg", vec![0.into(), 0.into(), 0.into()]));
+        for &[x0, y0, x1, y1] in rects {
+            content.operations.push(Operation::new(
+                "re",
+                vec![x0.into(), y0.into(), (x1 - x0).into(), (y1 - y0).into()],
+            ));
+        }
+        content.operations.push(Operation::new("f", vec![]));
+        content.operations.push(Operation::new("Q", vec![]));
+        let encoded = content
+            .encode()
+            .map_err(|e| format!("Failed to encode content of page {}: {}", page_num, e))?;
+        doc.change_page_content(page_id, encoded)
+            .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))?;
+
+        let annotations = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| page.get_deref(b"Annots", &doc).ok())
+            .and_then(|a| a.as_array().ok())
+            .cloned();
+        if let Some(annotations) = annotations {
+            let kept: Vec<Object> = annotations
+                .into_iter()
+                .filter(|annot| {
+                    let rect = doc
+                        .dereference(annot)
+                        .ok()
+                        .and_then(|(_, a)| a.as_dict().ok())
+                        .and_then(|a| a.get(b"Rect").ok())
+                        .and_then(rect_from_object);
+                    rect.is_none_or(|r| {
+                        let r = [
+                            r[0].min(r[2]),
+                            r[1].min(r[3]),
+                            r[0].max(r[2]),
+                            r[1].max(r[3]),
+                        ];
+                        !rects.iter().any(|region| rects_overlap(*region, r))
+                    })
+                })
+                .collect();
+            doc.get_dictionary_mut(page_id)
+                .map_err(|e| format!("Failed to access page {}: {}", page_num, e))?
+                .set("Annots", kept);
+        }
+    }
+
+    doc.trailer.remove(b"Info");
+    for page_id in pages.values() {
+        if let Ok(page) = doc.get_dictionary_mut(*page_id) {
+            page.remove(b"Metadata");
+            page.remove(b"PieceInfo");
+        }
+    }
+    let catalog = doc
+        .catalog_mut()
+        .map_err(|e| format!("Failed to read document catalog: {}", e))?;
+    catalog.remove(b"Metadata");
+    catalog.remove(b"PieceInfo");
+    let mut clean = rebuild_clean(&doc)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    clean
+        .save(output_path)
+        .map_err(|e| format!("Failed to save redacted PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_secure_export_leaves_no_trace_of_redacted_text() {
+        let (test_dir, output_dir) = setup_unique_paths("secure_export");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("redacted.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Case").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        doc.change_page_content(
+            page_id,
+            b"BT /F1 12 Tf 100 700 Td (Public line) Tj 0 -100 Td (Secret 4711) Tj ET".to_vec(),
+        )
+        .unwrap();
+        // Leftovers from an earlier revision and the document properties
+        doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 100 600 Td (Secret 4711) Tj ET".to_vec(),
+        ));
+        let info = doc.add_object(dictionary! { "Title" => Object::string_literal("Secret 4711") });
+        doc.trailer.set("Info", Object::Reference(info));
+        doc.save(&input).unwrap();
+
+        let regions = vec![RedactRegion {
+            page: 1,
+            rect: [90.0, 590.0, 300.0, 620.0],
+        }];
+        let result = secure_export(input.to_str().unwrap(), regions, output.to_str().unwrap());
+        assert!(result.is_ok(), "secure_export failed: {:?}", result.err());
+
+        let redacted = Document::load(&output).unwrap();
+        assert_eq!(redacted.get_pages().len(), 2);
+        let text = redacted.extract_text(&[1]).unwrap();
+        assert!(text.contains("Public line"), "text: {:?}", text);
+        assert!(!text.contains("Secret"), "text: {:?}", text);
+
+        let raw = fs::read(&output).unwrap();
+        assert!(!raw.windows(6).any(|w| w == b"Secret"));
+        for object in redacted.objects.values() {
+            if let Ok(stream) = object.as_stream() {
+                let data = stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone());
+                assert!(!data.windows(6).any(|w| w == b"Secret"));
+            }
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_secure_export_rejects_unknown_page() {
+        let (test_dir, output_dir) = setup_unique_paths("secure_export_page");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Case").unwrap();
+
+        let regions = vec![RedactRegion {
+            page: 3,
+            rect: [0.0, 0.0, 10.0, 10.0],
+        }];
+        let result = secure_export(
+            input.to_str().unwrap(),
+            regions,
+            output_dir.join("out.pdf").to_str().unwrap(),
+        );
+        assert!(result.unwrap_err().contains("Page number 3 not found"));
+        assert!(secure_export(input.to_str().unwrap(), Vec::new(), "out.pdf").is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
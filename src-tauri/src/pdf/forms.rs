@@ -1,3 +1,4 @@
+use crate::pdf::utils::rebuild_clean;
 use lopdf::{dictionary, Document, Object};
 
 #[derive(serde::Serialize)]
@@ -73,7 +74,12 @@ pub fn get_form_fields(path: &str) -> Result<Vec<FormField>, String> {
 }
 
 #[tauri::command]
-pub fn set_form_fields(path: &str, updates: std::collections::HashMap<String, String>, output_path: &str) -> Result<(), String> {
+pub fn set_form_fields(
+    path: &str,
+    updates: std::collections::HashMap<String, String>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     let mut field_updates = vec![];
 
@@ -110,6 +116,10 @@ pub fn set_form_fields(path: &str, updates: std::collections::HashMap<String, St
         doc.objects.insert(fid, Object::Dictionary(field));
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save: {}", e))?;
     Ok(())
 }
@@ -123,7 +133,12 @@ pub struct NewFormField {
 }
 
 #[tauri::command]
-pub fn create_form_fields(path: &str, new_fields: Vec<NewFormField>, output_path: &str) -> Result<(), String> {
+pub fn create_form_fields(
+    path: &str,
+    new_fields: Vec<NewFormField>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     
     // 1. Ensure AcroForm exists
@@ -194,6 +209,10 @@ pub fn create_form_fields(path: &str, new_fields: Vec<NewFormField>, output_path
     acroform.set("Fields", global_fields);
     doc.objects.insert(acroform_id, Object::Dictionary(acroform));
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save: {}", e))?;
     Ok(())
 }
@@ -237,7 +256,8 @@ mod tests {
         let result = create_form_fields(
             input_path.to_str().unwrap(),
             new_fields,
-            output_path.to_str().unwrap()
+            output_path.to_str().unwrap(),
+            None
         );
 
         assert!(result.is_ok());
@@ -0,0 +1,167 @@
+use crate::pdf::utils::load_document;
+use lopdf::{content::Content, Document, Object, ObjectId};
+use sha2::{Digest, Sha256};
+
+/// Format a number the same way regardless of how it was written in the source
+/// stream, so `1`, `1.0` and `1.000` all serialize as `1`.
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    let formatted = format!("{:.6}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn normalize_operand(object: &Object, out: &mut String) {
+    match object {
+        Object::Null => out.push_str("null"),
+        Object::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Object::Integer(i) => out.push_str(&format_number(*i as f64)),
+        Object::Real(r) => out.push_str(&format_number(*r as f64)),
+        Object::Name(name) => {
+            out.push('/');
+            out.push_str(&String::from_utf8_lossy(name));
+        }
+        // Literal and hex strings with the same bytes are the same text
+        Object::String(bytes, _) => {
+            out.push('<');
+            out.push_str(&hex::encode(bytes));
+            out.push('>');
+        }
+        Object::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                normalize_operand(item, out);
+            }
+            out.push(']');
+        }
+        Object::Dictionary(dict) => {
+            out.push_str("<<");
+            for (key, value) in dict.iter() {
+                out.push('/');
+                out.push_str(&String::from_utf8_lossy(key));
+                out.push(' ');
+                normalize_operand(value, out);
+                out.push(' ');
+            }
+            out.push_str(">>");
+        }
+        Object::Reference((num, gen)) => out.push_str(&format!("{} {} R", num, gen)),
+        Object::Stream(_) => out.push_str("stream"),
+    }
+}
+
+/// Re-serialize decoded operators with canonical spacing and number formatting.
+fn normalize_content(raw: &[u8]) -> Result<Vec<u8>, String> {
+    let content = Content::decode(raw).map_err(|e| format!("Failed to decode content: {}", e))?;
+    let mut out = String::new();
+    for operation in &content.operations {
+        for operand in &operation.operands {
+            normalize_operand(operand, &mut out);
+            out.push(' ');
+        }
+        out.push_str(&operation.operator);
+        out.push('\n');
+    }
+    Ok(out.into_bytes())
+}
+
+/// Hash a single page's content. In strict mode the decompressed bytes are hashed
+/// as-is; otherwise operators are normalized first so formatting-only differences
+/// (operand spacing, `1.0` vs `1`, line endings) produce the same digest.
+pub fn page_content_hash(
+    doc: &Document,
+    page_id: ObjectId,
+    strict: bool,
+) -> Result<String, String> {
+    let raw = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read page content: {}", e))?;
+    let bytes = if strict {
+        raw
+    } else {
+        normalize_content(&raw)?
+    };
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+#[tauri::command]
+pub fn content_hash(path: &str, strict: bool) -> Result<String, String> {
+    let doc = load_document(path, None)?;
+
+    let mut hasher = Sha256::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let page_hash = page_content_hash(&doc, page_id, strict)
+            .map_err(|e| format!("Page {}: {}", page_num, e))?;
+        hasher.update(page_hash.as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Stream;
+
+    fn write_with_content(path: &str, content: &str) {
+        create_minimal_pdf(path, 1, "Hash").unwrap();
+        let mut doc = Document::load(path).unwrap();
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let content_id = doc.add_object(Stream::new(
+            lopdf::Dictionary::new(),
+            content.as_bytes().to_vec(),
+        ));
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Contents", Object::Reference(content_id));
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_content_hash_ignores_formatting_when_not_strict() {
+        let (test_dir, output_dir) = setup_unique_paths("content_hash_normalize");
+        let a = test_dir.join("a.pdf");
+        let b = test_dir.join("b.pdf");
+        write_with_content(
+            a.to_str().unwrap(),
+            "q 1 0 0 1 100 700 cm BT /F1 12 Tf (Hello) Tj ET Q",
+        );
+        write_with_content(
+            b.to_str().unwrap(),
+            "q\r\n1.0  0 0 1.0 100.00 700 cm\r\nBT /F1 12.0 Tf <48656C6C6F> Tj ET\r\nQ",
+        );
+
+        let loose_a = content_hash(a.to_str().unwrap(), false).unwrap();
+        let loose_b = content_hash(b.to_str().unwrap(), false).unwrap();
+        assert_eq!(loose_a, loose_b);
+
+        let strict_a = content_hash(a.to_str().unwrap(), true).unwrap();
+        let strict_b = content_hash(b.to_str().unwrap(), true).unwrap();
+        assert_ne!(strict_a, strict_b);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_content_hash_detects_real_changes() {
+        let (test_dir, output_dir) = setup_unique_paths("content_hash_changes");
+        let a = test_dir.join("a.pdf");
+        let b = test_dir.join("b.pdf");
+        create_minimal_pdf(a.to_str().unwrap(), 2, "Original").unwrap();
+        create_minimal_pdf(b.to_str().unwrap(), 2, "Modified").unwrap();
+
+        assert_ne!(
+            content_hash(a.to_str().unwrap(), false).unwrap(),
+            content_hash(b.to_str().unwrap(), false).unwrap()
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::pdf::cleanup::{color_components, stream_filters};
+use crate::pdf::utils::{get_inherited_attribute, load_document};
+use image::{GrayImage, RgbImage};
+use lopdf::{Document, Object, Stream};
+use std::fs;
+use std::path::Path;
+
+/// Raw samples of an 8-bit Flate-compressed or uncompressed image, with the number of
+/// color components per pixel. Other bit depths and color spaces return `None`.
+fn decode_image_samples(doc: &Document, stream: &Stream) -> Option<(u32, u32, usize, Vec<u8>)> {
+    let filters = stream_filters(stream);
+    if filters.iter().any(|f| f != b"FlateDecode") {
+        return None;
+    }
+    let width = stream.dict.get(b"Width").and_then(|o| o.as_i64()).ok()? as u32;
+    let height = stream.dict.get(b"Height").and_then(|o| o.as_i64()).ok()? as u32;
+    let bits = stream
+        .dict
+        .get(b"BitsPerComponent")
+        .and_then(|o| o.as_i64())
+        .ok()?;
+    let components = color_components(doc, stream)?;
+    if bits != 8 {
+        return None;
+    }
+    let data = if filters.is_empty() {
+        stream.content.clone()
+    } else {
+        // lopdf refuses to decompress image streams directly
+        let mut plain = stream.clone();
+        plain.dict.remove(b"Subtype");
+        plain.decompressed_content().ok()?
+    };
+    let sample_count = (width * height) as usize * components;
+    if data.len() < sample_count {
+        return None;
+    }
+    Some((width, height, components, data[..sample_count].to_vec()))
+}
+
+/// Write an image XObject to `output_dir` as `<stem>.jpg` (JPEG data copied as is) or
+/// `<stem>.png` (Flate or uncompressed samples re-encoded). Returns `Ok(None)` for
+/// image encodings that cannot be exported.
+fn write_image(
+    doc: &Document,
+    stream: &Stream,
+    output_dir: &Path,
+    stem: &str,
+) -> Result<Option<String>, String> {
+    if stream_filters(stream) == [b"DCTDecode".to_vec()] {
+        let file_path = output_dir.join(format!("{}.jpg", stem));
+        fs::write(&file_path, &stream.content)
+            .map_err(|e| format!("Failed to write image to '{}': {}", file_path.display(), e))?;
+        return Ok(Some(file_path.to_string_lossy().to_string()));
+    }
+
+    let Some((width, height, components, samples)) = decode_image_samples(doc, stream) else {
+        return Ok(None);
+    };
+    let file_path = output_dir.join(format!("{}.png", stem));
+    let saved = match components {
+        1 => GrayImage::from_raw(width, height, samples).map(|img| img.save(&file_path)),
+        3 => RgbImage::from_raw(width, height, samples).map(|img| img.save(&file_path)),
+        4 => {
+            let rgb = samples
+                .chunks_exact(4)
+                .flat_map(|cmyk| {
+                    let k = 255 - cmyk[3] as u32;
+                    [0, 1, 2].map(|i| ((255 - cmyk[i] as u32) * k / 255) as u8)
+                })
+                .collect();
+            RgbImage::from_raw(width, height, rgb).map(|img| img.save(&file_path))
+        }
+        _ => None,
+    };
+    match saved {
+        Some(result) => result
+            .map(|_| Some(file_path.to_string_lossy().to_string()))
+            .map_err(|e| format!("Failed to write image to '{}': {}", file_path.display(), e)),
+        None => Ok(None),
+    }
+}
+
+/// Save the image XObjects drawn directly by a page into `output_dir`, named
+/// `page<N>_<resource name>`. JPEG images are written unchanged as `.jpg`; 8-bit
+/// Flate-compressed or uncompressed gray, RGB and CMYK images become `.png` (CMYK is
+/// converted to RGB). Images in other encodings, and images nested in form XObjects,
+/// are skipped. Returns the paths written, which is empty for a page without images.
+#[tauri::command]
+pub fn extract_images(
+    path: &str,
+    page_number: u32,
+    output_dir: &str,
+) -> Result<Vec<String>, String> {
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    let page_id = *pages.get(&page_number).ok_or_else(|| {
+        format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            page_number,
+            path,
+            pages.len()
+        )
+    })?;
+
+    let xobjects = get_inherited_attribute(&doc, page_id, b"Resources")
+        .and_then(|r| doc.dereference(r).ok())
+        .and_then(|(_, r)| r.as_dict().ok())
+        .and_then(|r| r.get_deref(b"XObject", &doc).ok())
+        .and_then(|x| x.as_dict().ok());
+    let Some(xobjects) = xobjects else {
+        return Ok(Vec::new());
+    };
+
+    let output_dir_path = Path::new(output_dir);
+    let mut written = Vec::new();
+    for (name, object) in xobjects.iter() {
+        let Ok(Object::Stream(stream)) = doc.dereference(object).map(|(_, o)| o) else {
+            continue;
+        };
+        let is_image = stream
+            .dict
+            .get(b"Subtype")
+            .and_then(|o| o.as_name())
+            .is_ok_and(|s| s == b"Image");
+        if !is_image {
+            continue;
+        }
+
+        if !output_dir_path.exists() {
+            fs::create_dir_all(output_dir_path).map_err(|e| {
+                format!("Failed to create output directory '{}': {}", output_dir, e)
+            })?;
+        }
+        let resource_name: String = String::from_utf8_lossy(name)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let stem = format!("page{}_{}", page_number, resource_name);
+        if let Some(file_path) = write_image(&doc, stream, output_dir_path, &stem)? {
+            written.push(file_path);
+        }
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use image::codecs::jpeg::JpegEncoder;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_extract_images_writes_jpeg_and_png() {
+        let (test_dir, output_dir) = setup_unique_paths("extract_images");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Photos").unwrap();
+
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg)
+            .encode(&[200u8; 4 * 4 * 3], 4, 4, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        let pixels = vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+        let mut flate = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            pixels.clone(),
+        );
+        flate.compress().unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let jpeg_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 4,
+                "Height" => 4,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            },
+            jpeg.clone(),
+        ));
+        let flate_id = doc.add_object(flate);
+        let page_id = doc.get_pages()[&2];
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Resources",
+            dictionary! {
+                "XObject" => dictionary! {
+                    "Im1" => Object::Reference(jpeg_id),
+                    "Im2" => Object::Reference(flate_id),
+                },
+            },
+        );
+        doc.save(&input).unwrap();
+
+        let images_dir = output_dir.join("images");
+        let written =
+            extract_images(input.to_str().unwrap(), 2, images_dir.to_str().unwrap()).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                images_dir
+                    .join("page2_Im1.jpg")
+                    .to_string_lossy()
+                    .to_string(),
+                images_dir
+                    .join("page2_Im2.png")
+                    .to_string_lossy()
+                    .to_string(),
+            ]
+        );
+        assert_eq!(fs::read(&written[0]).unwrap(), jpeg);
+        let png = image::open(&written[1]).unwrap().to_rgb8();
+        assert_eq!(png.dimensions(), (2, 2));
+        assert_eq!(png.into_raw(), pixels);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_extract_images_page_without_images() {
+        let (test_dir, output_dir) = setup_unique_paths("extract_images_none");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Text").unwrap();
+
+        let written =
+            extract_images(input.to_str().unwrap(), 1, output_dir.to_str().unwrap()).unwrap();
+        assert!(written.is_empty());
+        assert!(extract_images(input.to_str().unwrap(), 2, output_dir.to_str().unwrap()).is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,511 @@
+use crate::pdf::colorconv::page_is_color;
+use crate::pdf::extractor_text::extract_page_text;
+use crate::pdf::utils::{get_inherited_attribute, load_document, page_geometry, rect_from_object};
+use lopdf::{Document, Object, ObjectId};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+fn reference_label((num, gen): ObjectId) -> Value {
+    Value::String(format!("{} {} R", num, gen))
+}
+
+/// Convert a PDF object to JSON, following references until `depth` runs out. Each
+/// object is expanded at most once and `/Parent` links are never followed, so
+/// cycles and back-pointers show up as `"obj gen R"` strings instead.
+fn object_to_json(
+    doc: &Document,
+    object: &Object,
+    depth: u32,
+    expanded: &mut HashSet<ObjectId>,
+) -> Value {
+    match object {
+        Object::Null => Value::Null,
+        Object::Boolean(b) => Value::Bool(*b),
+        Object::Integer(i) => json!(i),
+        Object::Real(r) => json!(r),
+        Object::Name(name) => Value::String(format!("/{}", String::from_utf8_lossy(name))),
+        Object::String(bytes, _) => Value::String(String::from_utf8_lossy(bytes).to_string()),
+        Object::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| object_to_json(doc, item, depth, expanded))
+                .collect(),
+        ),
+        Object::Dictionary(dict) => {
+            let mut map = Map::new();
+            for (key, value) in dict.iter() {
+                let key = String::from_utf8_lossy(key).to_string();
+                let converted = match value {
+                    Object::Reference(id) if key == "Parent" => reference_label(*id),
+                    _ => object_to_json(doc, value, depth, expanded),
+                };
+                map.insert(key, converted);
+            }
+            Value::Object(map)
+        }
+        Object::Stream(stream) => {
+            let mut value = object_to_json(
+                doc,
+                &Object::Dictionary(stream.dict.clone()),
+                depth,
+                expanded,
+            );
+            if let Value::Object(map) = &mut value {
+                map.insert("StreamLength".to_string(), json!(stream.content.len()));
+            }
+            value
+        }
+        Object::Reference(id) => {
+            if depth == 0 || !expanded.insert(*id) {
+                return reference_label(*id);
+            }
+            match doc.get_object(*id) {
+                Ok(target) => object_to_json(doc, target, depth - 1, expanded),
+                Err(_) => reference_label(*id),
+            }
+        }
+    }
+}
+
+/// Dump the object graph reachable from the trailer as nested JSON, expanding
+/// references up to `depth` levels deep (the catalog is level 1, the page tree 2).
+#[tauri::command]
+pub fn structure_tree(path: &str, depth: u32) -> Result<Value, String> {
+    let doc = load_document(path, None)?;
+
+    let mut expanded = HashSet::new();
+    Ok(object_to_json(
+        &doc,
+        &Object::Dictionary(doc.trailer.clone()),
+        depth,
+        &mut expanded,
+    ))
+}
+
+// --- page_report: per-page overview for the document table ---
+
+#[derive(serde::Serialize, Debug)]
+pub struct PageReport {
+    pub page: u32,
+    pub width: f64,
+    pub height: f64,
+    pub rotation: i64,
+    pub is_color: bool,
+    pub has_text: bool,
+    pub annotation_count: usize,
+    /// Why the page's text could not be extracted; `has_text` is false then.
+    pub text_error: Option<String>,
+}
+
+/// Size (of the visible box, before rotation), rotation, color use, text presence
+/// and annotation count for every page, so the overview table needs one call. A page
+/// whose text cannot be decoded is still reported, with the reason in `text_error`.
+#[tauri::command]
+pub fn page_report(path: &str) -> Result<Vec<PageReport>, String> {
+    let doc = load_document(path, None)?;
+
+    Ok(doc
+        .get_pages()
+        .into_iter()
+        .map(|(page, page_id)| {
+            let ([x0, y0, x1, y1], rotation) = page_geometry(&doc, page_id);
+            let (has_text, text_error) = match extract_page_text(&doc, page_id) {
+                Ok(text) => (!text.trim().is_empty(), None),
+                Err(e) => (
+                    false,
+                    Some(format!("Failed to extract text of page {}: {}", page, e)),
+                ),
+            };
+            PageReport {
+                page,
+                width: x1 - x0,
+                height: y1 - y0,
+                rotation,
+                is_color: page_is_color(&doc, page_id),
+                has_text,
+                annotation_count: doc
+                    .get_page_annotations(page_id)
+                    .map(|a| a.len())
+                    .unwrap_or(0),
+                text_error,
+            }
+        })
+        .collect())
+}
+
+// --- get_page_resources ---
+
+#[derive(serde::Serialize, Debug)]
+pub struct PageResources {
+    pub fonts: Vec<String>,
+    pub xobjects: Vec<String>,
+    /// How many of `xobjects` are images (the rest are forms).
+    pub images: usize,
+    pub ext_gstates: Vec<String>,
+}
+
+/// List the resource names a page's content can refer to, from its effective
+/// (possibly inherited) `/Resources`, to debug missing fonts or unexpected XObjects.
+#[tauri::command]
+pub fn get_page_resources(path: &str, page: u32) -> Result<PageResources, String> {
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    let page_id = *pages.get(&page).ok_or_else(|| {
+        format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            page,
+            path,
+            pages.len()
+        )
+    })?;
+
+    let resources =
+        get_inherited_attribute(&doc, page_id, b"Resources").and_then(|r| r.as_dict().ok());
+    let category = |key: &[u8]| {
+        resources
+            .and_then(|r| r.get_deref(key, &doc).ok())
+            .and_then(|c| c.as_dict().ok())
+    };
+    let names = |key: &[u8]| -> Vec<String> {
+        category(key)
+            .map(|dict| {
+                dict.iter()
+                    .map(|(name, _)| String::from_utf8_lossy(name).to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let images = category(b"XObject")
+        .map(|xobjects| {
+            xobjects
+                .iter()
+                .filter(|(_, xobject)| {
+                    doc.dereference(xobject)
+                        .and_then(|(_, o)| o.as_stream())
+                        .and_then(|s| s.dict.get(b"Subtype"))
+                        .and_then(|s| s.as_name())
+                        .is_ok_and(|s| s == b"Image")
+                })
+                .count()
+        })
+        .unwrap_or(0);
+
+    Ok(PageResources {
+        fonts: names(b"Font"),
+        xobjects: names(b"XObject"),
+        images,
+        ext_gstates: names(b"ExtGState"),
+    })
+}
+
+// --- estimate_print_cost ---
+
+#[derive(serde::Serialize, Debug)]
+pub struct PrintCost {
+    pub color_pages: u32,
+    pub bw_pages: u32,
+    pub total: f64,
+    /// 1-based numbers of the pages classified as color, i.e. the ones driving cost.
+    pub color_page_numbers: Vec<u32>,
+}
+
+/// Estimate what printing a document costs given per-page prices for color and
+/// black-and-white pages. Pages are classified with the same analysis as
+/// `page_report`'s `is_color`.
+#[tauri::command]
+pub fn estimate_print_cost(
+    path: &str,
+    color_price: f64,
+    bw_price: f64,
+) -> Result<PrintCost, String> {
+    for (label, price) in [("Color", color_price), ("Black-and-white", bw_price)] {
+        if !price.is_finite() || price < 0.0 {
+            return Err(format!(
+                "{} page price must be a non-negative number, got {}.",
+                label, price
+            ));
+        }
+    }
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    let color_page_numbers: Vec<u32> = pages
+        .iter()
+        .filter(|(_, &page_id)| page_is_color(&doc, page_id))
+        .map(|(&page, _)| page)
+        .collect();
+    let color_pages = color_page_numbers.len() as u32;
+    let bw_pages = pages.len() as u32 - color_pages;
+
+    Ok(PrintCost {
+        color_pages,
+        bw_pages,
+        total: color_pages as f64 * color_price + bw_pages as f64 * bw_price,
+        color_page_numbers,
+    })
+}
+
+// --- get_page_info ---
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct PageInfo {
+    pub page_number: u32,
+    /// Width as displayed, i.e. after applying `rotation`.
+    pub width: f64,
+    /// Height as displayed, i.e. after applying `rotation`.
+    pub height: f64,
+    pub rotation: i32,
+}
+
+/// MediaBox size and rotation of every page, for drawing an accurate page picker.
+/// Both are inherited from ancestor Pages nodes when the page does not set them; a
+/// page with no MediaBox at all is reported as US Letter.
+#[tauri::command]
+pub fn get_page_info(path: &str) -> Result<Vec<PageInfo>, String> {
+    let doc = load_document(path, None)?;
+
+    Ok(doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_number, page_id)| {
+            let [x1, y1, x2, y2] = get_inherited_attribute(&doc, page_id, b"MediaBox")
+                .and_then(rect_from_object)
+                .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+            let rotation = get_inherited_attribute(&doc, page_id, b"Rotate")
+                .and_then(|r| r.as_i64().ok())
+                .unwrap_or(0)
+                .rem_euclid(360) as i32;
+            let (width, height) = ((x2 - x1).abs(), (y2 - y1).abs());
+            let (width, height) = if rotation % 180 == 90 {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            PageInfo {
+                page_number,
+                width,
+                height,
+                rotation,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    #[test]
+    fn test_structure_tree_has_root_and_pages() {
+        let (test_dir, output_dir) = setup_unique_paths("structure_tree");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Tree").unwrap();
+
+        let tree = structure_tree(input.to_str().unwrap(), 4).unwrap();
+        let pages = &tree["Root"]["Pages"];
+        assert_eq!(pages["Type"], "/Pages");
+        assert_eq!(pages["Count"], 2);
+        assert_eq!(pages["Kids"].as_array().unwrap().len(), 2);
+        // Pages point back at their parent by reference only
+        let parent = pages["Kids"][0]["Parent"].as_str().unwrap();
+        assert!(parent.ends_with(" R"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_structure_tree_depth_limits_expansion() {
+        let (test_dir, output_dir) = setup_unique_paths("structure_tree_depth");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Tree").unwrap();
+
+        let tree = structure_tree(input.to_str().unwrap(), 1).unwrap();
+        assert!(tree["Root"].is_object());
+        assert!(tree["Root"]["Pages"].as_str().unwrap().ends_with(" R"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_page_report_mixed_document() {
+        use lopdf::dictionary;
+
+        let (test_dir, output_dir) = setup_unique_paths("page_report");
+        let input = test_dir.join("mixed.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Report").unwrap();
+
+        // Page 2: rotated, red box and a link; page 3: no text, gray box only
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        doc.change_page_content(pages[&2], b"1 0 0 rg 0 0 50 50 re f".to_vec())
+            .unwrap();
+        doc.change_page_content(pages[&3], b"0.5 g 0 0 50 50 re f".to_vec())
+            .unwrap();
+        let link = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+        });
+        let page = doc.get_dictionary_mut(pages[&2]).unwrap();
+        page.set("Rotate", 90);
+        page.set("Annots", vec![Object::Reference(link)]);
+        page.set("MediaBox", vec![0.into(), 0.into(), 595.into(), 842.into()]);
+        doc.save(&input).unwrap();
+
+        let report = page_report(input.to_str().unwrap()).unwrap();
+        assert_eq!(report.len(), 3);
+
+        assert_eq!((report[0].width, report[0].height), (612.0, 792.0));
+        assert!(report[0].has_text && !report[0].is_color);
+        assert_eq!(report[0].annotation_count, 0);
+
+        assert_eq!((report[1].width, report[1].height), (595.0, 842.0));
+        assert_eq!(report[1].rotation, 90);
+        assert!(report[1].is_color && !report[1].has_text);
+        assert_eq!(report[1].annotation_count, 1);
+
+        assert!(!report[2].is_color && !report[2].has_text);
+        assert!(report.iter().all(|page| page.text_error.is_none()));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_page_report_keeps_going_past_unreadable_text() {
+        let (test_dir, output_dir) = setup_unique_paths("page_report_broken");
+        let input = test_dir.join("broken.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Report").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let broken = doc.get_pages()[&1];
+        // A dangling /Parent stops the page's resources, and so its fonts, being read
+        doc.get_dictionary_mut(broken)
+            .unwrap()
+            .set("Parent", Object::Reference((9999, 0)));
+        doc.save(&input).unwrap();
+
+        let report = page_report(input.to_str().unwrap()).unwrap();
+        assert_eq!(report.len(), 2);
+        assert!(!report[0].has_text);
+        assert!(report[0]
+            .text_error
+            .as_deref()
+            .is_some_and(|e| e.contains("page 1")));
+        assert!(report[1].has_text && report[1].text_error.is_none());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_get_page_resources_lists_font() {
+        use lopdf::{dictionary, Stream};
+
+        let (test_dir, output_dir) = setup_unique_paths("page_resources");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Res").unwrap();
+
+        let resources = get_page_resources(input.to_str().unwrap(), 1).unwrap();
+        assert_eq!(resources.fonts, vec!["F1".to_string()]);
+        assert!(resources.xobjects.is_empty());
+        assert_eq!(resources.images, 0);
+
+        // Resources inherited from the page tree are reported too
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Subtype" => "Image", "Width" => 1, "Height" => 1 },
+            vec![0],
+        ));
+        let pages_id = doc
+            .get_dictionary(pages[&2])
+            .and_then(|p| p.get(b"Parent"))
+            .and_then(|p| p.as_reference())
+            .unwrap();
+        doc.get_dictionary_mut(pages_id).unwrap().set(
+            "Resources",
+            dictionary! {
+                "XObject" => dictionary! { "Im0" => Object::Reference(image) },
+                "ExtGState" => dictionary! { "GS0" => dictionary! { "CA" => 1 } },
+            },
+        );
+        doc.get_dictionary_mut(pages[&2])
+            .unwrap()
+            .remove(b"Resources");
+        doc.save(&input).unwrap();
+
+        let inherited = get_page_resources(input.to_str().unwrap(), 2).unwrap();
+        assert!(inherited.fonts.is_empty());
+        assert_eq!(inherited.xobjects, vec!["Im0".to_string()]);
+        assert_eq!(inherited.images, 1);
+        assert_eq!(inherited.ext_gstates, vec!["GS0".to_string()]);
+        assert!(get_page_resources(input.to_str().unwrap(), 3).is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_estimate_print_cost_mixed_document() {
+        let (test_dir, output_dir) = setup_unique_paths("print_cost");
+        let input = test_dir.join("mixed.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 4, "Cost").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        doc.change_page_content(pages[&3], b"0 0 1 rg 0 0 50 50 re f".to_vec())
+            .unwrap();
+        doc.save(&input).unwrap();
+
+        let cost = estimate_print_cost(input.to_str().unwrap(), 0.25, 0.05).unwrap();
+        assert_eq!((cost.color_pages, cost.bw_pages), (1, 3));
+        assert_eq!(cost.color_page_numbers, vec![3]);
+        assert!((cost.total - 0.40).abs() < 1e-9, "total was {}", cost.total);
+
+        assert!(estimate_print_cost(input.to_str().unwrap(), -1.0, 0.05).is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_get_page_info_inherits_and_rotates() {
+        let (test_dir, output_dir) = setup_unique_paths("page_info");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Info").unwrap();
+
+        // Page 2 takes an A4 MediaBox and a rotation from the page tree root
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let root_id = doc
+            .get_dictionary(pages[&2])
+            .and_then(|p| p.get(b"Parent"))
+            .and_then(|p| p.as_reference())
+            .unwrap();
+        let root = doc.get_dictionary_mut(root_id).unwrap();
+        root.set("MediaBox", vec![0.into(), 0.into(), 595.into(), 842.into()]);
+        root.set("Rotate", 90);
+        let page = doc.get_dictionary_mut(pages[&2]).unwrap();
+        page.remove(b"MediaBox");
+        doc.get_dictionary_mut(pages[&1]).unwrap().set("Rotate", 0);
+        doc.save(&input).unwrap();
+
+        let info = get_page_info(input.to_str().unwrap()).unwrap();
+        assert_eq!(
+            info,
+            vec![
+                PageInfo {
+                    page_number: 1,
+                    width: 612.0,
+                    height: 792.0,
+                    rotation: 0,
+                },
+                PageInfo {
+                    page_number: 2,
+                    width: 842.0,
+                    height: 595.0,
+                    rotation: 90,
+                },
+            ]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
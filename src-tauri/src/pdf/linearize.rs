@@ -0,0 +1,154 @@
+use crate::pdf::utils::{
+    find_references_recursive, load_document, rebuild_clean, update_references_recursive,
+};
+use lopdf::{Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Objects in the order the first page needs them: the catalog, then the first page
+/// and everything it references (not following `/Parent` back into the page tree),
+/// then every other object in its original order.
+fn first_page_order(doc: &Document) -> Result<Vec<ObjectId>, String> {
+    let root_id = doc
+        .trailer
+        .get(b"Root")
+        .and_then(|o| o.as_reference())
+        .map_err(|e| format!("Document has no catalog (/Root): {}", e))?;
+
+    let mut queue: VecDeque<ObjectId> = VecDeque::new();
+    let mut seen: HashSet<ObjectId> = HashSet::new();
+    let mut order = vec![root_id];
+    seen.insert(root_id);
+    if let Some(&first_page) = doc.get_pages().get(&1) {
+        queue.push_back(first_page);
+        seen.insert(first_page);
+    }
+    while let Some(id) = queue.pop_front() {
+        let Ok(object) = doc.get_object(id) else {
+            continue;
+        };
+        order.push(id);
+        let result = match object {
+            Object::Dictionary(dict) => {
+                let mut dict = dict.clone();
+                dict.remove(b"Parent");
+                find_references_recursive(&Object::Dictionary(dict), &mut queue, &mut seen)
+            }
+            other => find_references_recursive(other, &mut queue, &mut seen),
+        };
+        result.map_err(|e| format!("Failed to walk objects of the first page: {}", e))?;
+    }
+
+    order.extend(doc.objects.keys().filter(|id| !seen.contains(id)));
+    Ok(order)
+}
+
+/// Rewrite `path` so the catalog, the first page and the objects it uses come first
+/// in the file, letting a viewer reading the file progressively show page 1 before
+/// the rest has arrived. Objects are renumbered in that order, which is the order
+/// lopdf writes them in.
+///
+/// This is not full linearization: lopdf writes its xref table at the end and cannot
+/// emit the linearization parameter dictionary or hint streams, so viewers will not
+/// report the file as "Fast Web View" enabled.
+#[tauri::command]
+pub fn linearize_pdf(path: &str, output_path: &str, clean: Option<bool>) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+    // Rebuild first: the first-page ordering below must be the last renumbering
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+    doc.prune_objects();
+
+    let order = first_page_order(&doc)?;
+    let id_map: HashMap<ObjectId, ObjectId> = order
+        .iter()
+        .enumerate()
+        .map(|(index, old_id)| (*old_id, (index as u32 + 1, 0)))
+        .collect();
+
+    let mut objects = BTreeMap::new();
+    for (old_id, mut object) in std::mem::take(&mut doc.objects) {
+        update_references_recursive(&mut object, &id_map)
+            .map_err(|e| format!("Failed to renumber object {:?}: {}", old_id, e))?;
+        objects.insert(id_map[&old_id], object);
+    }
+    doc.objects = objects;
+    doc.max_id = order.len() as u32;
+    for (_, value) in doc.trailer.iter_mut() {
+        update_references_recursive(value, &id_map)
+            .map_err(|e| format!("Failed to renumber trailer: {}", e))?;
+    }
+    // Links to the source file's xref sections are meaningless in the new file
+    doc.trailer.remove(b"Prev");
+    doc.trailer.remove(b"XRefStm");
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::xref::XrefEntry;
+    use lopdf::{dictionary, Stream};
+
+    fn offset(doc: &Document, id: ObjectId) -> u32 {
+        match doc.reference_table.get(id.0) {
+            Some(XrefEntry::Normal { offset, .. }) => *offset,
+            other => panic!("object {:?} has no file offset: {:?}", id, other),
+        }
+    }
+
+    #[test]
+    fn test_linearize_pdf_writes_first_page_first() {
+        let (test_dir, output_dir) = setup_unique_paths("linearize");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("linearized.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Web").unwrap();
+
+        // New content for page 1 gets the highest object number, so it is written last
+        let mut doc = Document::load(&input).unwrap();
+        let first_page = doc.get_pages()[&1];
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 72 720 Td (Cover) Tj ET".to_vec(),
+        ));
+        doc.get_dictionary_mut(first_page)
+            .unwrap()
+            .set("Contents", Object::Reference(content_id));
+        doc.save(&input).unwrap();
+
+        linearize_pdf(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+
+        let linearized = Document::load(&output).unwrap();
+        let pages = linearized.get_pages();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(linearized.extract_text(&[1]).unwrap().trim(), "Cover");
+        assert_eq!(linearized.extract_text(&[3]).unwrap().trim(), "Web-Page 3");
+
+        let first_content = linearized.get_page_contents(pages[&1])[0];
+        let second_content = linearized.get_page_contents(pages[&2])[0];
+        assert!(offset(&linearized, pages[&1]) < offset(&linearized, pages[&2]));
+        assert!(offset(&linearized, first_content) < offset(&linearized, pages[&2]));
+        assert!(offset(&linearized, first_content) < offset(&linearized, second_content));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -1,22 +1,137 @@
 // Necessary imports
-use crate::pdf::utils::manual_deep_copy;
-use lopdf::{dictionary, Document, Object};
+use crate::pdf::cancel::CancellationToken;
+use crate::pdf::editor::format_matrix_value;
+use crate::pdf::extractor_text::extract_page_text;
+use crate::pdf::metadata::{check_metadata_keys, utf16_text_string};
+use crate::pdf::outline::{read_outline_entries, write_outline_entries, OutlineEntry};
+use crate::pdf::utils::{
+    assert_max_pages, assert_page_total, get_inherited_attribute, load_document,
+    manual_deep_copy, rebuild_clean, rect_from_object,
+};
+use lopdf::{dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use tauri::State;
 
-#[tauri::command]
-pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
+/// Shift the page numbers of a source document's outline to where its pages land in the
+/// merged document. Items that point nowhere and have nothing left under them are dropped.
+fn offset_outline_entries(entries: Vec<OutlineEntry>, offset: u32) -> Vec<OutlineEntry> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let children = offset_outline_entries(entry.children, offset);
+            let page = entry.page.map(|p| p + offset);
+            (page.is_some() || !children.is_empty()).then_some(OutlineEntry {
+                page,
+                children,
+                ..entry
+            })
+        })
+        .collect()
+}
+
+/// Scale a page's content uniformly to fit `to`, centered, and make `to` its MediaBox.
+fn fit_page_to_box(
+    doc: &mut Document,
+    page_id: ObjectId,
+    from: [f64; 4],
+    to: [f64; 4],
+) -> Result<(), String> {
+    let (from_w, from_h) = (from[2] - from[0], from[3] - from[1]);
+    let (to_w, to_h) = (to[2] - to[0], to[3] - to[1]);
+    if from_w <= 0.0 || from_h <= 0.0 {
+        return Err(format!("Page {:?} has an empty MediaBox.", page_id));
+    }
+    let scale = (to_w / from_w).min(to_h / from_h);
+    let tx = to[0] + (to_w - from_w * scale) / 2.0 - from[0] * scale;
+    let ty = to[1] + (to_h - from_h * scale) / 2.0 - from[1] * scale;
+    let cm = [scale, 0.0, 0.0, scale, tx, ty]
+        .iter()
+        .map(|v| format_matrix_value(*v))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let original = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read content of page {:?}: {}", page_id, e))?;
+    let mut content = format!("q {} cm\n", cm).into_bytes();
+    content.extend_from_slice(&original);
+    content.extend_from_slice(b"\nQ");
+    doc.change_page_content(page_id, content)
+        .map_err(|e| format!("Failed to update content of page {:?}: {}", page_id, e))?;
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| format!("Copied page {:?} is not a dictionary: {}", page_id, e))?;
+    for key in [&b"CropBox"[..], b"TrimBox", b"BleedBox", b"ArtBox"] {
+        page.remove(key);
+    }
+    page.set(
+        "MediaBox",
+        to.iter().map(|v| Object::Real(*v as f32)).collect::<Vec<_>>(),
+    );
+    Ok(())
+}
+
+/// Merge `paths` into one document. Pages keep their own sizes unless
+/// `normalize_to_first` is set, in which case every page is scaled (preserving its
+/// aspect ratio, centered) to the size of the first page of the first document.
+///
+/// Object numbering is deterministic, so merging identical inputs produces
+/// byte-identical output that can be verified by hash.
+///
+/// `cancel_operation` stops the merge between source files with "Operation cancelled";
+/// nothing is written in that case. The command runs off the main thread so the cancel
+/// request is handled while it works, and clears any earlier cancel before starting.
+#[tauri::command(async)]
+pub fn merge_pdfs(
+    paths: Vec<&str>,
+    output_path: &str,
+    max_pages: Option<u32>,
+    normalize_to_first: bool,
+    metadata: Option<BTreeMap<String, String>>,
+    cancel: State<'_, CancellationToken>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    cancel.reset();
+    merge_pdfs_cancellable(
+        paths,
+        output_path,
+        max_pages,
+        normalize_to_first,
+        metadata,
+        &cancel,
+        clean,
+    )
+}
+
+/// `merge_pdfs` with an explicit cancellation token, for callers outside a Tauri
+/// command.
+pub(crate) fn merge_pdfs_cancellable(
+    paths: Vec<&str>,
+    output_path: &str,
+    max_pages: Option<u32>,
+    normalize_to_first: bool,
+    metadata: Option<BTreeMap<String, String>>,
+    cancel: &CancellationToken,
+    clean: Option<bool>,
+) -> Result<(), String> {
     // --- Input Validation & Dir Creation (as before) ---
-    if paths.is_empty() { return Err("No PDF files provided for merging.".to_string()); }
-    if paths.len() == 1 {
+    if paths.is_empty() {
+        return Err("No PDF files provided for merging.".to_string());
+    }
+    if let Some(fields) = &metadata {
+        check_metadata_keys(fields.keys())?;
+    }
+    if paths.len() == 1 && !normalize_to_first && metadata.is_none() && !clean.unwrap_or(false) {
         let source_path = paths[0];
-        let p = Path::new(source_path);
-        if !p.exists() {
-            return Err(format!("Input file not found: {}", source_path));
-        }
-        if !p.is_file() {
-            return Err(format!("Input path is not a file: {}", source_path));
+        // Load even though the file is copied as-is, so a non-PDF is rejected
+        let doc = load_document(source_path, None)?;
+        if let Some(max) = max_pages {
+            assert_max_pages(&doc, max)?;
         }
+        cancel.check()?;
         if let Some(parent_dir) = Path::new(output_path).parent() {
             if !parent_dir.exists() {
                 fs::create_dir_all(parent_dir).map_err(|e| {
@@ -38,15 +153,20 @@ pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
             })?;
         return Ok(());
     }
-    for path in &paths {
-        let p = Path::new(path);
-        if !p.exists() {
-            return Err(format!("Input file not found: {}", path));
-        }
-        if !p.is_file() {
-            return Err(format!("Input path is not a file: {}", path));
-        }
+    // --- Load every input first so the page limit is checked before copying ---
+    let mut sources = Vec::with_capacity(paths.len());
+    for path in paths {
+        cancel.check()?;
+        sources.push((path, load_document(path, None)?));
+    }
+    if let Some(max) = max_pages {
+        let total = sources.iter().map(|(_, doc)| doc.get_pages().len()).sum();
+        assert_page_total(total, max)?;
     }
+
+    // --- Build a fresh document using deep copy for every page ---
+    let (first_path, first_doc) = (sources[0].0, &sources[0].1);
+
     if let Some(parent_dir) = Path::new(output_path).parent() {
         if !parent_dir.exists() {
             fs::create_dir_all(parent_dir).map_err(|e| {
@@ -59,27 +179,27 @@ pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
         }
     }
 
-    // --- Build a fresh document using deep copy for every page ---
-    let first_path = paths[0];
-    let first_doc = Document::load(first_path)
-        .map_err(|e| format!("Failed to load base PDF '{}': {}", first_path, e))?;
-
     let mut target_doc = Document::with_version(first_doc.version.clone());
     let target_pages_id = target_doc.new_object_id();
     let target_catalog_id = target_doc.new_object_id();
     let mut kids = Vec::new();
+    let mut first_box: Option<[f64; 4]> = None;
+    let mut outline = Vec::new();
 
-    for path in paths {
-        let src_doc = Document::load(path)
-            .map_err(|e| format!("Failed to load source PDF '{}': {}", path, e))?;
+    for (path, src_doc) in &sources {
+        cancel.check()?;
 
         let page_ids: Vec<_> = src_doc.get_pages().values().cloned().collect();
         if page_ids.is_empty() {
             continue;
         }
 
-        let id_map = manual_deep_copy(&src_doc, &mut target_doc, &page_ids)
+        let id_map = manual_deep_copy(src_doc, &mut target_doc, &page_ids)
             .map_err(|e| format!("Failed to copy pages from '{}': {}", path, e))?;
+        outline.extend(offset_outline_entries(
+            read_outline_entries(src_doc),
+            kids.len() as u32,
+        ));
 
         for old_page_id in page_ids {
             let new_page_id = *id_map.get(&old_page_id).ok_or_else(|| {
@@ -89,13 +209,26 @@ pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
                 )
             })?;
 
+            if normalize_to_first {
+                let page_box = get_inherited_attribute(src_doc, old_page_id, b"MediaBox")
+                    .and_then(rect_from_object)
+                    .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+                match first_box {
+                    None => first_box = Some(page_box),
+                    Some(target_box) if target_box != page_box => {
+                        fit_page_to_box(&mut target_doc, new_page_id, page_box, target_box)?;
+                    }
+                    Some(_) => {}
+                }
+            }
+
             {
-                let page_obj = target_doc.get_object_mut(new_page_id).map_err(|e| {
-                    format!("Failed to fetch copied page {:?}: {}", new_page_id, e)
-                })?;
-                let page_dict = page_obj.as_dict_mut().map_err(|_| {
-                    format!("Copied page {:?} is not a dictionary", new_page_id)
-                })?;
+                let page_obj = target_doc
+                    .get_object_mut(new_page_id)
+                    .map_err(|e| format!("Failed to fetch copied page {:?}: {}", new_page_id, e))?;
+                let page_dict = page_obj
+                    .as_dict_mut()
+                    .map_err(|_| format!("Copied page {:?} is not a dictionary", new_page_id))?;
                 page_dict.set("Parent", Object::Reference(target_pages_id));
             }
 
@@ -118,9 +251,46 @@ pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
             "Pages" => Object::Reference(target_pages_id),
         }),
     );
-    target_doc.trailer.set("Root", Object::Reference(target_catalog_id));
+    target_doc
+        .trailer
+        .set("Root", Object::Reference(target_catalog_id));
+
+    let merged_pages: BTreeMap<u32, ObjectId> = kids
+        .iter()
+        .filter_map(|kid| kid.as_reference().ok())
+        .enumerate()
+        .map(|(i, id)| (i as u32 + 1, id))
+        .collect();
+    write_outline_entries(&mut target_doc, &outline, &merged_pages)?;
+
+    // The merged file takes the first document's Info, with `metadata` applied on top
+    let mut info_id = match first_doc.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) => manual_deep_copy(first_doc, &mut target_doc, &[*id])
+            .map_err(|e| format!("Failed to copy Info from '{}': {}", first_path, e))?
+            .get(id)
+            .copied(),
+        Ok(Object::Dictionary(info)) => Some(target_doc.add_object(info.clone())),
+        _ => None,
+    };
+    if let Some(fields) = metadata {
+        let id = *info_id.get_or_insert_with(|| target_doc.add_object(dictionary! {}));
+        let info = target_doc
+            .get_dictionary_mut(id)
+            .map_err(|e| format!("Failed to access merged Info dictionary: {}", e))?;
+        for (key, value) in &fields {
+            info.set(key.as_str(), utf16_text_string(value));
+        }
+    }
+    if let Some(id) = info_id {
+        target_doc.trailer.set("Info", Object::Reference(id));
+    }
 
     target_doc.compress();
+    cancel.check()?;
+    if clean.unwrap_or(false) {
+        target_doc = rebuild_clean(&target_doc)?;
+    }
+
     target_doc
         .save(output_path)
         .map_err(|e| format!("Failed to save merged PDF to '{}': {}", output_path, e))?;
@@ -128,11 +298,339 @@ pub fn merge_pdfs(paths: Vec<&str>, output_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Assemble a new document whose pages are `sequence`, given as (index into `docs`, page
+/// id) pairs, in that order. Pages are deep-copied once per source document so shared
+/// resources stay shared; a page listed twice gets its own page dictionary. A sequence
+/// longer than `max_pages` is rejected before anything is copied.
+pub(crate) fn assemble_page_sequence(
+    docs: &[Document],
+    sequence: &[(usize, ObjectId)],
+    max_pages: Option<u32>,
+) -> Result<Document, String> {
+    if let Some(max) = max_pages {
+        assert_page_total(sequence.len(), max)?;
+    }
+    let version = docs
+        .first()
+        .map(|d| d.version.clone())
+        .unwrap_or_else(|| "1.5".to_string());
+    let mut target_doc = Document::with_version(version);
+    let target_pages_id = target_doc.new_object_id();
+    let target_catalog_id = target_doc.new_object_id();
+
+    let mut id_maps: Vec<HashMap<ObjectId, ObjectId>> = Vec::with_capacity(docs.len());
+    for (doc_index, doc) in docs.iter().enumerate() {
+        let mut page_ids: Vec<ObjectId> = sequence
+            .iter()
+            .filter(|(i, _)| *i == doc_index)
+            .map(|(_, id)| *id)
+            .collect();
+        page_ids.sort();
+        page_ids.dedup();
+        let id_map = manual_deep_copy(doc, &mut target_doc, &page_ids)
+            .map_err(|e| format!("Failed to copy pages: {}", e))?;
+        id_maps.push(id_map);
+    }
+
+    let mut used: HashSet<ObjectId> = HashSet::new();
+    let mut kids = Vec::with_capacity(sequence.len());
+    for (doc_index, old_page_id) in sequence {
+        let mut new_page_id = *id_maps
+            .get(*doc_index)
+            .and_then(|m| m.get(old_page_id))
+            .ok_or_else(|| {
+                format!(
+                    "Internal error: mapped page id for {:?} missing",
+                    old_page_id
+                )
+            })?;
+        if !used.insert(new_page_id) {
+            let duplicate = target_doc
+                .get_object(new_page_id)
+                .map_err(|e| format!("Failed to fetch copied page {:?}: {}", new_page_id, e))?
+                .clone();
+            new_page_id = target_doc.add_object(duplicate);
+        }
+        target_doc
+            .get_dictionary_mut(new_page_id)
+            .map_err(|e| format!("Copied page {:?} is not a dictionary: {}", new_page_id, e))?
+            .set("Parent", Object::Reference(target_pages_id));
+        kids.push(Object::Reference(new_page_id));
+    }
+
+    target_doc.objects.insert(
+        target_pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => Object::Integer(kids.len() as i64),
+            "Kids" => Object::Array(kids),
+        }),
+    );
+    target_doc.objects.insert(
+        target_catalog_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => Object::Reference(target_pages_id),
+        }),
+    );
+    target_doc
+        .trailer
+        .set("Root", Object::Reference(target_catalog_id));
+
+    // The deep copy follows /Parent into the source page tree; drop what it dragged in
+    target_doc.prune_objects();
+    Ok(target_doc)
+}
+
+/// Write the pages in `sequence` (see `assemble_page_sequence`) to `output_path`.
+pub(crate) fn build_from_page_sequence(
+    docs: &[Document],
+    sequence: &[(usize, ObjectId)],
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut target_doc = assemble_page_sequence(docs, sequence, max_pages)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    target_doc.compress();
+    if clean.unwrap_or(false) {
+        target_doc = rebuild_clean(&target_doc)?;
+    }
+
+    target_doc
+        .save(output_path)
+        .map_err(|e| format!("Failed to save merged PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Reassemble a double-sided scan from a pass of fronts and a pass of backs,
+/// interleaving them as front 1, back 1, front 2, back 2, ... When the stack was
+/// flipped for the second pass the backs come out last-page-first, so
+/// `backs_reversed` walks them from the end.
+#[tauri::command]
+pub fn merge_duplex(
+    front_path: &str,
+    back_path: &str,
+    backs_reversed: bool,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let fronts = load_document(front_path, None)?;
+    let backs = load_document(back_path, None)?;
+
+    let front_pages: Vec<ObjectId> = fronts.get_pages().into_values().collect();
+    let mut back_pages: Vec<ObjectId> = backs.get_pages().into_values().collect();
+    if front_pages.len() != back_pages.len() {
+        return Err(format!(
+            "Fronts '{}' have {} pages but backs '{}' have {}; both scans must have the same page count.",
+            front_path,
+            front_pages.len(),
+            back_path,
+            back_pages.len()
+        ));
+    }
+    if backs_reversed {
+        back_pages.reverse();
+    }
+
+    let sequence: Vec<(usize, ObjectId)> = front_pages
+        .into_iter()
+        .zip(back_pages)
+        .flat_map(|(front, back)| [(0, front), (1, back)])
+        .collect();
+
+    build_from_page_sequence(&[fronts, backs], &sequence, output_path, max_pages, clean)
+}
+
+/// Interleave the pages of `front` and `back` (front 1, back 1, front 2, ...). Unlike
+/// `merge_duplex` the page counts may differ: the pages left over in the longer
+/// document follow the interleaved ones. `reverse_back` takes the backs last page
+/// first, for a stack that was flipped before scanning the second side.
+#[tauri::command]
+pub fn merge_interleaved(
+    front: &str,
+    back: &str,
+    reverse_back: bool,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let front_doc = load_document(front, None)?;
+    let back_doc = load_document(back, None)?;
+
+    let front_pages: Vec<ObjectId> = front_doc.get_pages().into_values().collect();
+    let mut back_pages: Vec<ObjectId> = back_doc.get_pages().into_values().collect();
+    if reverse_back {
+        back_pages.reverse();
+    }
+
+    let mut sequence = Vec::with_capacity(front_pages.len() + back_pages.len());
+    let mut fronts = front_pages.into_iter();
+    let mut backs = back_pages.into_iter();
+    loop {
+        let (next_front, next_back) = (fronts.next(), backs.next());
+        if next_front.is_none() && next_back.is_none() {
+            break;
+        }
+        sequence.extend(next_front.map(|id| (0, id)));
+        sequence.extend(next_back.map(|id| (1, id)));
+    }
+
+    build_from_page_sequence(
+        &[front_doc, back_doc],
+        &sequence,
+        output_path,
+        max_pages,
+        clean,
+    )
+}
+
+/// Put the first page of `cover_path` in front of every page of `body_path`.
+#[tauri::command]
+pub fn prepend_cover(
+    cover_path: &str,
+    body_path: &str,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let cover = load_document(cover_path, None)?;
+    let body = load_document(body_path, None)?;
+
+    let cover_page = *cover
+        .get_pages()
+        .get(&1)
+        .ok_or_else(|| format!("Cover document '{}' has no pages.", cover_path))?;
+    let sequence: Vec<(usize, ObjectId)> = std::iter::once((0, cover_page))
+        .chain(body.get_pages().into_values().map(|id| (1, id)))
+        .collect();
+
+    build_from_page_sequence(&[cover, body], &sequence, output_path, max_pages, clean)
+}
+
+/// Insert every page of `insert` into `base` after page `after_page`; 0 puts them in
+/// front of the first page.
+#[tauri::command]
+pub fn append_pdf(
+    base: &str,
+    insert: &str,
+    after_page: u32,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let base_doc = load_document(base, None)?;
+    let insert_doc = load_document(insert, None)?;
+
+    let base_pages: Vec<ObjectId> = base_doc.get_pages().into_values().collect();
+    if after_page as usize > base_pages.len() {
+        return Err(format!(
+            "Cannot insert after page {}: '{}' has only {} pages.",
+            after_page,
+            base,
+            base_pages.len()
+        ));
+    }
+    let (before, after) = base_pages.split_at(after_page as usize);
+    let sequence: Vec<(usize, ObjectId)> = before
+        .iter()
+        .map(|id| (0, *id))
+        .chain(insert_doc.get_pages().into_values().map(|id| (1, id)))
+        .chain(after.iter().map(|id| (0, *id)))
+        .collect();
+
+    build_from_page_sequence(
+        &[base_doc, insert_doc],
+        &sequence,
+        output_path,
+        max_pages,
+        clean,
+    )
+}
+
+/// Collect every page, across all inputs in order, whose extracted text contains
+/// `query` (case-insensitive) into one document. Returns how many pages matched in
+/// each source.
+#[tauri::command]
+pub fn merge_pages_matching(
+    paths: Vec<&str>,
+    query: &str,
+    output_path: &str,
+    max_pages: Option<u32>,
+    clean: Option<bool>,
+) -> Result<Vec<u32>, String> {
+    if paths.is_empty() {
+        return Err("No input files provided for merging.".to_string());
+    }
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Err("Search query must not be empty.".to_string());
+    }
+
+    let docs = paths
+        .iter()
+        .map(|path| load_document(path, None))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut sequence = Vec::new();
+    let mut matches = Vec::with_capacity(docs.len());
+    for (doc_index, doc) in docs.iter().enumerate() {
+        let before = sequence.len();
+        for page_id in doc.get_pages().into_values() {
+            // Pages whose text can't be decoded simply don't match
+            let text = extract_page_text(doc, page_id).unwrap_or_default();
+            if text.to_lowercase().contains(&needle) {
+                sequence.push((doc_index, page_id));
+            }
+        }
+        matches.push((sequence.len() - before) as u32);
+    }
+    if sequence.is_empty() {
+        return Err(format!("No pages contain '{}'.", query));
+    }
+
+    build_from_page_sequence(&docs, &sequence, output_path, max_pages, clean)?;
+    Ok(matches)
+}
+
 
 // --- Tests ---
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    /// The command takes its token from Tauri state; tests merge with a fresh one.
+    fn merge_pdfs(
+        paths: Vec<&str>,
+        output_path: &str,
+        max_pages: Option<u32>,
+        normalize_to_first: bool,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> Result<(), String> {
+        merge_pdfs_cancellable(
+            paths,
+            output_path,
+            max_pages,
+            normalize_to_first,
+            metadata,
+            &CancellationToken::default(),
+            None,
+        )
+    }
     use crate::pdf::test_utils::create_minimal_pdf;
     use lopdf::Document;
     use std::fs;
@@ -193,7 +691,7 @@ mod tests {
         assert!(path2.exists(), "doc2 should exist after creation");
 
         let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap()];
-        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap());
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, false, None);
 
         // Assertions remain the same
         assert!(result.is_ok(), "merge_pdfs failed: {:?}", result.err());
@@ -244,7 +742,7 @@ mod tests {
 
 
         let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap(), path3.to_str().unwrap()];
-        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap());
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, false, None);
 
         assert!(result.is_ok(), "merge_pdfs failed: {:?}", result.err());
         assert!(output_path.exists(), "Output file was not created");
@@ -269,10 +767,31 @@ mod tests {
         let mut file = fs::File::create(&not_pdf_path).expect("Failed to create dummy text file");
         writeln!(file, "This is text, not PDF.").expect("Failed to write to text file");
         let paths_vec = vec![path1.to_str().unwrap(), not_pdf_path.to_str().unwrap()];
-        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap());
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, false, None);
         assert!(result.is_err());
         let err_msg = result.err().unwrap();
-        assert!(err_msg.contains("Failed to load source PDF"));
+        assert!(err_msg.contains("Failed to load PDF"));
+        assert!(err_msg.contains(not_pdf_path.to_str().unwrap()));
+        assert!(!output_path.exists());
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_single_input_not_a_pdf() {
+        let (test_dir, output_dir) = get_unique_paths("merge_single_not_a_pdf");
+        let not_pdf_path = test_dir.join("renamed.pdf");
+        let output_path = output_dir.join("merged_single.pdf");
+        fs::write(&not_pdf_path, "This is text, not PDF.").unwrap();
+
+        let result = merge_pdfs(
+            vec![not_pdf_path.to_str().unwrap()],
+            output_path.to_str().unwrap(),
+            None,
+            false,
+            None,
+        );
+        let err_msg = result.unwrap_err();
+        assert!(err_msg.contains("Failed to load PDF"));
         assert!(err_msg.contains(not_pdf_path.to_str().unwrap()));
         assert!(!output_path.exists());
         teardown_unique_paths(&test_dir, &output_dir);
@@ -291,7 +810,7 @@ mod tests {
         create_minimal_pdf(path2.to_str().unwrap(), 1, "Out2").expect("Failed to create out2");
 
         let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap()];
-        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap());
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, false, None);
 
         assert!(
             result.is_ok(),
@@ -306,4 +825,657 @@ mod tests {
         }
         teardown_unique_paths(&test_dir, &output_dir);
     }
+
+    fn page_text(doc: &Document, page_id: lopdf::ObjectId) -> String {
+        String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_merge_duplex_reversed_backs() {
+        let (test_dir, output_dir) = get_unique_paths("merge_duplex");
+        let fronts = test_dir.join("fronts.pdf");
+        let backs = test_dir.join("backs.pdf");
+        let output_path = output_dir.join("duplex.pdf");
+        create_minimal_pdf(fronts.to_str().unwrap(), 3, "Front").unwrap();
+        create_minimal_pdf(backs.to_str().unwrap(), 3, "Back").unwrap();
+
+        let result = merge_duplex(
+            fronts.to_str().unwrap(),
+            backs.to_str().unwrap(),
+            true,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(result.is_ok(), "merge_duplex failed: {:?}", result.err());
+
+        let merged = Document::load(&output_path).unwrap();
+        let order: Vec<String> = merged
+            .get_pages()
+            .values()
+            .map(|id| page_text(&merged, *id))
+            .collect();
+        let expected = [
+            "Front-Page 1",
+            "Back-Page 3",
+            "Front-Page 2",
+            "Back-Page 2",
+            "Front-Page 3",
+            "Back-Page 1",
+        ];
+        assert_eq!(order.len(), expected.len());
+        for (content, label) in order.iter().zip(expected) {
+            assert!(content.contains(label), "expected {} in {}", label, content);
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    fn page_order(path: &std::path::Path) -> Vec<String> {
+        let merged = Document::load(path).unwrap();
+        merged
+            .get_pages()
+            .values()
+            .map(|id| page_text(&merged, *id))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_interleaved_equal_counts() {
+        let (test_dir, output_dir) = get_unique_paths("merge_interleaved");
+        let fronts = test_dir.join("fronts.pdf");
+        let backs = test_dir.join("backs.pdf");
+        let output_path = output_dir.join("interleaved.pdf");
+        create_minimal_pdf(fronts.to_str().unwrap(), 2, "Front").unwrap();
+        create_minimal_pdf(backs.to_str().unwrap(), 2, "Back").unwrap();
+
+        merge_interleaved(
+            fronts.to_str().unwrap(),
+            backs.to_str().unwrap(),
+            false,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let order = page_order(&output_path);
+        let expected = ["Front-Page 1", "Back-Page 1", "Front-Page 2", "Back-Page 2"];
+        assert_eq!(order.len(), expected.len());
+        for (content, label) in order.iter().zip(expected) {
+            assert!(content.contains(label), "expected {} in {}", label, content);
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_interleaved_reverse_back_uneven() {
+        let (test_dir, output_dir) = get_unique_paths("merge_interleaved_reverse");
+        let fronts = test_dir.join("fronts.pdf");
+        let backs = test_dir.join("backs.pdf");
+        let output_path = output_dir.join("interleaved.pdf");
+        create_minimal_pdf(fronts.to_str().unwrap(), 3, "Front").unwrap();
+        create_minimal_pdf(backs.to_str().unwrap(), 2, "Back").unwrap();
+
+        merge_interleaved(
+            fronts.to_str().unwrap(),
+            backs.to_str().unwrap(),
+            true,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // The third front has no back left to pair with and goes last
+        let order = page_order(&output_path);
+        let expected = [
+            "Front-Page 1",
+            "Back-Page 2",
+            "Front-Page 2",
+            "Back-Page 1",
+            "Front-Page 3",
+        ];
+        assert_eq!(order.len(), expected.len());
+        for (content, label) in order.iter().zip(expected) {
+            assert!(content.contains(label), "expected {} in {}", label, content);
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_duplex_page_count_mismatch() {
+        let (test_dir, output_dir) = get_unique_paths("merge_duplex_mismatch");
+        let fronts = test_dir.join("fronts.pdf");
+        let backs = test_dir.join("backs.pdf");
+        let output_path = output_dir.join("duplex.pdf");
+        create_minimal_pdf(fronts.to_str().unwrap(), 3, "Front").unwrap();
+        create_minimal_pdf(backs.to_str().unwrap(), 2, "Back").unwrap();
+
+        let result = merge_duplex(
+            fronts.to_str().unwrap(),
+            backs.to_str().unwrap(),
+            false,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("same page count"));
+        assert!(!output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_respects_max_pages() {
+        let (test_dir, output_dir) = get_unique_paths("merge_max_pages");
+        let path1 = test_dir.join("cap1.pdf");
+        let path2 = test_dir.join("cap2.pdf");
+        let output_path = output_dir.join("capped.pdf");
+        create_minimal_pdf(path1.to_str().unwrap(), 3, "Cap1").unwrap();
+        create_minimal_pdf(path2.to_str().unwrap(), 2, "Cap2").unwrap();
+
+        let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap()];
+        let result = merge_pdfs(
+            paths_vec.clone(),
+            output_path.to_str().unwrap(),
+            Some(4),
+            false,
+            None,
+        );
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Result would have 5 pages, limit is 4.");
+        assert!(!output_path.exists());
+
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), Some(5), false, None);
+        assert!(result.is_ok(), "merge at the limit failed: {:?}", result.err());
+        assert!(output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_page_sequence_commands_respect_max_pages() {
+        let (test_dir, output_dir) = get_unique_paths("sequence_max_pages");
+        let base = test_dir.join("base.pdf");
+        let insert = test_dir.join("insert.pdf");
+        let output_path = output_dir.join("capped.pdf");
+        create_minimal_pdf(base.to_str().unwrap(), 3, "Base").unwrap();
+        create_minimal_pdf(insert.to_str().unwrap(), 2, "Insert").unwrap();
+
+        let result = append_pdf(
+            base.to_str().unwrap(),
+            insert.to_str().unwrap(),
+            1,
+            output_path.to_str().unwrap(),
+            Some(4),
+            None,
+        );
+        assert_eq!(result.unwrap_err(), "Result would have 5 pages, limit is 4.");
+        let result = prepend_cover(
+            insert.to_str().unwrap(),
+            base.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            Some(3),
+            None,
+        );
+        assert_eq!(result.unwrap_err(), "Result would have 4 pages, limit is 3.");
+        assert!(!output_path.exists());
+
+        let result = merge_interleaved(
+            base.to_str().unwrap(),
+            insert.to_str().unwrap(),
+            false,
+            output_path.to_str().unwrap(),
+            Some(5),
+            None,
+        );
+        assert!(result.is_ok(), "merge at the limit failed: {:?}", result.err());
+        assert_eq!(Document::load(&output_path).unwrap().get_pages().len(), 5);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_prepend_cover() {
+        let (test_dir, output_dir) = get_unique_paths("prepend_cover");
+        let cover = test_dir.join("cover.pdf");
+        let body = test_dir.join("body.pdf");
+        let output_path = output_dir.join("with_cover.pdf");
+        create_minimal_pdf(cover.to_str().unwrap(), 1, "Cover").unwrap();
+        create_minimal_pdf(body.to_str().unwrap(), 3, "Body").unwrap();
+
+        let result = prepend_cover(
+            cover.to_str().unwrap(),
+            body.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(result.is_ok(), "prepend_cover failed: {:?}", result.err());
+
+        let merged = Document::load(&output_path).unwrap();
+        let order: Vec<String> = merged
+            .get_pages()
+            .values()
+            .map(|id| page_text(&merged, *id))
+            .collect();
+        assert_eq!(order.len(), 4);
+        assert!(order[0].contains("Cover-Page 1"));
+        assert!(order[1].contains("Body-Page 1"));
+        assert!(order[3].contains("Body-Page 3"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_append_pdf_after_page() {
+        let (test_dir, output_dir) = get_unique_paths("append_pdf");
+        let base = test_dir.join("base.pdf");
+        let insert = test_dir.join("insert.pdf");
+        let output_path = output_dir.join("combined.pdf");
+        create_minimal_pdf(base.to_str().unwrap(), 3, "Base").unwrap();
+        create_minimal_pdf(insert.to_str().unwrap(), 1, "Insert").unwrap();
+
+        let result = append_pdf(
+            base.to_str().unwrap(),
+            insert.to_str().unwrap(),
+            2,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(result.is_ok(), "append_pdf failed: {:?}", result.err());
+
+        let merged = Document::load(&output_path).unwrap();
+        let order: Vec<String> = merged
+            .get_pages()
+            .values()
+            .map(|id| page_text(&merged, *id))
+            .collect();
+        assert_eq!(order.len(), 4);
+        for (text, expected) in
+            order
+                .iter()
+                .zip(["Base-Page 1", "Base-Page 2", "Insert-Page 1", "Base-Page 3"])
+        {
+            assert!(text.contains(expected), "{:?} vs {}", text, expected);
+        }
+
+        let prepended = output_dir.join("prepended.pdf");
+        append_pdf(
+            base.to_str().unwrap(),
+            insert.to_str().unwrap(),
+            0,
+            prepended.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        let merged = Document::load(&prepended).unwrap();
+        assert!(page_text(&merged, merged.get_pages()[&1]).contains("Insert-Page 1"));
+
+        let too_far = append_pdf(
+            base.to_str().unwrap(),
+            insert.to_str().unwrap(),
+            4,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        );
+        assert!(too_far.is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    fn create_a4_pdf(path: &Path, prefix: &str) {
+        create_minimal_pdf(path.to_str().unwrap(), 1, prefix).unwrap();
+        let mut doc = Document::load(path).unwrap();
+        let page_id = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("MediaBox", vec![0.into(), 0.into(), 595.into(), 842.into()]);
+        doc.save(path).unwrap();
+    }
+
+    fn media_box(doc: &Document, page_num: u32) -> [f64; 4] {
+        let page_id = doc.get_pages()[&page_num];
+        rect_from_object(doc.get_dictionary(page_id).unwrap().get(b"MediaBox").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_merge_keeps_each_page_size() {
+        let (test_dir, output_dir) = get_unique_paths("merge_mixed_sizes");
+        let letter = test_dir.join("letter.pdf");
+        let a4 = test_dir.join("a4.pdf");
+        let output_path = output_dir.join("mixed.pdf");
+        create_minimal_pdf(letter.to_str().unwrap(), 1, "Letter").unwrap();
+        create_a4_pdf(&a4, "A4");
+
+        let paths_vec = vec![letter.to_str().unwrap(), a4.to_str().unwrap()];
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, false, None);
+        assert!(result.is_ok(), "merge failed: {:?}", result.err());
+
+        let merged = Document::load(&output_path).unwrap();
+        assert_eq!(media_box(&merged, 1), [0.0, 0.0, 612.0, 792.0]);
+        assert_eq!(media_box(&merged, 2), [0.0, 0.0, 595.0, 842.0]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_normalize_to_first_scales_pages() {
+        let (test_dir, output_dir) = get_unique_paths("merge_normalize");
+        let letter = test_dir.join("letter.pdf");
+        let a4 = test_dir.join("a4.pdf");
+        let output_path = output_dir.join("uniform.pdf");
+        create_minimal_pdf(letter.to_str().unwrap(), 1, "Letter").unwrap();
+        create_a4_pdf(&a4, "A4");
+
+        let paths_vec = vec![letter.to_str().unwrap(), a4.to_str().unwrap()];
+        let result = merge_pdfs(paths_vec, output_path.to_str().unwrap(), None, true, None);
+        assert!(result.is_ok(), "merge failed: {:?}", result.err());
+
+        let merged = Document::load(&output_path).unwrap();
+        assert_eq!(media_box(&merged, 1), [0.0, 0.0, 612.0, 792.0]);
+        assert_eq!(media_box(&merged, 2), [0.0, 0.0, 612.0, 792.0]);
+        // A4 is height-limited: 792 / 842 scale, centered horizontally
+        let content = page_text(&merged, merged.get_pages()[&2]);
+        assert!(content.starts_with("q 0.940618 0 0 0.940618 26.166271 0 cm"), "{}", content);
+        assert!(content.contains("A4-Page 1"));
+        let first = page_text(&merged, merged.get_pages()[&1]);
+        assert!(first.starts_with("BT"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_pages_matching_keeps_only_matches() {
+        let (test_dir, output_dir) = get_unique_paths("merge_pages_matching");
+        let path1 = test_dir.join("first.pdf");
+        let path2 = test_dir.join("second.pdf");
+        let output_path = output_dir.join("matches.pdf");
+        let sources = [(&path1, "First", vec![2]), (&path2, "Second", vec![1, 3])];
+        for (path, prefix, hits) in sources {
+            create_minimal_pdf(path.to_str().unwrap(), 3, prefix).unwrap();
+            let mut doc = Document::load(path).unwrap();
+            let pages = doc.get_pages();
+            for page in hits {
+                let content = format!(
+                    "BT /F1 12 Tf 100 700 Td ({}-Page {} Invoice) Tj ET",
+                    prefix, page
+                );
+                doc.change_page_content(pages[&page], content.into_bytes()).unwrap();
+            }
+            doc.save(path).unwrap();
+        }
+
+        let counts = merge_pages_matching(
+            vec![path1.to_str().unwrap(), path2.to_str().unwrap()],
+            "invoice",
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(counts, vec![1, 2]);
+
+        let merged = Document::load(&output_path).unwrap();
+        let order: Vec<String> = merged
+            .get_pages()
+            .values()
+            .map(|id| page_text(&merged, *id))
+            .collect();
+        let expected = ["First-Page 2", "Second-Page 1", "Second-Page 3"];
+        assert_eq!(order.len(), expected.len());
+        for (content, label) in order.iter().zip(expected) {
+            assert!(content.contains(label), "expected {} in {}", label, content);
+        }
+
+        let result = merge_pages_matching(
+            vec![path1.to_str().unwrap()],
+            "receipt",
+            output_dir.join("none.pdf").to_str().unwrap(),
+            None,
+            None,
+        );
+        assert_eq!(result.unwrap_err(), "No pages contain 'receipt'.");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_is_reproducible() {
+        let (test_dir, output_dir) = get_unique_paths("merge_reproducible");
+        let path1 = test_dir.join("repro1.pdf");
+        let path2 = test_dir.join("repro2.pdf");
+        let first_output = output_dir.join("first.pdf");
+        let second_output = output_dir.join("second.pdf");
+        create_minimal_pdf(path1.to_str().unwrap(), 3, "Repro1").unwrap();
+        create_minimal_pdf(path2.to_str().unwrap(), 2, "Repro2").unwrap();
+
+        let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap()];
+        merge_pdfs(paths_vec.clone(), first_output.to_str().unwrap(), None, false, None).unwrap();
+        merge_pdfs(paths_vec, second_output.to_str().unwrap(), None, false, None).unwrap();
+
+        let first = fs::read(&first_output).unwrap();
+        let second = fs::read(&second_output).unwrap();
+        assert!(!first.is_empty());
+        assert_eq!(first, second, "merging identical inputs should be byte-identical");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_keeps_bookmarks_from_each_source() {
+        let (test_dir, output_dir) = get_unique_paths("merge_outlines");
+        let path1 = test_dir.join("doc1.pdf");
+        let path2 = test_dir.join("doc2.pdf");
+        let output_path = output_dir.join("merged.pdf");
+        for (path, title) in [(&path1, "Chapter One"), (&path2, "Chapter Two")] {
+            create_minimal_pdf(path.to_str().unwrap(), 2, "Doc").unwrap();
+            let mut doc = Document::load(path).unwrap();
+            let pages = doc.get_pages();
+            let entry = OutlineEntry {
+                title: Object::string_literal(title),
+                page: Some(2),
+                dest: None,
+                children: vec![],
+            };
+            write_outline_entries(&mut doc, &[entry], &pages).unwrap();
+            doc.save(path).unwrap();
+        }
+
+        merge_pdfs(
+            vec![path1.to_str().unwrap(), path2.to_str().unwrap()],
+            output_path.to_str().unwrap(),
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let merged = Document::load(&output_path).unwrap();
+        assert!(merged.catalog().unwrap().has(b"Outlines"));
+        let bookmarks: Vec<(String, Option<u32>)> = read_outline_entries(&merged)
+            .into_iter()
+            .map(|e| {
+                (
+                    String::from_utf8_lossy(e.title.as_str().unwrap()).into_owned(),
+                    e.page,
+                )
+            })
+            .collect();
+        assert_eq!(
+            bookmarks,
+            vec![
+                ("Chapter One".to_string(), Some(2)),
+                ("Chapter Two".to_string(), Some(4))
+            ]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_carries_over_first_info() {
+        let (test_dir, output_dir) = get_unique_paths("merge_info");
+        let path1 = test_dir.join("doc1.pdf");
+        let path2 = test_dir.join("doc2.pdf");
+        let output_path = output_dir.join("merged.pdf");
+        let overridden_path = output_dir.join("merged_overridden.pdf");
+        create_minimal_pdf(path1.to_str().unwrap(), 1, "Doc1").unwrap();
+        create_minimal_pdf(path2.to_str().unwrap(), 1, "Doc2").unwrap();
+        let mut doc = Document::load(&path1).unwrap();
+        let info_id = doc.add_object(lopdf::dictionary! {
+            "Title" => Object::string_literal("Annual Report"),
+            "Author" => Object::string_literal("Finance"),
+        });
+        doc.trailer.set("Info", Object::Reference(info_id));
+        doc.save(&path1).unwrap();
+        let paths_vec = vec![path1.to_str().unwrap(), path2.to_str().unwrap()];
+
+        merge_pdfs(
+            paths_vec.clone(),
+            output_path.to_str().unwrap(),
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        let info = crate::pdf::parser::parse_pdf(output_path.to_str().unwrap(), None).unwrap();
+        assert_eq!(info.get("Title").map(String::as_str), Some("Annual Report"));
+
+        let overrides = BTreeMap::from([("Title".to_string(), "Combined Report".to_string())]);
+        merge_pdfs(
+            paths_vec,
+            overridden_path.to_str().unwrap(),
+            None,
+            false,
+            Some(overrides),
+        )
+        .unwrap();
+        let info = crate::pdf::parser::parse_pdf(overridden_path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            info.get("Title").map(String::as_str),
+            Some("Combined Report")
+        );
+        assert_eq!(info.get("Author").map(String::as_str), Some("Finance"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_pdfs_stops_when_cancelled() {
+        let (test_dir, output_dir) = get_unique_paths("merge_cancelled");
+        let path1 = test_dir.join("a.pdf");
+        let path2 = test_dir.join("b.pdf");
+        let output_path = output_dir.join("merged.pdf");
+        create_minimal_pdf(path1.to_str().unwrap(), 2, "A").unwrap();
+        create_minimal_pdf(path2.to_str().unwrap(), 2, "B").unwrap();
+
+        let token = CancellationToken::default();
+        token.cancel();
+        for paths in [
+            vec![path1.to_str().unwrap()],
+            vec![path1.to_str().unwrap(), path2.to_str().unwrap()],
+        ] {
+            let result = merge_pdfs_cancellable(
+                paths,
+                output_path.to_str().unwrap(),
+                None,
+                false,
+                None,
+                &token,
+                None,
+            );
+            assert_eq!(result, Err("Operation cancelled".to_string()));
+            assert!(!output_path.exists());
+        }
+
+        token.reset();
+        let result = merge_pdfs_cancellable(
+            vec![path1.to_str().unwrap(), path2.to_str().unwrap()],
+            output_path.to_str().unwrap(),
+            None,
+            false,
+            None,
+            &token,
+            None,
+        );
+        assert!(result.is_ok());
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_pdfs_cancelled_from_another_thread() {
+        let (test_dir, output_dir) = get_unique_paths("merge_cancelled_thread");
+        let output_path = output_dir.join("merged.pdf");
+        let inputs: Vec<String> = (0..40)
+            .map(|i| {
+                let path = test_dir.join(format!("in_{}.pdf", i));
+                create_minimal_pdf(path.to_str().unwrap(), 3, &format!("Doc{}", i)).unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+
+        let token = CancellationToken::default();
+        let result = std::thread::scope(|scope| {
+            let merge = scope.spawn(|| {
+                merge_pdfs_cancellable(
+                    inputs.iter().map(String::as_str).collect(),
+                    output_path.to_str().unwrap(),
+                    None,
+                    false,
+                    None,
+                    &token,
+                    None,
+                )
+            });
+            token.cancel();
+            merge.join().unwrap()
+        });
+        assert_eq!(result, Err("Operation cancelled".to_string()));
+        assert!(!output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_merge_single_input_clean_is_rebuilt() {
+        let (test_dir, output_dir) = get_unique_paths("merge_single_clean");
+        let input = test_dir.join("bloated.pdf");
+        let output_path = output_dir.join("merged.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Solo").unwrap();
+
+        // The single-input shortcut copies the file as-is, which would keep the cruft
+        let mut doc = Document::load(&input).unwrap();
+        for i in 0..10 {
+            doc.add_object(lopdf::Stream::new(
+                lopdf::Dictionary::new(),
+                format!("stale content {}", i).into_bytes(),
+            ));
+        }
+        doc.save(&input).unwrap();
+
+        merge_pdfs_cancellable(
+            vec![input.to_str().unwrap()],
+            output_path.to_str().unwrap(),
+            None,
+            false,
+            None,
+            &CancellationToken::default(),
+            Some(true),
+        )
+        .unwrap();
+
+        let merged = Document::load(&output_path).unwrap();
+        assert!(merged.objects.len() < doc.objects.len());
+        assert_eq!(merged.extract_text(&[2]).unwrap().trim(), "Solo-Page 2");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
@@ -1,14 +1,55 @@
-use lopdf::{Document, Object, StringFormat};
+use crate::pdf::utils::{load_document, rebuild_clean};
+use lopdf::{Dictionary, Document, Object, StringFormat};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
 
+/// Encode a value as a PDF text string in UTF-16BE with a byte order mark, so any
+/// Unicode text survives and `parse_pdf` decodes it back exactly.
+pub(crate) fn utf16_text_string(value: &str) -> Object {
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in value.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+/// Whether `key` can be written as a PDF name without escaping: printable ASCII with
+/// no whitespace, delimiters or `#`.
+pub(crate) fn is_valid_name_token(key: &str) -> bool {
+    !key.is_empty()
+        && key.bytes().all(|b| {
+            (0x21..=0x7E).contains(&b) && !b"()<>[]{}/%#".contains(&b)
+        })
+}
+
+/// Reject the first key that cannot be written as an Info dictionary name.
+pub(crate) fn check_metadata_keys<'a>(keys: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+    match keys.into_iter().find(|key| !is_valid_name_token(key)) {
+        Some(key) => Err(format!(
+            "Invalid metadata key '{}': keys must be printable ASCII without spaces or any of ()<>[]{{}}/%#.",
+            key
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Write Info dictionary fields. `custom` holds arbitrary extra keys (e.g.
+/// `InvoiceNumber`), stored as UTF-16BE text strings.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_metadata(
     path: &str, 
     title: Option<String>, 
     author: Option<String>, 
     subject: Option<String>, 
     keywords: Option<String>, 
-    output_path: &str
+    custom: Option<BTreeMap<String, String>>,
+    output_path: &str, clean: Option<bool>
 ) -> Result<(), String> {
+    let custom = custom.unwrap_or_default();
+    check_metadata_keys(custom.keys())?;
+
     let mut doc = Document::load(path).map_err(|e| format!("Failed to load: {}", e))?;
     
     let info_id = if let Ok(id) = doc.trailer.get(b"Info") {
@@ -27,9 +68,16 @@ pub fn update_metadata(
         if let Some(a) = author { info.set("Author", Object::String(a.into_bytes(), StringFormat::Literal)); }
         if let Some(s) = subject { info.set("Subject", Object::String(s.into_bytes(), StringFormat::Literal)); }
         if let Some(k) = keywords { info.set("Keywords", Object::String(k.into_bytes(), StringFormat::Literal)); }
+        for (key, value) in &custom {
+            info.set(key.as_str(), utf16_text_string(value));
+        }
         info.set("Producer", Object::String("Pinnacle World-Class Assistant".into(), StringFormat::Literal));
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save: {}", e))?;
     Ok(())
 }
@@ -40,18 +88,84 @@ pub fn batch_update_metadata(
     title: Option<String>,
     author: Option<String>,
     subject: Option<String>,
-    keywords: Option<String>
+    keywords: Option<String>, clean: Option<bool>
 ) -> Result<(), String> {
     for path in paths {
         let output_path = path.clone();
-        update_metadata(&path, title.clone(), author.clone(), subject.clone(), keywords.clone(), &output_path)?;
+        update_metadata(
+            &path,
+            title.clone(),
+            author.clone(),
+            subject.clone(),
+            keywords.clone(),
+            None,
+            &output_path,
+            clean,
+        )?;
     }
     Ok(())
 }
 
+
+/// Set arbitrary Info dictionary entries (e.g. `Title`, `Author`, `Subject`) from a
+/// key/value map. Values are stored as UTF-16BE text strings so `parse_pdf` reads them
+/// back unchanged. Existing keys not in the map are kept; a missing Info dictionary is
+/// created.
+#[tauri::command]
+pub fn set_pdf_metadata(
+    path: &str,
+    metadata: BTreeMap<String, String>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    check_metadata_keys(metadata.keys())?;
+    let mut doc = load_document(path, None)?;
+
+    // An inline Info dictionary is moved into its own object so readers that expect a
+    // reference (like `parse_pdf`) find it
+    let info_id = match doc.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) if doc.get_dictionary(*id).is_ok() => *id,
+        Ok(Object::Dictionary(info)) => {
+            let info = info.clone();
+            doc.add_object(info)
+        }
+        _ => doc.add_object(Dictionary::new()),
+    };
+    doc.trailer.set("Info", Object::Reference(info_id));
+
+    let info = doc
+        .get_dictionary_mut(info_id)
+        .map_err(|e| format!("Failed to access Info dictionary: {}", e))?;
+    for (key, value) in &metadata {
+        info.set(key.as_str(), utf16_text_string(value));
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pdf::parser::parse_pdf;
     use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
 
     #[test]
@@ -67,7 +181,9 @@ mod tests {
             Some("Author Name".to_string()),
             None,
             None,
-            output_path.to_str().unwrap()
+            None,
+            output_path.to_str().unwrap(),
+            None
         );
         
         assert!(result.is_ok());
@@ -80,4 +196,104 @@ mod tests {
         
         teardown_unique_paths(&test_dir, &output_dir);
     }
+
+    #[test]
+    fn test_update_metadata_custom_key_round_trips() {
+        let (test_dir, output_dir) = setup_unique_paths("metadata_custom");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Content").unwrap();
+
+        let custom = BTreeMap::from([("ProjectCode".to_string(), "PRJ-042 \u{2013} Phase ß".to_string())]);
+        let result = update_metadata(
+            input_path.to_str().unwrap(),
+            Some("Report".to_string()),
+            None,
+            None,
+            None,
+            Some(custom),
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "update_metadata failed: {:?}", result.err());
+
+        let metadata = parse_pdf(output_path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            metadata.get("ProjectCode"),
+            Some(&"PRJ-042 \u{2013} Phase ß".to_string())
+        );
+        assert_eq!(metadata.get("Title"), Some(&"Report".to_string()));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_update_metadata_rejects_invalid_key() {
+        let (test_dir, output_dir) = setup_unique_paths("metadata_invalid_key");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Content").unwrap();
+
+        let custom = BTreeMap::from([("Project Code".to_string(), "X".to_string())]);
+        let result = update_metadata(
+            input_path.to_str().unwrap(),
+            None,
+            None,
+            None,
+            None,
+            Some(custom),
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("Invalid metadata key 'Project Code'"));
+        assert!(!output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_set_pdf_metadata_round_trips_and_keeps_other_keys() {
+        let (test_dir, output_dir) = setup_unique_paths("set_pdf_metadata");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Content").unwrap();
+
+        // Starts without an Info dictionary
+        let first = BTreeMap::from([
+            ("Author".to_string(), "Zoë Müller".to_string()),
+            ("Title".to_string(), "Draft".to_string()),
+        ]);
+        set_pdf_metadata(
+            input_path.to_str().unwrap(),
+            first,
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let second = BTreeMap::from([
+            (
+                "Title".to_string(),
+                "Quarterly Report \u{2014} Q3".to_string(),
+            ),
+            ("Subject".to_string(), "Finance".to_string()),
+        ]);
+        set_pdf_metadata(
+            output_path.to_str().unwrap(),
+            second,
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let metadata = parse_pdf(output_path.to_str().unwrap(), None).unwrap();
+        assert_eq!(
+            metadata.get("Title"),
+            Some(&"Quarterly Report \u{2014} Q3".to_string())
+        );
+        assert_eq!(metadata.get("Subject"), Some(&"Finance".to_string()));
+        assert_eq!(metadata.get("Author"), Some(&"Zoë Müller".to_string()));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
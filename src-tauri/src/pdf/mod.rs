@@ -15,6 +15,7 @@ pub mod rasterizer;
 pub mod editor;
 pub mod reorder;
 pub mod security_utils;
+pub mod compressor;
 pub mod compare;
 pub mod metadata;
 pub mod outline;
@@ -22,10 +23,35 @@ pub mod annotation_reader;
 pub mod forms;
 pub mod image_to_pdf;
 pub mod watermark;
+pub mod watermarker;
 pub mod watcher;
 pub mod forensic_redact;
 pub mod templates;
 pub mod briefing;
+pub mod xobject;
+pub mod batch;
+pub mod hash;
+pub mod fonts;
+pub mod cropper;
+pub mod extractor_text;
+pub mod inspect;
+pub mod concat;
+pub mod viewprefs;
+pub mod cleanup;
+pub mod blanks;
+pub mod render;
+pub mod repair;
+pub mod dedup;
+pub mod colorconv;
+pub mod separations;
+pub mod pdfa;
+pub mod version;
+pub mod diff;
+pub mod dests;
+pub mod stamping;
+pub mod images;
+pub mod cancel;
+pub mod linearize;
 
 // Shared helpers only compiled for tests
 #[cfg(test)]
@@ -37,32 +63,58 @@ pub mod signatures;
 // Optional but recommended: Re-export the functions you want to be easily accessible
 // from the 'pdf' module itself, hiding the internal structure (parser, merger, etc.)
 // This makes the import in main.rs cleaner.
-pub use extractor::extract_pdf_page;
-pub use merger::merge_pdfs;
-pub use parser::parse_pdf;
-pub use splitter::split_pdf;
-pub use rotator::rotate_pdf;
-pub use remover::delete_pages;
-pub use sanitize::sanitize_pdf;
+pub use extractor::{extract_pdf_page, extract_range_complete, extract_pdf_range};
+pub use merger::{append_pdf, merge_duplex, merge_interleaved, merge_pdfs, prepend_cover, merge_pages_matching};
+pub use parser::{parse_pdf, is_tagged, count_struct_elements, find_heavy_pages, detect_source_app, get_page_count, validate_content_streams};
+pub use splitter::{split_pdf, split_by_size_respecting_bookmarks, split_on_text, split_with_overlap, split_by_plan, split_into_n, split_into_chunks};
+pub use rotator::{rotate_pdf, get_rotations, preview_rotation, set_global_rotation};
+pub use remover::{delete_pages, keep_pages};
+pub use sanitize::{embed_external_references, find_external_references, sanitize_pdf, untag_pdf};
 pub use convert::pdf_to_text;
 pub use convert::pdf_to_text_string;
 pub use convert::write_text_file;
 pub use rasterizer::pdf_to_images;
-pub use annotations::{add_annotation, add_ink_annotation, delete_annotation, update_annotation_contents};
+pub use annotations::{add_annotation, add_ink_annotation, delete_annotation, update_annotation_contents, list_links, rewrite_uris, set_annotation_flags, add_goto_link, find_duplicate_annotations};
 pub use reorder::reorder_pages;
-pub use security_utils::{compress_pdf, decrypt_pdf, encrypt_pdf, flatten_annotations};
+pub use security_utils::{decrypt_pdf, encrypt_pdf, flatten_annotations, is_encrypted};
+pub use compressor::{compress_pdf, prune_page_resources};
 pub use compare::compare_pdfs_text;
-pub use outline::{get_pdf_outline, set_pdf_outline};
+pub use outline::{get_pdf_outline, set_pdf_outline, generate_toc_page};
 pub use annotation_reader::get_annotations;
 pub use forms::{get_form_fields, set_form_fields, create_form_fields};
-pub use editor::{replace_text_block, pdf_to_docx};
+pub use editor::{replace_text_block, pdf_to_docx, transform_content, insert_blank_page, insert_blank_pages, pad_to_multiple};
 pub use image_to_pdf::images_to_pdf;
 pub use watermark::add_watermark;
+pub use watermarker::add_text_watermark;
 pub use watcher::start_folder_watcher;
-pub use forensic_redact::forensic_redact;
+pub use forensic_redact::{forensic_redact, secure_export};
 pub use templates::markdown_to_pdf;
 pub use briefing::generate_briefing;
-pub use metadata::{update_metadata, batch_update_metadata};
+pub use metadata::{update_metadata, batch_update_metadata, set_pdf_metadata};
 pub use signatures::add_signature_visual;
 pub use signatures::sign_pdf_pfx;
 pub use signatures::verify_signatures;
+pub use xobject::export_as_xobject;
+pub use batch::{batch_watermark, merge_from_list};
+pub use hash::content_hash;
+pub use fonts::{add_tounicode, check_font_embedding, embed_standard_fonts};
+pub use cropper::{crop_pages, crop_to_common_box, get_print_boxes, set_print_boxes};
+pub use extractor_text::{diagnose_text, detect_text_direction, extract_text};
+pub use inspect::{structure_tree, page_report, get_page_resources, estimate_print_cost, get_page_info};
+pub use concat::concatenate_pages_vertically;
+pub use viewprefs::{get_view_settings, set_text_direction};
+pub use cleanup::cleanup_scan;
+pub use blanks::find_empty_pages;
+pub use render::{generate_index_sheet, flatten_heavy_pages, tile_overview, ink_coverage, render_thumbnails};
+pub use repair::{collapse_revisions, remove_object, clear_broken_content};
+pub use dedup::match_by_id;
+pub use colorconv::{rgb_to_cmyk, strip_icc_profiles};
+pub use separations::{list_separations, flatten_separations};
+pub use pdfa::check_pdfa;
+pub use version::downgrade_version;
+pub use diff::diff_pages;
+pub use dests::list_destinations;
+pub use stamping::{add_page_numbers, stamp_timestamp};
+pub use images::extract_images;
+pub use cancel::{cancel_operation, reset_cancellation, CancellationToken};
+pub use linearize::linearize_pdf;
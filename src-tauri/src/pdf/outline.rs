@@ -1,4 +1,12 @@
-use lopdf::{Document, Object};
+use crate::pdf::annotations::{goto_link_annotation, resolve_destination_page};
+use crate::pdf::editor::insert_empty_page;
+use crate::pdf::parser::decode_pdf_string;
+use crate::pdf::utils::{load_document, page_geometry, rebuild_clean};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, StringFormat};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct OutlineItem {
@@ -61,7 +69,12 @@ fn parse_outline_node(doc: &Document, node_id: (u32, u16)) -> Vec<OutlineItem> {
 }
 
 #[tauri::command]
-pub fn set_pdf_outline(path: &str, items: Vec<OutlineItem>, output_path: &str) -> Result<(), String> {
+pub fn set_pdf_outline(
+    path: &str,
+    items: Vec<OutlineItem>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     let mut doc = Document::load(path).map_err(|e| e.to_string())?;
     
     // Create new Outlines hierarchy
@@ -108,6 +121,389 @@ pub fn set_pdf_outline(path: &str, items: Vec<OutlineItem>, output_path: &str) -
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save: {}", e))?;
     Ok(())
 }
+
+/// A bookmark with its raw title and target, read so it can be carried over into a
+/// newly assembled document.
+pub(crate) struct OutlineEntry {
+    pub title: Object,
+    pub page: Option<u32>,
+    /// The explicit destination array (target page first), if it had one.
+    pub dest: Option<Vec<Object>>,
+    pub children: Vec<OutlineEntry>,
+}
+
+/// The destination of an outline item: its `/Dest`, or the `/D` of a GoTo action.
+fn outline_item_dest<'a>(doc: &'a Document, node: &'a Dictionary) -> Option<&'a Object> {
+    node.get(b"Dest").ok().or_else(|| {
+        node.get_deref(b"A", doc)
+            .and_then(|a| a.as_dict())
+            .ok()
+            .filter(|a| a.get(b"S").and_then(|s| s.as_name()).ok() == Some(b"GoTo"))
+            .and_then(|a| a.get(b"D").ok())
+    })
+}
+
+fn read_outline_siblings(
+    doc: &Document,
+    first: Option<ObjectId>,
+    visited: &mut HashSet<ObjectId>,
+) -> Vec<OutlineEntry> {
+    let mut entries = Vec::new();
+    let mut current = first;
+    while let Some(node_id) = current {
+        if !visited.insert(node_id) {
+            break;
+        }
+        let Ok(node) = doc.get_dictionary(node_id) else {
+            break;
+        };
+        let dest = outline_item_dest(doc, node);
+        let first_child = node.get(b"First").and_then(|f| f.as_reference()).ok();
+        entries.push(OutlineEntry {
+            title: node
+                .get(b"Title")
+                .cloned()
+                .unwrap_or_else(|_| Object::string_literal("Untitled")),
+            page: dest.and_then(|d| resolve_destination_page(doc, d)),
+            dest: dest
+                .and_then(|d| doc.dereference(d).ok())
+                .and_then(|(_, d)| d.as_array().ok().cloned()),
+            children: read_outline_siblings(doc, first_child, visited),
+        });
+        current = node.get(b"Next").and_then(|n| n.as_reference()).ok();
+    }
+    entries
+}
+
+/// Read the whole outline tree of `doc`, in order.
+pub(crate) fn read_outline_entries(doc: &Document) -> Vec<OutlineEntry> {
+    let first = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get_deref(b"Outlines", doc).ok())
+        .and_then(|outlines| outlines.as_dict().ok())
+        .and_then(|outlines| outlines.get(b"First").and_then(|f| f.as_reference()).ok());
+    read_outline_siblings(doc, first, &mut HashSet::new())
+}
+
+/// Write `entries` as sibling items under `parent_id`, returning (first, last, count
+/// of all items written). `page_ids` maps the entries' page numbers to pages of `doc`.
+fn write_outline_siblings(
+    doc: &mut Document,
+    entries: &[OutlineEntry],
+    parent_id: ObjectId,
+    page_ids: &BTreeMap<u32, ObjectId>,
+) -> (Option<ObjectId>, Option<ObjectId>, i64) {
+    let ids: Vec<ObjectId> = entries.iter().map(|_| doc.new_object_id()).collect();
+    let mut total = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let mut node = dictionary! {
+            "Title" => entry.title.clone(),
+            "Parent" => Object::Reference(parent_id),
+        };
+        if i > 0 {
+            node.set("Prev", Object::Reference(ids[i - 1]));
+        }
+        if i + 1 < ids.len() {
+            node.set("Next", Object::Reference(ids[i + 1]));
+        }
+        if let Some(page_id) = entry.page.and_then(|p| page_ids.get(&p)) {
+            let mut dest = entry
+                .dest
+                .clone()
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| vec![Object::Null, Object::Name(b"Fit".to_vec())]);
+            dest[0] = Object::Reference(*page_id);
+            node.set("Dest", dest);
+        }
+        let (first, last, count) = write_outline_siblings(doc, &entry.children, ids[i], page_ids);
+        if let (Some(first), Some(last)) = (first, last) {
+            node.set("First", Object::Reference(first));
+            node.set("Last", Object::Reference(last));
+            node.set("Count", count);
+        }
+        total += 1 + count;
+        doc.objects.insert(ids[i], Object::Dictionary(node));
+    }
+    (ids.first().copied(), ids.last().copied(), total)
+}
+
+/// Replace the outline of `doc` with `entries` (all items open), pointing each item at
+/// `page_ids[entry.page]`. Items whose page is missing from `page_ids` keep no
+/// destination.
+pub(crate) fn write_outline_entries(
+    doc: &mut Document,
+    entries: &[OutlineEntry],
+    page_ids: &BTreeMap<u32, ObjectId>,
+) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let outlines_id = doc.new_object_id();
+    let (first, last, count) = write_outline_siblings(doc, entries, outlines_id, page_ids);
+    let mut outlines = dictionary! { "Type" => "Outlines", "Count" => count };
+    if let (Some(first), Some(last)) = (first, last) {
+        outlines.set("First", Object::Reference(first));
+        outlines.set("Last", Object::Reference(last));
+    }
+    doc.objects.insert(outlines_id, Object::Dictionary(outlines));
+    doc.catalog_mut()
+        .map_err(|e| format!("Failed to access document catalog: {}", e))?
+        .set("Outlines", Object::Reference(outlines_id));
+    Ok(())
+}
+
+// --- generate_toc_page ---
+
+const TOC_MARGIN: f64 = 72.0;
+const TOC_HEADING_SIZE: f64 = 18.0;
+const TOC_ENTRY_SIZE: f64 = 12.0;
+const TOC_LINE_HEIGHT: f64 = 20.0;
+const TOC_INDENT: f64 = 18.0;
+/// Helvetica digits are all 0.556 em wide; other glyphs average about half an em.
+const TOC_DIGIT_WIDTH: f64 = 0.556;
+const TOC_AVERAGE_CHAR_WIDTH: f64 = 0.5;
+
+/// Flatten the outline depth-first into (depth, title, page) rows, skipping items that
+/// do not point at a page.
+fn toc_rows(entries: &[OutlineEntry], depth: usize, rows: &mut Vec<(usize, String, u32)>) {
+    for entry in entries {
+        if let Some(page) = entry.page {
+            let title = match &entry.title {
+                Object::String(bytes, _) => decode_pdf_string(bytes),
+                _ => "Untitled".to_string(),
+            };
+            rows.push((depth, title, page));
+        }
+        toc_rows(&entry.children, depth + 1, rows);
+    }
+}
+
+/// Encode text for the standard Helvetica font (WinAnsiEncoding). Characters outside
+/// Latin-1 become `?`, and titles wider than `max_width` are cut with an ellipsis.
+fn toc_text(text: &str, max_width: f64) -> Vec<u8> {
+    let max_chars = (max_width / (TOC_ENTRY_SIZE * TOC_AVERAGE_CHAR_WIDTH)) as usize;
+    let mut chars: Vec<char> = text.chars().filter(|c| !c.is_control()).collect();
+    if chars.len() > max_chars {
+        chars.truncate(max_chars.saturating_sub(3));
+        chars.extend("...".chars());
+    }
+    chars
+        .into_iter()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect()
+}
+
+fn toc_text_operations(font: &str, size: f64, x: f64, y: f64, text: Vec<u8>) -> Vec<Operation> {
+    vec![
+        Operation::new("BT", vec![]),
+        Operation::new(
+            "Tf",
+            vec![Object::Name(font.as_bytes().to_vec()), size.into()],
+        ),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new("Tj", vec![Object::String(text, StringFormat::Literal)]),
+        Operation::new("ET", vec![]),
+    ]
+}
+
+/// Insert a table of contents in front of the document, built from its bookmarks: one
+/// line per bookmark (indented by level) with the page number it points to, each line
+/// a link to that page. Page numbers are positions in the output, after the contents
+/// pages. Long outlines continue on further contents pages. Pages match the size of
+/// the document's first page.
+#[tauri::command]
+pub fn generate_toc_page(path: &str, output_path: &str, clean: Option<bool>) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+
+    let mut rows = Vec::new();
+    toc_rows(&read_outline_entries(&doc), 0, &mut rows);
+    if rows.is_empty() {
+        return Err(format!(
+            "Document '{}' has no bookmarks to build a table of contents from.",
+            path
+        ));
+    }
+
+    let pages = doc.get_pages();
+    let (bounds, _) = page_geometry(&doc, pages[&1]);
+    let (width, height) = (bounds[2] - bounds[0], bounds[3] - bounds[1]);
+    let rows_top = height - TOC_MARGIN - TOC_HEADING_SIZE * 2.0;
+    let rows_per_page = (((rows_top - TOC_MARGIN) / TOC_LINE_HEIGHT).floor() as usize).max(1);
+    let chunks: Vec<&[(usize, String, u32)]> = rows.chunks(rows_per_page).collect();
+    let toc_page_count = chunks.len() as u32;
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let toc_page_id = insert_empty_page(&mut doc, index as u32, [0.0, 0.0, width, height])?;
+
+        let mut operations = toc_text_operations(
+            "F2",
+            TOC_HEADING_SIZE,
+            TOC_MARGIN,
+            height - TOC_MARGIN - TOC_HEADING_SIZE,
+            b"Contents".to_vec(),
+        );
+        let mut links = Vec::with_capacity(chunk.len());
+        for (row, (depth, title, page)) in chunk.iter().enumerate() {
+            let y = rows_top - row as f64 * TOC_LINE_HEIGHT;
+            let x = TOC_MARGIN + *depth as f64 * TOC_INDENT;
+            let label = (page + toc_page_count).to_string();
+            let label_width = label.len() as f64 * TOC_ENTRY_SIZE * TOC_DIGIT_WIDTH;
+            let label_x = width - TOC_MARGIN - label_width;
+
+            let title_width = label_x - x - TOC_ENTRY_SIZE * 2.0;
+            operations.extend(toc_text_operations(
+                "F1",
+                TOC_ENTRY_SIZE,
+                x,
+                y,
+                toc_text(title, title_width),
+            ));
+            operations.extend(toc_text_operations(
+                "F1",
+                TOC_ENTRY_SIZE,
+                label_x,
+                y,
+                label.into_bytes(),
+            ));
+
+            let rect = [x, y - 4.0, width - TOC_MARGIN, y + TOC_ENTRY_SIZE];
+            let link = goto_link_annotation(&doc, rect, pages[page]);
+            links.push(Object::Reference(doc.add_object(link)));
+        }
+
+        let content = Content { operations }
+            .encode()
+            .map_err(|e| format!("Failed to encode contents page: {}", e))?;
+        doc.change_page_content(toc_page_id, content)
+            .map_err(|e| format!("Failed to write contents page: {}", e))?;
+        let toc_page = doc
+            .get_dictionary_mut(toc_page_id)
+            .map_err(|e| format!("Failed to access contents page: {}", e))?;
+        toc_page.set(
+            "Resources",
+            dictionary! {
+                "Font" => dictionary! {
+                    "F1" => dictionary! {
+                        "Type" => "Font",
+                        "Subtype" => "Type1",
+                        "BaseFont" => "Helvetica",
+                        "Encoding" => "WinAnsiEncoding",
+                    },
+                    "F2" => dictionary! {
+                        "Type" => "Font",
+                        "Subtype" => "Type1",
+                        "BaseFont" => "Helvetica-Bold",
+                        "Encoding" => "WinAnsiEncoding",
+                    },
+                },
+            },
+        );
+        toc_page.set("Annots", links);
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path).map_err(|e| {
+        format!(
+            "Failed to save PDF with contents page to '{}': {}",
+            output_path, e
+        )
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::annotations::list_links;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    #[test]
+    fn test_generate_toc_page_links_bookmarks() {
+        let (test_dir, output_dir) = setup_unique_paths("toc_page");
+        let input = test_dir.join("book.pdf");
+        let output = output_dir.join("with_toc.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Book").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let bookmark = |title: &str, page: u32, children| OutlineEntry {
+            title: Object::string_literal(title),
+            page: Some(page),
+            dest: None,
+            children,
+        };
+        let entries = vec![
+            bookmark("Introduction", 1, vec![]),
+            bookmark("Methods", 2, vec![bookmark("Sampling", 3, vec![])]),
+        ];
+        write_outline_entries(&mut doc, &entries, &pages).unwrap();
+        doc.save(&input).unwrap();
+
+        generate_toc_page(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+
+        let result = Document::load(&output).unwrap();
+        assert_eq!(result.get_pages().len(), 4);
+        let toc_text = result.extract_text(&[1]).unwrap();
+        for title in ["Contents", "Introduction", "Methods", "Sampling"] {
+            assert!(
+                toc_text.contains(title),
+                "missing '{}' in {:?}",
+                title,
+                toc_text
+            );
+        }
+        assert!(result.extract_text(&[2]).unwrap().contains("Book-Page 1"));
+
+        let links = list_links(output.to_str().unwrap()).unwrap();
+        let targets: Vec<(u32, &str, &str)> = links
+            .iter()
+            .map(|l| (l.page, l.kind.as_str(), l.target.as_str()))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![(1, "goto", "2"), (1, "goto", "3"), (1, "goto", "4")]
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_generate_toc_page_requires_bookmarks() {
+        let (test_dir, output_dir) = setup_unique_paths("toc_page_none");
+        let input = test_dir.join("plain.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Plain").unwrap();
+
+        let result = generate_toc_page(
+            input.to_str().unwrap(),
+            output_dir.join("out.pdf").to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("has no bookmarks"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
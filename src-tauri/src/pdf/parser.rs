@@ -1,8 +1,9 @@
-use lopdf::{Dictionary, Document, Object};
-use std::collections::BTreeMap;
-use std::path::Path;
+use crate::pdf::utils::load_document;
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashSet};
 
-fn decode_pdf_string(bytes: &[u8]) -> String {
+pub(crate) fn decode_pdf_string(bytes: &[u8]) -> String {
     if bytes.starts_with(&[0xFE, 0xFF]) {
         // UTF-16BE with BOM
         let utf16_data: Vec<u16> = bytes[2..]
@@ -19,20 +20,14 @@ fn decode_pdfdoc_encoding(bytes: &[u8]) -> String {
     bytes.iter().map(|&byte| byte as char).collect()
 }
 
+/// Read the document information dictionary as a key/value map. `password` opens
+/// encrypted files; see `load_document`.
 #[tauri::command]
-pub fn parse_pdf(path: &str) -> Result<BTreeMap<String, String>, String> {
-    // --- Input Validation (as before) ---
-    let input_path = Path::new(path);
-    if !input_path.exists() {
-        return Err(format!("Input file not found: {}", path));
-    }
-    if !input_path.is_file() {
-        return Err(format!("Input path is not a file: {}", path));
-    }
-
-    // --- Load Document (as before) ---
-    let doc = Document::load(path)
-        .map_err(|e| format!("Failed to load or parse PDF '{}': {}", path, e))?;
+pub fn parse_pdf(
+    path: &str,
+    password: Option<String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let doc = load_document(path, password.as_deref())?;
 
     // --- Get Info Dictionary (explicit logic as before) ---
     let info_dict_result: Result<Option<&Dictionary>, String> = match doc.trailer.get(b"Info") {
@@ -78,11 +73,203 @@ pub fn parse_pdf(path: &str) -> Result<BTreeMap<String, String>, String> {
     Ok(metadata)
 }
 
+fn catalog(doc: &Document) -> Result<&Dictionary, String> {
+    doc.trailer
+        .get(b"Root")
+        .and_then(|root| root.as_reference())
+        .and_then(|root_id| doc.get_dictionary(root_id))
+        .map_err(|e| format!("Failed to read document catalog: {}", e))
+}
+
+/// Number of pages in the document, so the UI can offer a page selector up front.
+#[tauri::command]
+pub fn get_page_count(path: &str) -> Result<u32, String> {
+    Ok(load_document(path, None)?.get_pages().len() as u32)
+}
+
+/// Operator appended after a page's content before decoding. The content parser stops
+/// quietly at the first token it cannot read, so if this is not the last operation
+/// decoded, the content before it is malformed.
+const CONTENT_END_MARKER: &str = "PdfManagerContentEnd";
+
+/// Whether the page's content can be read back and decodes completely into operators.
+pub(crate) fn page_content_decodes(doc: &Document, page_id: ObjectId) -> bool {
+    let decoded = doc.get_page_content(page_id).ok().and_then(|mut content| {
+        content.extend_from_slice(format!("\n{}\n", CONTENT_END_MARKER).as_bytes());
+        Content::decode(&content).ok()
+    });
+    decoded
+        .and_then(|content| content.operations.last().map(|op| op.operator.clone()))
+        .is_some_and(|operator| operator == CONTENT_END_MARKER)
+}
+
+/// Pages (1-based) whose content streams cannot be read back or fail to decode as
+/// operators, e.g. malformed operators or truncated streams. Such files load fine
+/// structurally but render broken. Inline images are not understood by the content
+/// parser, so pages that use them are reported as well.
+#[tauri::command]
+pub fn validate_content_streams(path: &str) -> Result<Vec<u32>, String> {
+    let doc = load_document(path, None)?;
+    Ok(doc
+        .get_pages()
+        .into_iter()
+        .filter(|(_, page_id)| !page_content_decodes(&doc, *page_id))
+        .map(|(page_number, _)| page_number)
+        .collect())
+}
+
+/// Report whether the document is tagged: the catalog declares `/MarkInfo << /Marked true >>`
+/// and carries a `/StructTreeRoot` with the logical structure used by assistive technology.
+#[tauri::command]
+pub fn is_tagged(path: &str) -> Result<bool, String> {
+    let doc = load_document(path, None)?;
+    let catalog = catalog(&doc)?;
+
+    let marked = catalog
+        .get(b"MarkInfo")
+        .ok()
+        .and_then(|obj| match obj {
+            Object::Reference(id) => doc.get_dictionary(*id).ok(),
+            Object::Dictionary(dict) => Some(dict),
+            _ => None,
+        })
+        .and_then(|mark_info| mark_info.get(b"Marked").ok())
+        .and_then(|marked| marked.as_bool().ok())
+        .unwrap_or(false);
+
+    Ok(marked && catalog.has(b"StructTreeRoot"))
+}
+
+/// Count the structure elements (dictionaries with an `/S` type) reachable from the
+/// catalog's `/StructTreeRoot`. Returns 0 for untagged documents.
+#[tauri::command]
+pub fn count_struct_elements(path: &str) -> Result<usize, String> {
+    let doc = load_document(path, None)?;
+    let catalog = catalog(&doc)?;
+
+    let root = match catalog.get(b"StructTreeRoot") {
+        Ok(Object::Reference(id)) => match doc.get_dictionary(*id) {
+            Ok(dict) => dict,
+            Err(_) => return Ok(0),
+        },
+        Ok(Object::Dictionary(dict)) => dict,
+        _ => return Ok(0),
+    };
+
+    let mut visited = HashSet::new();
+    let mut pending: Vec<&Object> = root.get(b"K").into_iter().collect();
+    let mut count = 0;
+    while let Some(obj) = pending.pop() {
+        let dict = match obj {
+            Object::Reference(id) => {
+                if !visited.insert(*id) {
+                    continue;
+                }
+                match doc.get_object(*id) {
+                    Ok(Object::Dictionary(dict)) => dict,
+                    Ok(Object::Array(items)) => {
+                        pending.extend(items.iter());
+                        continue;
+                    }
+                    _ => continue,
+                }
+            }
+            Object::Dictionary(dict) => dict,
+            Object::Array(items) => {
+                pending.extend(items.iter());
+                continue;
+            }
+            // Marked-content ids (integers) are leaves, not structure elements
+            _ => continue,
+        };
+        // Marked-content and object references (/MCR, /OBJR) have no /S entry
+        if dict.has(b"S") {
+            count += 1;
+            if let Ok(kids) = dict.get(b"K") {
+                pending.push(kids);
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Pages whose decompressed content streams exceed `max_content_bytes`, i.e. the
+/// vector-heavy pages that bloat a file (detailed drawings, plots, maps).
+#[tauri::command]
+pub fn find_heavy_pages(path: &str, max_content_bytes: usize) -> Result<Vec<u32>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut heavy = Vec::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let content = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        if content.len() > max_content_bytes {
+            heavy.push(page_num);
+        }
+    }
+    Ok(heavy)
+}
+
+/// Known Producer/Creator signatures (all lowercase substrings must appear) and the
+/// application they identify. More specific entries come first.
+const SOURCE_APP_SIGNATURES: &[(&[&str], &str)] = &[
+    (&["microsoft", "word"], "Microsoft Word"),
+    (&["microsoft", "excel"], "Microsoft Excel"),
+    (&["microsoft", "powerpoint"], "Microsoft PowerPoint"),
+    (&["microsoft: print to pdf"], "Microsoft Print to PDF"),
+    (&["libreoffice"], "LibreOffice"),
+    (&["openoffice"], "OpenOffice"),
+    (&["google docs"], "Google Docs"),
+    (&["pdftex"], "LaTeX"),
+    (&["xetex"], "LaTeX"),
+    (&["luatex"], "LaTeX"),
+    (&["latex"], "LaTeX"),
+    (&["ghostscript"], "Ghostscript"),
+    (&["skia/pdf"], "Chrome/Chromium"),
+    (&["chrome"], "Chrome/Chromium"),
+    (&["firefox"], "Firefox"),
+    (&["wkhtmltopdf"], "wkhtmltopdf"),
+    (&["quartz pdfcontext"], "macOS (Quartz)"),
+    (&["adobe indesign"], "Adobe InDesign"),
+    (&["adobe illustrator"], "Adobe Illustrator"),
+    (&["acrobat distiller"], "Adobe Acrobat Distiller"),
+    (&["acrobat"], "Adobe Acrobat"),
+    (&["reportlab"], "ReportLab"),
+    (&["itext"], "iText"),
+    (&["cairo"], "Cairo"),
+];
+
+fn match_source_app(field: &str) -> Option<&'static str> {
+    let field = field.to_lowercase();
+    SOURCE_APP_SIGNATURES
+        .iter()
+        .find(|(needles, _)| needles.iter().all(|needle| field.contains(needle)))
+        .map(|(_, app)| *app)
+}
+
+/// Best guess at the application that produced the file, from the Info `/Creator`
+/// (the authoring tool) and then `/Producer` (the PDF library). `None` when neither
+/// matches a known signature.
+#[tauri::command]
+pub fn detect_source_app(path: &str) -> Result<Option<String>, String> {
+    let metadata = parse_pdf(path, None)?;
+    Ok(["Creator", "Producer"]
+        .iter()
+        .filter_map(|key| metadata.get(*key))
+        .find_map(|value| match_source_app(value))
+        .map(str::to_string))
+}
+
 // --- Tests ---
 #[cfg(test)]
 mod tests {
     // Import the function being tested
-    use super::parse_pdf;
+    use super::{
+        count_struct_elements, detect_source_app, find_heavy_pages, get_page_count, is_tagged,
+        parse_pdf, validate_content_streams,
+    };
 
     // Imports needed for testing
     use lopdf::{dictionary, Dictionary, Document, Object}; // Added Dictionary
@@ -197,7 +384,7 @@ mod tests {
         create_test_pdf(file_path.to_str().unwrap(), Some(info), None).expect("Create");
         assert!(file_path.exists());
 
-        let result = parse_pdf(file_path.to_str().unwrap());
+        let result = parse_pdf(file_path.to_str().unwrap(), None);
         assert!(result.is_ok(), "parse_pdf failed: {:?}", result.err());
         let metadata = result.unwrap();
 
@@ -225,7 +412,7 @@ mod tests {
             "Test PDF does not exist after creation!"
         );
 
-        let result = parse_pdf(file_path.to_str().unwrap());
+        let result = parse_pdf(file_path.to_str().unwrap(), None);
 
         assert!(result.is_ok(), "parse_pdf failed: {:?}", result.err());
         let metadata = result.unwrap();
@@ -250,7 +437,7 @@ mod tests {
             "Test PDF does not exist after creation!"
         );
 
-        let result = parse_pdf(file_path.to_str().unwrap());
+        let result = parse_pdf(file_path.to_str().unwrap(), None);
 
         // Expect Ok with empty map, as the function should handle this gracefully
         assert!(
@@ -284,7 +471,7 @@ mod tests {
             "Test PDF does not exist after creation!"
         );
 
-        let result = parse_pdf(file_path.to_str().unwrap());
+        let result = parse_pdf(file_path.to_str().unwrap(), None);
 
         // Expect Ok with empty map, as the function should handle this gracefully
         assert!(
@@ -306,7 +493,7 @@ mod tests {
         let bad_path_buf = test_dir.join("no_way_this_exists.pdf");
         let bad_path = bad_path_buf.to_str().unwrap();
 
-        let result = parse_pdf(bad_path);
+        let result = parse_pdf(bad_path, None);
 
         assert!(
             result.is_err(),
@@ -342,16 +529,189 @@ mod tests {
             "Non-PDF Test file does not exist after creation!"
         );
 
-        let result = parse_pdf(file_path.to_str().unwrap());
+        let result = parse_pdf(file_path.to_str().unwrap(), None);
 
         assert!(result.is_err(), "parse_pdf should fail for non-PDF file");
         let err_msg = result.err().unwrap();
         // Check for a plausible error message from lopdf::Document::load
         assert!(
-            err_msg.contains("Failed to load or parse PDF"),
+            err_msg.contains("Failed to load PDF"),
             "Error message mismatch: {}",
             err_msg
         );
         // Examples: "invalid PDF header", "cannot find trailer", "failed to read" etc.
     }
+
+    fn tag_document(file_path: &str) {
+        let mut doc = Document::load(file_path).unwrap();
+        let p1 = doc.add_object(dictionary! { "Type" => "StructElem", "S" => "P", "K" => 0_i64 });
+        let p2 = doc.add_object(dictionary! {
+            "Type" => "StructElem",
+            "S" => "P",
+            "K" => vec![
+                Object::Integer(1),
+                Object::Dictionary(dictionary! { "Type" => "MCR", "MCID" => 2_i64 }),
+            ],
+        });
+        let document = doc.add_object(dictionary! {
+            "Type" => "StructElem",
+            "S" => "Document",
+            "K" => vec![Object::Reference(p1), Object::Reference(p2)],
+        });
+        let tree_root = doc.add_object(dictionary! {
+            "Type" => "StructTreeRoot",
+            "K" => Object::Reference(document),
+        });
+        let root_id = doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let catalog = doc.get_dictionary_mut(root_id).unwrap();
+        catalog.set("MarkInfo", dictionary! { "Marked" => true });
+        catalog.set("StructTreeRoot", Object::Reference(tree_root));
+        doc.save(file_path).unwrap();
+    }
+
+    #[test]
+    fn test_is_tagged_with_struct_tree() {
+        let env = TestEnvironment::new("is_tagged_true");
+        let file_path = env.test_dir().join("tagged.pdf");
+        create_test_pdf(file_path.to_str().unwrap(), None, None).expect("Create");
+        tag_document(file_path.to_str().unwrap());
+
+        assert_eq!(is_tagged(file_path.to_str().unwrap()), Ok(true));
+        // Document + two P elements; the integer and MCR kids are not elements
+        assert_eq!(count_struct_elements(file_path.to_str().unwrap()), Ok(3));
+    }
+
+    #[test]
+    fn test_is_tagged_without_struct_tree() {
+        let env = TestEnvironment::new("is_tagged_false");
+        let file_path = env.test_dir().join("untagged.pdf");
+        create_test_pdf(file_path.to_str().unwrap(), None, None).expect("Create");
+
+        assert_eq!(is_tagged(file_path.to_str().unwrap()), Ok(false));
+        assert_eq!(count_struct_elements(file_path.to_str().unwrap()), Ok(0));
+    }
+
+    #[test]
+    fn test_find_heavy_pages_flags_large_content() {
+        use crate::pdf::test_utils::create_minimal_pdf;
+
+        let env = TestEnvironment::new("heavy_pages");
+        let file_path = env.test_dir().join("heavy.pdf");
+        create_minimal_pdf(file_path.to_str().unwrap(), 3, "Heavy").unwrap();
+        let mut doc = Document::load(&file_path).unwrap();
+        let page_id = doc.get_pages()[&2];
+        let mut content = String::new();
+        for i in 0..2000 {
+            content.push_str(&format!("{} {} m {} {} l S\n", i % 600, i % 780, 600 - i % 600, 5));
+        }
+        doc.change_page_content(page_id, content.into_bytes()).unwrap();
+        doc.compress();
+        doc.save(&file_path).unwrap();
+
+        let heavy = find_heavy_pages(file_path.to_str().unwrap(), 10_000);
+        assert_eq!(heavy, Ok(vec![2]));
+    }
+
+    #[test]
+    fn test_detect_source_app_maps_skia_to_chrome() {
+        let env = TestEnvironment::new("detect_source_app");
+        let file_path = env.test_dir().join("chrome.pdf");
+        let info = BTreeMap::from([("Producer", "Skia/PDF m120")]);
+        create_test_pdf(file_path.to_str().unwrap(), Some(info), None).expect("Create");
+
+        let app = detect_source_app(file_path.to_str().unwrap()).unwrap();
+        assert_eq!(app, Some("Chrome/Chromium".to_string()));
+    }
+
+    #[test]
+    fn test_detect_source_app_prefers_creator_and_handles_unknown() {
+        let env = TestEnvironment::new("detect_source_app_creator");
+        let latex_path = env.test_dir().join("latex.pdf");
+        let info = BTreeMap::from([
+            ("Creator", "LaTeX with hyperref"),
+            ("Producer", "GPL Ghostscript 10.02"),
+        ]);
+        create_test_pdf(latex_path.to_str().unwrap(), Some(info), None).expect("Create");
+        assert_eq!(
+            detect_source_app(latex_path.to_str().unwrap()).unwrap(),
+            Some("LaTeX".to_string())
+        );
+
+        let unknown_path = env.test_dir().join("unknown.pdf");
+        let info = BTreeMap::from([("Producer", "In-house generator 2.1")]);
+        create_test_pdf(unknown_path.to_str().unwrap(), Some(info), None).expect("Create");
+        assert_eq!(detect_source_app(unknown_path.to_str().unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_page_count() {
+        use crate::pdf::test_utils::create_minimal_pdf;
+
+        let env = TestEnvironment::new("page_count");
+        let file_path = env.test_dir().join("four.pdf");
+        create_minimal_pdf(file_path.to_str().unwrap(), 4, "Count").unwrap();
+
+        assert_eq!(get_page_count(file_path.to_str().unwrap()), Ok(4));
+        let missing = env.test_dir().join("missing.pdf");
+        assert!(get_page_count(missing.to_str().unwrap())
+            .unwrap_err()
+            .contains("Input file not found"));
+    }
+
+    #[test]
+    fn test_validate_content_streams_flags_truncated_page() {
+        use crate::pdf::test_utils::create_minimal_pdf;
+
+        let env = TestEnvironment::new("validate_content");
+        let file_path = env.test_dir().join("truncated.pdf");
+        create_minimal_pdf(file_path.to_str().unwrap(), 3, "Valid").unwrap();
+
+        let mut doc = Document::load(&file_path).unwrap();
+        let page_id = doc.get_pages()[&2];
+        doc.change_page_content(page_id, b"BT /F1 12 Tf 100 700 Td (Cut off".to_vec())
+            .unwrap();
+        doc.save(&file_path).unwrap();
+
+        assert_eq!(
+            validate_content_streams(file_path.to_str().unwrap()),
+            Ok(vec![2])
+        );
+    }
+
+    #[test]
+    fn test_parse_pdf_with_password() {
+        use crate::pdf::test_utils::create_encrypted_pdf;
+
+        let env = TestEnvironment::new("parse_password");
+        let file_path = env.test_dir().join("locked.pdf");
+        let path = file_path.to_str().unwrap();
+        create_encrypted_pdf(path, 2, "Quarterly", "s3cret").unwrap();
+
+        let metadata = parse_pdf(path, Some("s3cret".to_string())).unwrap();
+        assert_eq!(
+            metadata.get("Title").map(String::as_str),
+            Some("Quarterly Report")
+        );
+
+        let wrong = parse_pdf(path, Some("guess".to_string())).unwrap_err();
+        assert!(wrong.contains("Incorrect password"), "{}", wrong);
+        let missing = parse_pdf(path, None).unwrap_err();
+        assert!(missing.contains("password is required"), "{}", missing);
+    }
+
+    #[test]
+    fn test_parse_pdf_empty_user_password() {
+        use crate::pdf::test_utils::create_encrypted_pdf;
+
+        let env = TestEnvironment::new("parse_empty_password");
+        let file_path = env.test_dir().join("restricted.pdf");
+        let path = file_path.to_str().unwrap();
+        create_encrypted_pdf(path, 1, "Restricted", "").unwrap();
+
+        let metadata = parse_pdf(path, None).unwrap();
+        assert_eq!(
+            metadata.get("Title").map(String::as_str),
+            Some("Restricted Report")
+        );
+    }
 }
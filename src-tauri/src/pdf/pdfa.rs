@@ -0,0 +1,190 @@
+use crate::pdf::fonts::{collect_page_fonts, is_font_embedded};
+use crate::pdf::utils::load_document;
+use lopdf::{Dictionary, Document, Object};
+
+/// Call `visit` on every dictionary in the document, including stream dictionaries and
+/// dictionaries nested directly inside other objects (e.g. inline ExtGState entries).
+fn for_each_dictionary(doc: &Document, mut visit: impl FnMut(&Dictionary)) {
+    fn walk(object: &Object, visit: &mut dyn FnMut(&Dictionary)) {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            Object::Array(items) => {
+                items.iter().for_each(|item| walk(item, visit));
+                return;
+            }
+            _ => return,
+        };
+        visit(dict);
+        dict.iter().for_each(|(_, value)| walk(value, visit));
+    }
+    doc.objects
+        .values()
+        .for_each(|object| walk(object, &mut visit));
+}
+
+/// Whether a dictionary introduces transparency: a soft mask, a constant alpha below 1,
+/// a non-normal blend mode or a transparency group.
+fn uses_transparency(dict: &Dictionary) -> bool {
+    let soft_mask = dict
+        .get(b"SMask")
+        .is_ok_and(|o| o.as_name().map_or(true, |n| n != b"None"));
+    let alpha = [b"CA".as_slice(), b"ca".as_slice()].iter().any(|key| {
+        dict.get(key)
+            .and_then(|o| o.as_float())
+            .is_ok_and(|a| a < 1.0)
+    });
+    let blend = dict
+        .get(b"BM")
+        .and_then(|o| o.as_name())
+        .is_ok_and(|n| n != b"Normal" && n != b"Compatible");
+    let group = dict
+        .get(b"S")
+        .and_then(|o| o.as_name())
+        .is_ok_and(|n| n == b"Transparency");
+    soft_mask || alpha || blend || group
+}
+
+fn has_pdfa_output_intent(doc: &Document, catalog: &Dictionary) -> bool {
+    catalog
+        .get_deref(b"OutputIntents", doc)
+        .and_then(|o| o.as_array())
+        .is_ok_and(|intents| {
+            intents.iter().any(|intent| {
+                doc.dereference(intent)
+                    .and_then(|(_, o)| o.as_dict())
+                    .and_then(|d| d.get(b"S"))
+                    .and_then(|s| s.as_name())
+                    .is_ok_and(|s| s == b"GTS_PDFA1")
+            })
+        })
+}
+
+fn has_pdfa_identification(doc: &Document, catalog: &Dictionary) -> bool {
+    let metadata = match catalog
+        .get_deref(b"Metadata", doc)
+        .and_then(|o| o.as_stream())
+    {
+        Ok(stream) => stream,
+        Err(_) => return false,
+    };
+    let xmp = metadata
+        .decompressed_content()
+        .unwrap_or_else(|_| metadata.content.clone());
+    let xmp = String::from_utf8_lossy(&xmp);
+    xmp.contains("pdfaid:part") && xmp.contains("pdfaid:conformance")
+}
+
+/// Run a pre-flight checklist of common PDF/A-1b violations and describe each problem
+/// found. An empty list means the file passes the checked subset; it is not a full
+/// conformance validation.
+#[tauri::command]
+pub fn check_pdfa(path: &str) -> Result<Vec<String>, String> {
+    let doc = load_document(path, None)?;
+    let catalog = doc
+        .catalog()
+        .map_err(|e| format!("Failed to read document catalog: {}", e))?;
+
+    let mut issues = Vec::new();
+
+    if doc.is_encrypted() || doc.trailer.has(b"Encrypt") {
+        issues.push("Document is encrypted; PDF/A forbids encryption.".to_string());
+    }
+
+    let mut missing_fonts: Vec<String> = collect_page_fonts(&doc)
+        .into_iter()
+        .filter(|(id, _)| {
+            doc.get_dictionary(*id)
+                .map(|font| !is_font_embedded(&doc, font))
+                .unwrap_or(false)
+        })
+        .map(|(_, name)| name)
+        .collect();
+    missing_fonts.sort();
+    missing_fonts.dedup();
+    for name in missing_fonts {
+        issues.push(format!("Font '{}' is not embedded.", name));
+    }
+
+    let mut transparency = false;
+    let mut javascript = catalog
+        .get_deref(b"Names", &doc)
+        .and_then(|o| o.as_dict())
+        .is_ok_and(|names| names.has(b"JavaScript"));
+    for_each_dictionary(&doc, |dict| {
+        transparency |= uses_transparency(dict);
+        javascript |= dict.has(b"JS")
+            || dict
+                .get(b"S")
+                .and_then(|o| o.as_name())
+                .is_ok_and(|n| n == b"JavaScript");
+    });
+    if transparency {
+        issues.push(
+            "Document uses transparency (soft masks, alpha, blend modes or transparency groups)."
+                .to_string(),
+        );
+    }
+    if javascript {
+        issues.push("Document contains JavaScript.".to_string());
+    }
+
+    if !has_pdfa_output_intent(&doc, catalog) {
+        issues.push("No PDF/A OutputIntent (GTS_PDFA1) is present.".to_string());
+    }
+    if !has_pdfa_identification(&doc, catalog) {
+        issues.push("XMP metadata does not declare PDF/A conformance (pdfaid).".to_string());
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_check_pdfa_reports_unembedded_font() {
+        let (test_dir, output_dir) = setup_unique_paths("pdfa_fonts");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Archive").unwrap();
+
+        let issues = check_pdfa(input.to_str().unwrap()).unwrap();
+        assert!(issues.contains(&"Font 'Helvetica' is not embedded.".to_string()));
+        assert!(issues.iter().any(|i| i.contains("OutputIntent")));
+        assert!(!issues.iter().any(|i| i.contains("JavaScript")));
+        assert!(!issues.iter().any(|i| i.contains("transparency")));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_check_pdfa_reports_javascript_and_transparency() {
+        let (test_dir, output_dir) = setup_unique_paths("pdfa_js");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Script").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let action_id = doc.add_object(dictionary! {
+            "S" => "JavaScript",
+            "JS" => Object::string_literal("app.alert('hi');"),
+        });
+        let page_id = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page_id).unwrap().set(
+            "Group",
+            dictionary! { "S" => "Transparency", "CS" => "DeviceRGB" },
+        );
+        doc.catalog_mut()
+            .unwrap()
+            .set("OpenAction", Object::Reference(action_id));
+        doc.save(&input).unwrap();
+
+        let issues = check_pdfa(input.to_str().unwrap()).unwrap();
+        assert!(issues.contains(&"Document contains JavaScript.".to_string()));
+        assert!(issues.iter().any(|i| i.contains("transparency")));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
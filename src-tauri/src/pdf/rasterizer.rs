@@ -1,8 +1,13 @@
+#[cfg(feature = "render")]
 use pdfium_render::prelude::*;
+#[cfg(feature = "render")]
 use std::path::Path;
+#[cfg(feature = "render")]
 use std::fs;
+#[cfg(feature = "render")]
 use image::ImageFormat;
 
+#[cfg(feature = "render")]
 #[tauri::command]
 pub fn pdf_to_images(path: &str, output_dir: &str, format: &str) -> Result<Vec<String>, String> {
     // Ensure output directory exists
@@ -49,6 +54,12 @@ pub fn pdf_to_images(path: &str, output_dir: &str, format: &str) -> Result<Vec<S
     Ok(output_files)
 }
 
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn pdf_to_images(_path: &str, _output_dir: &str, _format: &str) -> Result<Vec<String>, String> {
+    Err("Converting pages to images requires the 'render' feature.".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     // Note: Tests for rasterization might fail in CI if pdfium is not present.
@@ -1,19 +1,83 @@
+use crate::pdf::utils::{load_document, rebuild_clean};
 use lopdf::Document;
 use std::collections::BTreeSet;
 use std::fs;
 use std::path::Path;
 
 #[tauri::command]
-pub fn delete_pages(path: &str, pages_to_delete: Vec<u32>, output_path: &str) -> Result<(), String> {
+pub fn delete_pages(
+    path: &str,
+    pages_to_delete: Vec<u32>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     if pages_to_delete.is_empty() {
         return Err("The list of pages to delete cannot be empty.".to_string());
     }
 
-    let input_path = Path::new(path);
-    if !input_path.exists() {
-        return Err(format!("Input file not found: {}", path));
+    let doc = load_document(path, None)?;
+
+    let page_count = doc.get_pages().len() as u32;
+    let mut pages_to_delete_set = BTreeSet::new();
+    for page_num in pages_to_delete {
+        if page_num == 0 || page_num > page_count {
+            return Err(format!(
+                "Invalid page number: {}. Page numbers must be between 1 and {}.",
+                page_num, page_count
+            ));
+        }
+        pages_to_delete_set.insert(page_num);
     }
 
+    let pages_to_delete_vec: Vec<u32> = pages_to_delete_set.into_iter().collect();
+    save_without_pages(doc, &pages_to_delete_vec, output_path, clean)
+}
+
+/// Keep only the listed pages, in their original document order, and drop the rest.
+/// Duplicates are ignored; listing every page is an error since nothing would change.
+#[tauri::command]
+pub fn keep_pages(
+    path: &str,
+    pages_to_keep: Vec<u32>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if pages_to_keep.is_empty() {
+        return Err("The list of pages to keep cannot be empty.".to_string());
+    }
+
+    let doc = load_document(path, None)?;
+
+    let page_count = doc.get_pages().len() as u32;
+    let mut pages_to_keep_set = BTreeSet::new();
+    for page_num in pages_to_keep {
+        if page_num == 0 || page_num > page_count {
+            return Err(format!(
+                "Invalid page number: {}. Page numbers must be between 1 and {}.",
+                page_num, page_count
+            ));
+        }
+        pages_to_keep_set.insert(page_num);
+    }
+    if pages_to_keep_set.len() as u32 == page_count {
+        return Err(format!(
+            "Keeping all {} pages of '{}' would leave the document unchanged.",
+            page_count, path
+        ));
+    }
+
+    let pages_to_delete_vec: Vec<u32> = (1..=page_count)
+        .filter(|page_num| !pages_to_keep_set.contains(page_num))
+        .collect();
+    save_without_pages(doc, &pages_to_delete_vec, output_path, clean)
+}
+
+fn save_without_pages(
+    mut doc: Document,
+    pages_to_delete: &[u32],
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     if let Some(parent_dir) = Path::new(output_path).parent() {
         if !parent_dir.exists() {
             fs::create_dir_all(parent_dir).map_err(|e| {
@@ -26,23 +90,12 @@ pub fn delete_pages(path: &str, pages_to_delete: Vec<u32>, output_path: &str) ->
         }
     }
 
-    let mut doc = Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
+    doc.delete_pages(pages_to_delete);
 
-    let page_count = doc.get_pages().len() as u32;
-    let mut pages_to_delete_set = BTreeSet::new();
-    for page_num in pages_to_delete {
-        if page_num == 0 || page_num > page_count {
-            return Err(format!(
-                "Invalid page number: {}. Page numbers must be between 1 and {}.",
-                page_num, page_count
-            ));
-        }
-        pages_to_delete_set.insert(page_num);
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
     }
 
-    let pages_to_delete_vec: Vec<u32> = pages_to_delete_set.into_iter().collect();
-    doc.delete_pages(&pages_to_delete_vec);
-
     doc.save(output_path)
         .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
 
@@ -108,6 +161,7 @@ mod tests {
             env.input_path_str(),
             pages_to_delete.clone(),
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_ok(), "delete_pages failed: {:?}", result.err());
@@ -149,6 +203,7 @@ mod tests {
             env.input_path_str(),
             pages_to_delete,
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(
@@ -174,6 +229,7 @@ mod tests {
             env.input_path_str(),
             pages_to_delete,
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err(), "Function should fail for empty pages list");
@@ -185,4 +241,91 @@ mod tests {
         }
         assert!(!output_path.exists());
     }
+
+    #[test]
+    fn test_delete_pages_clean_drops_cruft() {
+        let env = TestEnvironment::new("delete_clean");
+        let bloated = env.output_path("bloated.pdf");
+        let plain_path = env.output_path("plain.pdf");
+        let clean_path = env.output_path("clean.pdf");
+
+        // Unreferenced leftovers, as repeated edits tend to accumulate
+        let mut doc = Document::load(env.input_path_str()).unwrap();
+        for i in 0..10 {
+            doc.add_object(lopdf::Stream::new(
+                lopdf::Dictionary::new(),
+                format!("stale content {}", i).into_bytes(),
+            ));
+        }
+        doc.save(&bloated).unwrap();
+
+        let bloated_str = bloated.to_str().unwrap();
+        delete_pages(bloated_str, vec![2, 4], plain_path.to_str().unwrap(), None).unwrap();
+        delete_pages(
+            bloated_str,
+            vec![2, 4],
+            clean_path.to_str().unwrap(),
+            Some(true),
+        )
+        .unwrap();
+
+        let plain = Document::load(&plain_path).unwrap();
+        let clean = Document::load(&clean_path).unwrap();
+        assert!(
+            clean.objects.len() < plain.objects.len(),
+            "clean output has {} objects, plain output {}",
+            clean.objects.len(),
+            plain.objects.len()
+        );
+        assert_eq!(clean.get_pages().len(), 3);
+        assert_eq!(clean.extract_text(&[3]).unwrap().trim(), "Sample-Page 5");
+    }
+
+    #[test]
+    fn test_keep_pages_keeps_original_order() {
+        let env = TestEnvironment::new("keep_pages");
+        let output_path = env.output_path("kept.pdf");
+
+        let result = keep_pages(
+            env.input_path_str(),
+            vec![4, 2, 4],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "keep_pages failed: {:?}", result.err());
+
+        let output_doc = Document::load(&output_path).unwrap();
+        assert_eq!(output_doc.get_pages().len(), 2);
+        assert_eq!(
+            output_doc.extract_text(&[1]).unwrap().trim(),
+            "Sample-Page 2"
+        );
+        assert_eq!(
+            output_doc.extract_text(&[2]).unwrap().trim(),
+            "Sample-Page 4"
+        );
+    }
+
+    #[test]
+    fn test_keep_pages_rejects_whole_document() {
+        let env = TestEnvironment::new("keep_pages_all");
+        let output_path = env.output_path("kept_all.pdf");
+
+        let result = keep_pages(
+            env.input_path_str(),
+            vec![1, 2, 3, 4, 5, 5],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("unchanged"));
+        assert!(!output_path.exists());
+
+        let result = keep_pages(
+            env.input_path_str(),
+            vec![],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,809 @@
+//! Commands that rasterize pages through pdfium. The rendering entry points are only
+//! compiled with the `render` feature; without it they return an error instead.
+
+#[cfg(feature = "render")]
+use crate::pdf::parser::find_heavy_pages;
+#[cfg(feature = "render")]
+use crate::pdf::utils::{load_document, rebuild_clean};
+use crate::pdf::utils::{get_inherited_attribute, page_geometry, rect_from_object};
+use image::RgbImage;
+use lopdf::{dictionary, Document, Object, ObjectId, Stream};
+#[cfg(feature = "render")]
+use pdfium_render::prelude::*;
+#[cfg(feature = "render")]
+use std::fs;
+#[cfg(feature = "render")]
+use std::path::Path;
+
+// --- Shared helpers ---
+
+#[cfg(feature = "render")]
+fn bind_pdfium() -> Result<Pdfium, String> {
+    Ok(Pdfium::new(Pdfium::bind_to_system_library().map_err(
+        |e| {
+            format!(
+                "Failed to bind to Pdfium system library: {:?}. Please ensure Pdfium is installed.",
+                e
+            )
+        },
+    )?))
+}
+
+/// Render every page of `path` to an RGB image `width` pixels wide.
+#[cfg(feature = "render")]
+pub(crate) fn render_pages_rgb(path: &str, width: i32) -> Result<Vec<RgbImage>, String> {
+    let input_path = Path::new(path);
+    if !input_path.exists() {
+        return Err(format!("Input file not found: {}", path));
+    }
+    if !input_path.is_file() {
+        return Err(format!("Input path is not a file: {}", path));
+    }
+
+    let pdfium = bind_pdfium()?;
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| format!("Failed to load PDF '{}': {:?}", path, e))?;
+    let config = PdfRenderConfig::new().set_target_width(width);
+
+    document
+        .pages()
+        .iter()
+        .enumerate()
+        .map(|(index, page)| {
+            page.render_with_config(&config)
+                .map(|bitmap| bitmap.as_image().to_rgb8())
+                .map_err(|e| format!("Failed to render page {}: {:?}", index + 1, e))
+        })
+        .collect()
+}
+
+/// Wrap an RGB image as a Flate-compressed Image XObject.
+pub(crate) fn rgb_image_xobject(img: &RgbImage) -> Stream {
+    let mut stream = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => img.width() as i64,
+            "Height" => img.height() as i64,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+        },
+        img.as_raw().clone(),
+    );
+    // An uncompressed image is still valid if Flate fails
+    let _ = stream.compress();
+    stream
+}
+
+// --- generate_index_sheet: thumbnail contact sheet prepended to the document ---
+
+const INDEX_COLUMNS: usize = 4;
+const INDEX_ROWS: usize = 5;
+const INDEX_MARGIN: f64 = 36.0;
+const INDEX_CELL_PADDING: f64 = 6.0;
+const INDEX_LABEL_SIZE: f64 = 9.0;
+#[cfg(feature = "render")]
+const INDEX_THUMBNAIL_WIDTH: i32 = 200;
+
+/// Lay `thumbnails` out in a grid on as many new pages as needed (sized like the
+/// first page) and insert those pages at the front of `doc`. Each thumbnail is
+/// labeled with its page number. Returns the number of index pages added.
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(crate) fn prepend_index_sheets(
+    doc: &mut Document,
+    thumbnails: &[RgbImage],
+) -> Result<usize, String> {
+    let pages = doc.get_pages();
+    let first_page = *pages
+        .values()
+        .next()
+        .ok_or_else(|| "Document has no pages to index.".to_string())?;
+    let [x0, y0, x1, y1] = get_inherited_attribute(doc, first_page, b"MediaBox")
+        .and_then(rect_from_object)
+        .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+    let (page_w, page_h) = (x1 - x0, y1 - y0);
+    let pages_id = doc
+        .catalog()
+        .and_then(|c| c.get(b"Pages"))
+        .and_then(|p| p.as_reference())
+        .map_err(|e| format!("Failed to find page tree: {}", e))?;
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let cell_w = (page_w - 2.0 * INDEX_MARGIN) / INDEX_COLUMNS as f64;
+    let cell_h = (page_h - 2.0 * INDEX_MARGIN) / INDEX_ROWS as f64;
+    let per_sheet = INDEX_COLUMNS * INDEX_ROWS;
+
+    let mut sheet_ids = Vec::new();
+    for (sheet_index, chunk) in thumbnails.chunks(per_sheet).enumerate() {
+        let mut content = String::new();
+        let mut xobjects = lopdf::Dictionary::new();
+        for (slot, thumb) in chunk.iter().enumerate() {
+            let page_number = sheet_index * per_sheet + slot + 1;
+            let (col, row) = (slot % INDEX_COLUMNS, slot / INDEX_COLUMNS);
+            let cell_x = INDEX_MARGIN + col as f64 * cell_w;
+            let cell_top = page_h - INDEX_MARGIN - row as f64 * cell_h;
+
+            // Fit the thumbnail above its label, preserving aspect ratio
+            let box_w = cell_w - 2.0 * INDEX_CELL_PADDING;
+            let box_h = cell_h - 2.0 * INDEX_CELL_PADDING - INDEX_LABEL_SIZE * 1.5;
+            let scale =
+                (box_w / thumb.width().max(1) as f64).min(box_h / thumb.height().max(1) as f64);
+            let (draw_w, draw_h) = (thumb.width() as f64 * scale, thumb.height() as f64 * scale);
+            let draw_x = cell_x + (cell_w - draw_w) / 2.0;
+            let draw_y = cell_top - INDEX_CELL_PADDING - draw_h;
+
+            let name = format!("Th{}", page_number);
+            xobjects.set(
+                name.clone(),
+                Object::Reference(doc.add_object(rgb_image_xobject(thumb))),
+            );
+            content.push_str(&format!(
+                "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /{} Do Q\n",
+                draw_w, draw_h, draw_x, draw_y, name
+            ));
+            content.push_str(&format!(
+                "q 0.6 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S Q\n",
+                draw_x, draw_y, draw_w, draw_h
+            ));
+
+            // Helvetica digits are half an em wide, close enough to center the label
+            let label = format!("Page {}", page_number);
+            let label_w = label.len() as f64 * INDEX_LABEL_SIZE * 0.5;
+            content.push_str(&format!(
+                "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                INDEX_LABEL_SIZE,
+                cell_x + (cell_w - label_w) / 2.0,
+                draw_y - INDEX_LABEL_SIZE * 1.3,
+                label
+            ));
+        }
+
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+        sheet_ids.push(doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => Object::Reference(pages_id),
+            "MediaBox" => vec![0.into(), 0.into(), Object::Real(page_w as f32), Object::Real(page_h as f32)],
+            "Contents" => Object::Reference(content_id),
+            "Resources" => dictionary! {
+                "Font" => dictionary! { "F1" => Object::Reference(font_id) },
+                "XObject" => xobjects,
+            },
+        }));
+    }
+
+    let root = doc
+        .get_dictionary_mut(pages_id)
+        .map_err(|e| format!("Failed to access page tree: {}", e))?;
+    let mut kids: Vec<Object> = sheet_ids.iter().map(|id| Object::Reference(*id)).collect();
+    if let Ok(existing) = root.get(b"Kids").and_then(|k| k.as_array()) {
+        kids.extend(existing.iter().cloned());
+    }
+    let count = root.get(b"Count").and_then(|c| c.as_i64()).unwrap_or(0);
+    root.set("Kids", Object::Array(kids));
+    root.set("Count", Object::Integer(count + sheet_ids.len() as i64));
+
+    Ok(sheet_ids.len())
+}
+
+/// Prepend one or more contact-sheet pages showing a labeled thumbnail of every
+/// page, as a visual table of contents.
+#[cfg(feature = "render")]
+#[tauri::command]
+pub fn generate_index_sheet(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let thumbnails = render_pages_rgb(path, INDEX_THUMBNAIL_WIDTH)?;
+    let mut doc = load_document(path, None)?;
+    prepend_index_sheets(&mut doc, &thumbnails)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn generate_index_sheet(
+    _path: &str,
+    _output_path: &str,
+    _clean: Option<bool>,
+) -> Result<(), String> {
+    Err("Index sheets require the 'render' feature.".to_string())
+}
+
+// --- flatten_heavy_pages: rasterize vector-heavy pages ---
+
+#[cfg(feature = "render")]
+const FLATTEN_DPI: f64 = 150.0;
+
+/// Replace a page's content and resources with `img` drawn over its visible area.
+/// The image is expected as the page is displayed, so a rotated page is normalized
+/// to an upright MediaBox without `/Rotate`.
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(crate) fn replace_page_with_image(
+    doc: &mut Document,
+    page_id: ObjectId,
+    img: &RgbImage,
+) -> Result<(), String> {
+    let ([x0, y0, x1, y1], rotate) = page_geometry(doc, page_id);
+    let (w, h) = (x1 - x0, y1 - y0);
+    let image_id = doc.add_object(rgb_image_xobject(img));
+    let (cm, upright_box) = if rotate == 0 {
+        (format!("{:.2} 0 0 {:.2} {:.2} {:.2}", w, h, x0, y0), None)
+    } else {
+        let (dw, dh) = if rotate % 180 == 0 { (w, h) } else { (h, w) };
+        (format!("{:.2} 0 0 {:.2} 0 0", dw, dh), Some((dw, dh)))
+    };
+    let content_id = doc.add_object(Stream::new(
+        dictionary! {},
+        format!("q {} cm /Flat Do Q", cm).into_bytes(),
+    ));
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| format!("Failed to access page {:?}: {}", page_id, e))?;
+    page.set("Contents", Object::Reference(content_id));
+    page.set(
+        "Resources",
+        dictionary! { "XObject" => dictionary! { "Flat" => Object::Reference(image_id) } },
+    );
+    if let Some((dw, dh)) = upright_box {
+        for key in [&b"CropBox"[..], b"TrimBox", b"BleedBox", b"ArtBox"] {
+            page.remove(key);
+        }
+        page.set(
+            "MediaBox",
+            vec![
+                0.into(),
+                0.into(),
+                Object::Real(dw as f32),
+                Object::Real(dh as f32),
+            ],
+        );
+        // Explicit 0 so an inherited /Rotate doesn't turn the upright image again
+        page.set("Rotate", 0);
+    }
+    Ok(())
+}
+
+/// Rasterize the pages `find_heavy_pages` flags at `max_content_bytes`, replacing
+/// their vector content with a single image. Text on those pages stops being
+/// selectable. Returns the flattened page numbers.
+#[cfg(feature = "render")]
+#[tauri::command]
+pub fn flatten_heavy_pages(
+    path: &str,
+    max_content_bytes: usize,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<u32>, String> {
+    let heavy = find_heavy_pages(path, max_content_bytes)?;
+    let mut doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+
+    if !heavy.is_empty() {
+        let pdfium = bind_pdfium()?;
+        let document = pdfium
+            .load_pdf_from_file(path, None)
+            .map_err(|e| format!("Failed to load PDF '{}': {:?}", path, e))?;
+        for &page_num in &heavy {
+            let page_id = pages[&page_num];
+            let ([x0, y0, x1, y1], rotate) = page_geometry(&doc, page_id);
+            let display_width = if rotate % 180 == 0 { x1 - x0 } else { y1 - y0 };
+            let config = PdfRenderConfig::new()
+                .set_target_width((display_width / 72.0 * FLATTEN_DPI).round() as i32);
+            let img = document
+                .pages()
+                .get((page_num - 1) as u16)
+                .and_then(|page| {
+                    page.render_with_config(&config)
+                        .map(|bitmap| bitmap.as_image().to_rgb8())
+                })
+                .map_err(|e| format!("Failed to render page {}: {:?}", page_num, e))?;
+            replace_page_with_image(&mut doc, page_id, &img)?;
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.prune_objects();
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(heavy)
+}
+
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn flatten_heavy_pages(
+    _path: &str,
+    _max_content_bytes: usize,
+    _output_path: &str,
+    _clean: Option<bool>,
+) -> Result<Vec<u32>, String> {
+    Err("Flattening pages requires the 'render' feature.".to_string())
+}
+
+// --- tile_overview: every page shrunk onto one landscape poster ---
+
+// A4 landscape
+const OVERVIEW_PAGE_WIDTH: f64 = 842.0;
+const OVERVIEW_PAGE_HEIGHT: f64 = 595.0;
+const OVERVIEW_MARGIN: f64 = 24.0;
+const OVERVIEW_CELL_PADDING: f64 = 3.0;
+#[cfg(feature = "render")]
+const OVERVIEW_THUMBNAIL_WIDTH: i32 = 240;
+
+/// Columns and rows for `count` tiles of aspect ratio `aspect` (width / height) that
+/// let each tile be drawn as large as possible within `area_w` x `area_h`.
+fn overview_grid(count: usize, aspect: f64, area_w: f64, area_h: f64) -> (usize, usize) {
+    let tile_height = |(columns, rows): (usize, usize)| {
+        (area_w / columns as f64 / aspect).min(area_h / rows as f64)
+    };
+    (1..=count.max(1))
+        .map(|columns| (columns, count.div_ceil(columns).max(1)))
+        .max_by(|a, b| tile_height(*a).total_cmp(&tile_height(*b)))
+        .unwrap_or((1, 1))
+}
+
+/// Build a new one-page document with `thumbnails` tiled in reading order on a
+/// landscape page, the grid sized so the tiles come out as large as possible.
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(crate) fn build_tile_overview(thumbnails: &[RgbImage]) -> Result<Document, String> {
+    let first = thumbnails
+        .first()
+        .ok_or_else(|| "Document has no pages to tile.".to_string())?;
+    let aspect = first.width().max(1) as f64 / first.height().max(1) as f64;
+    let area_w = OVERVIEW_PAGE_WIDTH - 2.0 * OVERVIEW_MARGIN;
+    let area_h = OVERVIEW_PAGE_HEIGHT - 2.0 * OVERVIEW_MARGIN;
+    let (columns, rows) = overview_grid(thumbnails.len(), aspect, area_w, area_h);
+    let cell_w = area_w / columns as f64;
+    let cell_h = area_h / rows as f64;
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let mut content = String::new();
+    let mut xobjects = lopdf::Dictionary::new();
+    for (index, thumb) in thumbnails.iter().enumerate() {
+        let (col, row) = (index % columns, index / columns);
+        let box_w = cell_w - 2.0 * OVERVIEW_CELL_PADDING;
+        let box_h = cell_h - 2.0 * OVERVIEW_CELL_PADDING;
+        let scale =
+            (box_w / thumb.width().max(1) as f64).min(box_h / thumb.height().max(1) as f64);
+        let (draw_w, draw_h) = (thumb.width() as f64 * scale, thumb.height() as f64 * scale);
+        let draw_x = OVERVIEW_MARGIN + col as f64 * cell_w + (cell_w - draw_w) / 2.0;
+        let draw_y =
+            OVERVIEW_PAGE_HEIGHT - OVERVIEW_MARGIN - row as f64 * cell_h - (cell_h + draw_h) / 2.0;
+
+        let name = format!("Tile{}", index + 1);
+        xobjects.set(
+            name.clone(),
+            Object::Reference(doc.add_object(rgb_image_xobject(thumb))),
+        );
+        content.push_str(&format!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /{} Do Q\n",
+            draw_w, draw_h, draw_x, draw_y, name
+        ));
+        content.push_str(&format!(
+            "q 0.6 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S Q\n",
+            draw_x, draw_y, draw_w, draw_h
+        ));
+    }
+
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => Object::Reference(pages_id),
+        "MediaBox" => vec![0.into(), 0.into(), OVERVIEW_PAGE_WIDTH.into(), OVERVIEW_PAGE_HEIGHT.into()],
+        "Contents" => Object::Reference(content_id),
+        "Resources" => dictionary! { "XObject" => xobjects },
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![Object::Reference(page_id)],
+            "Count" => 1,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => Object::Reference(pages_id),
+    });
+    doc.trailer.set("Root", Object::Reference(catalog_id));
+
+    Ok(doc)
+}
+
+/// Write a single landscape page showing every page of `path` as a small tile, for
+/// at-a-glance viewing of the whole document.
+#[cfg(feature = "render")]
+#[tauri::command]
+pub fn tile_overview(path: &str, output_path: &str, clean: Option<bool>) -> Result<(), String> {
+    let thumbnails = render_pages_rgb(path, OVERVIEW_THUMBNAIL_WIDTH)?;
+    let mut doc = build_tile_overview(&thumbnails)?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn tile_overview(_path: &str, _output_path: &str, _clean: Option<bool>) -> Result<(), String> {
+    Err("Overview posters require the 'render' feature.".to_string())
+}
+
+// --- ink_coverage: share of each page that is not paper white ---
+
+/// Render width for coverage estimates; coverage is a ratio, so low resolution is enough.
+#[cfg(feature = "render")]
+const COVERAGE_RENDER_WIDTH: i32 = 150;
+/// A pixel counts as inked when any channel is darker than this.
+const COVERAGE_WHITE_THRESHOLD: u8 = 245;
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct PageCoverage {
+    pub page: u32,
+    pub coverage_percent: f64,
+}
+
+/// Percentage (0 to 100) of pixels in `img` that are not white.
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(crate) fn coverage_percent(img: &RgbImage) -> f64 {
+    let total = img.width() as u64 * img.height() as u64;
+    if total == 0 {
+        return 0.0;
+    }
+    let inked = img
+        .pixels()
+        .filter(|p| p.0.iter().any(|c| *c < COVERAGE_WHITE_THRESHOLD))
+        .count() as u64;
+    inked as f64 * 100.0 / total as f64
+}
+
+/// Approximate how much ink or toner each page uses: the page is rendered at low
+/// resolution and the share of non-white pixels reported.
+#[cfg(feature = "render")]
+#[tauri::command]
+pub fn ink_coverage(path: &str) -> Result<Vec<PageCoverage>, String> {
+    Ok(render_pages_rgb(path, COVERAGE_RENDER_WIDTH)?
+        .iter()
+        .enumerate()
+        .map(|(index, img)| PageCoverage {
+            page: index as u32 + 1,
+            coverage_percent: coverage_percent(img),
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn ink_coverage(_path: &str) -> Result<Vec<PageCoverage>, String> {
+    Err("Ink coverage estimates require the 'render' feature.".to_string())
+}
+
+// --- render_thumbnails: small PNG previews for page pickers ---
+
+/// Render every page to `output_dir/thumb_<N>.png`, scaled to fit within
+/// `max_dimension` pixels on both sides. Pdfium applies each page's `/Rotate`, so
+/// rotated pages come out the way a viewer shows them.
+#[cfg(feature = "render")]
+#[tauri::command]
+pub fn render_thumbnails(
+    path: &str,
+    output_dir: &str,
+    max_dimension: u32,
+) -> Result<Vec<String>, String> {
+    if max_dimension == 0 {
+        return Err("Thumbnail size must be at least 1 pixel.".to_string());
+    }
+    let input_path = Path::new(path);
+    if !input_path.exists() {
+        return Err(format!("Input file not found: {}", path));
+    }
+    if !input_path.is_file() {
+        return Err(format!("Input path is not a file: {}", path));
+    }
+
+    let pdfium = bind_pdfium()?;
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| format!("Failed to load PDF '{}': {:?}", path, e))?;
+    let limit = i32::try_from(max_dimension).unwrap_or(i32::MAX);
+    let config = PdfRenderConfig::new()
+        .set_maximum_width(limit)
+        .set_maximum_height(limit);
+
+    let output_dir_path = Path::new(output_dir);
+    if !output_dir_path.exists() {
+        fs::create_dir_all(output_dir_path)
+            .map_err(|e| format!("Failed to create output directory '{}': {}", output_dir, e))?;
+    }
+
+    let mut written = Vec::new();
+    for (index, page) in document.pages().iter().enumerate() {
+        let thumbnail = page
+            .render_with_config(&config)
+            .map(|bitmap| bitmap.as_image().to_rgb8())
+            .map_err(|e| format!("Failed to render page {}: {:?}", index + 1, e))?;
+        let file_path = output_dir_path.join(format!("thumb_{}.png", index + 1));
+        thumbnail.save(&file_path).map_err(|e| {
+            format!(
+                "Failed to write thumbnail to '{}': {}",
+                file_path.display(),
+                e
+            )
+        })?;
+        written.push(file_path.to_string_lossy().to_string());
+    }
+
+    Ok(written)
+}
+
+#[cfg(not(feature = "render"))]
+#[tauri::command]
+pub fn render_thumbnails(
+    _path: &str,
+    _output_dir: &str,
+    _max_dimension: u32,
+) -> Result<Vec<String>, String> {
+    Err("Thumbnail rendering not enabled: it requires the 'render' feature.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    /// The pdfium-backed tests only run where the library can be loaded.
+    #[cfg(feature = "render")]
+    fn pdfium_available() -> bool {
+        bind_pdfium().is_ok()
+    }
+
+    #[test]
+    fn test_prepend_index_sheets_layout() {
+        let (test_dir, output_dir) = setup_unique_paths("index_sheet_layout");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Index").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let thumbnails = vec![RgbImage::from_pixel(20, 26, image::Rgb([200, 200, 200])); 3];
+        assert_eq!(prepend_index_sheets(&mut doc, &thumbnails).unwrap(), 1);
+
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 4);
+        let sheet = doc.get_dictionary(pages[&1]).unwrap();
+        let xobjects = sheet
+            .get(b"Resources")
+            .and_then(|r| r.as_dict())
+            .and_then(|r| r.get(b"XObject"))
+            .and_then(|x| x.as_dict())
+            .unwrap();
+        assert_eq!(xobjects.len(), 3);
+        let second = String::from_utf8_lossy(&doc.get_page_content(pages[&2]).unwrap()).to_string();
+        assert!(second.contains("Index-Page 1"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_generate_index_sheet_prepends_page() {
+        if !pdfium_available() {
+            return;
+        }
+        let (test_dir, output_dir) = setup_unique_paths("index_sheet");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("indexed.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Index").unwrap();
+
+        let result = generate_index_sheet(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert!(
+            result.is_ok(),
+            "generate_index_sheet failed: {:?}",
+            result.err()
+        );
+
+        let doc = Document::load(&output).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 4);
+        for (n, page_id) in pages.iter().skip(1) {
+            let content =
+                String::from_utf8_lossy(&doc.get_page_content(*page_id).unwrap()).to_string();
+            assert!(content.contains(&format!("Index-Page {}", n - 1)));
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_replace_page_with_image_uprights_rotated_page() {
+        let (test_dir, output_dir) = setup_unique_paths("replace_page_with_image");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Flat").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        doc.get_dictionary_mut(pages[&2]).unwrap().set("Rotate", 90);
+        let img = RgbImage::from_pixel(10, 10, image::Rgb([0, 0, 0]));
+        replace_page_with_image(&mut doc, pages[&1], &img).unwrap();
+        replace_page_with_image(&mut doc, pages[&2], &img).unwrap();
+
+        let first = String::from_utf8_lossy(&doc.get_page_content(pages[&1]).unwrap()).to_string();
+        assert_eq!(first, "q 612.00 0 0 792.00 0.00 0.00 cm /Flat Do Q");
+        let second = doc.get_dictionary(pages[&2]).unwrap();
+        assert_eq!(second.get(b"Rotate").unwrap().as_i64().unwrap(), 0);
+        let media_box = rect_from_object(second.get(b"MediaBox").unwrap()).unwrap();
+        assert_eq!(media_box, [0.0, 0.0, 792.0, 612.0]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_build_tile_overview_is_one_page() {
+        for count in [1, 7, 40] {
+            let thumbnails = vec![RgbImage::from_pixel(24, 31, image::Rgb([90, 90, 90])); count];
+            let doc = build_tile_overview(&thumbnails).unwrap();
+            let pages = doc.get_pages();
+            assert_eq!(pages.len(), 1);
+            let [_, _, width, height] =
+                rect_from_object(doc.get_dictionary(pages[&1]).unwrap().get(b"MediaBox").unwrap())
+                    .unwrap();
+            assert!(width > height);
+            let tiles = doc
+                .get_dictionary(pages[&1])
+                .and_then(|p| p.get(b"Resources"))
+                .and_then(|r| r.as_dict())
+                .and_then(|r| r.get(b"XObject"))
+                .and_then(|x| x.as_dict())
+                .unwrap();
+            assert_eq!(tiles.len(), count);
+        }
+        assert_eq!(overview_grid(7, 0.77, 794.0, 547.0), (4, 2));
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_tile_overview_outputs_single_page() {
+        if !pdfium_available() {
+            return;
+        }
+        let (test_dir, output_dir) = setup_unique_paths("tile_overview");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("overview.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 9, "Tile").unwrap();
+
+        let result = tile_overview(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert!(result.is_ok(), "tile_overview failed: {:?}", result.err());
+        assert_eq!(Document::load(&output).unwrap().get_pages().len(), 1);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_coverage_percent_counts_non_white_pixels() {
+        let mut img = RgbImage::from_pixel(10, 10, image::Rgb([255, 255, 255]));
+        assert_eq!(coverage_percent(&img), 0.0);
+        for x in 0..10 {
+            for y in 0..3 {
+                img.put_pixel(x, y, image::Rgb([0, 0, 200]));
+            }
+        }
+        assert_eq!(coverage_percent(&img), 30.0);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_ink_coverage_blank_vs_filled_page() {
+        if !pdfium_available() {
+            return;
+        }
+        let (test_dir, output_dir) = setup_unique_paths("ink_coverage");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Ink").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let filled = doc.get_pages()[&2];
+        doc.change_page_content(filled, b"0 0 0 rg 0 0 612 792 re f".to_vec())
+            .unwrap();
+        doc.save(&input).unwrap();
+
+        let coverage = ink_coverage(input.to_str().unwrap()).unwrap();
+        assert_eq!(coverage.len(), 2);
+        assert!(coverage[0].coverage_percent < 5.0, "{:?}", coverage[0]);
+        assert!(coverage[1].coverage_percent > 95.0, "{:?}", coverage[1]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_render_thumbnails_fit_and_follow_rotation() {
+        if !pdfium_available() {
+            return;
+        }
+        let (test_dir, output_dir) = setup_unique_paths("render_thumbnails");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Thumb").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let rotated = doc.get_pages()[&2];
+        doc.get_dictionary_mut(rotated).unwrap().set("Rotate", 90);
+        doc.save(&input).unwrap();
+
+        let thumbs_dir = output_dir.join("thumbs");
+        let written =
+            render_thumbnails(input.to_str().unwrap(), thumbs_dir.to_str().unwrap(), 120).unwrap();
+        assert_eq!(written.len(), 2);
+
+        let (w1, h1) = image::image_dimensions(&written[0]).unwrap();
+        let (w2, h2) = image::image_dimensions(&written[1]).unwrap();
+        assert!(w1.max(h1) <= 120 && w2.max(h2) <= 120);
+        assert!(h1 > w1, "portrait page rendered {}x{}", w1, h1);
+        assert!(w2 > h2, "rotated page rendered {}x{}", w2, h2);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -1,10 +1,18 @@
-use crate::pdf::utils::manual_deep_copy;
+use crate::pdf::utils::{manual_deep_copy, rebuild_clean};
 use lopdf::{dictionary, Document, Object};
 use std::path::Path;
+use std::collections::HashSet;
 use std::fs;
 
+/// Write the pages of `path` in the order given by `new_order` (1-based source page
+/// numbers). Pages may be repeated or left out; every number must exist in the source.
 #[tauri::command]
-pub fn reorder_pages(path: &str, new_order: Vec<u32>, output_path: &str) -> Result<(), String> {
+pub fn reorder_pages(
+    path: &str,
+    new_order: Vec<u32>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     if new_order.is_empty() {
         return Err("The new order cannot be empty.".to_string());
     }
@@ -37,9 +45,21 @@ pub fn reorder_pages(path: &str, new_order: Vec<u32>, output_path: &str) -> Resu
     let object_map = manual_deep_copy(&doc, &mut new_doc, &page_ids_to_copy)
         .map_err(|e| format!("Failed to copy objects: {}", e))?;
 
+    let mut used = HashSet::new();
     let mut new_kids = Vec::with_capacity(new_order.len());
     for old_id in &page_ids_to_copy {
-        let &new_id = object_map.get(old_id).unwrap();
+        let mut new_id = *object_map
+            .get(old_id)
+            .ok_or_else(|| format!("Internal error: mapped page id for {:?} missing", old_id))?;
+        // A page listed more than once gets its own page object; the tree must not
+        // reference the same node twice. Content and resources stay shared.
+        if !used.insert(new_id) {
+            let duplicate = new_doc
+                .get_object(new_id)
+                .map_err(|e| format!("Failed to fetch copied page {:?}: {}", new_id, e))?
+                .clone();
+            new_id = new_doc.add_object(duplicate);
+        }
         new_kids.push(Object::Reference(new_id));
         
         if let Ok(page_obj) = new_doc.get_object_mut(new_id) {
@@ -66,6 +86,9 @@ pub fn reorder_pages(path: &str, new_order: Vec<u32>, output_path: &str) -> Resu
     );
     new_doc.trailer.set("Root", Object::Reference(new_catalog_id));
     new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
     new_doc.save(output_path).map_err(|e| format!("Failed to save: {}", e))?;
 
     Ok(())
@@ -87,18 +110,59 @@ mod tests {
         let result = reorder_pages(
             input_path.to_str().unwrap(),
             vec![3, 2, 1],
-            output_path.to_str().unwrap()
+            output_path.to_str().unwrap(),
+            None
         );
         
         assert!(result.is_ok());
         let output_doc = Document::load(output_path).unwrap();
         assert_eq!(output_doc.get_pages().len(), 3);
+        for (position, source_page) in [(1, 3), (2, 2), (3, 1)] {
+            let text = output_doc.extract_text(&[position]).unwrap();
+            assert!(
+                text.contains(&format!("Test Page-Page {}", source_page)),
+                "output page {} text: {:?}",
+                position,
+                text
+            );
+        }
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_reorder_pages_allows_duplicates() {
+        let (test_dir, output_dir) = setup_unique_paths("reorder_duplicates");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 3, "Dup").unwrap();
+
+        reorder_pages(
+            input_path.to_str().unwrap(),
+            vec![1, 2, 1],
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages = output_doc.get_pages();
+        assert_eq!(pages.len(), 3);
+        assert_ne!(pages[&1], pages[&3]);
+        assert!(output_doc.extract_text(&[3]).unwrap().contains("Dup-Page 1"));
+
+        let missing = reorder_pages(
+            input_path.to_str().unwrap(),
+            vec![1, 4],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert_eq!(missing.unwrap_err(), "Page 4 not found in document.");
         teardown_unique_paths(&test_dir, &output_dir);
     }
 
     #[test]
     fn test_reorder_empty_order() {
-        let result = reorder_pages("any.pdf", vec![], "out.pdf");
+        let result = reorder_pages("any.pdf", vec![], "out.pdf", None);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "The new order cannot be empty.");
     }
@@ -0,0 +1,301 @@
+use lopdf::{Dictionary, Object, ObjectId, Stream};
+use crate::pdf::parser::page_content_decodes;
+use crate::pdf::utils::{file_size, load_document, rebuild_clean};
+use std::fs;
+use std::path::Path;
+
+// --- collapse_revisions: flatten incremental updates into one revision ---
+
+/// Rewrite an incrementally-updated PDF as a single revision. Loading already
+/// resolves every object to its latest version; superseded and orphaned objects
+/// are then pruned and the survivors renumbered before writing one fresh xref.
+/// Returns the number of bytes saved (negative if the output grew).
+#[tauri::command]
+pub fn collapse_revisions(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<i64, String> {
+    let mut doc = load_document(path, None)?;
+
+    let original_size = file_size(path)?;
+
+    doc.prune_objects();
+    doc.renumber_objects();
+    // Links to earlier revisions' xref sections are meaningless in the new file
+    doc.trailer.remove(b"Prev");
+    doc.trailer.remove(b"XRefStm");
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save collapsed PDF to '{}': {}", output_path, e))?;
+
+    Ok(original_size as i64 - file_size(output_path)? as i64)
+}
+
+// --- remove_object: delete one object and null out references to it ---
+
+/// Replace every reference to `target` inside `object` with null.
+fn null_references(object: &mut Object, target: ObjectId) {
+    match object {
+        Object::Reference(id) if *id == target => *object = Object::Null,
+        Object::Array(items) => items
+            .iter_mut()
+            .for_each(|item| null_references(item, target)),
+        Object::Dictionary(dict) => dict
+            .iter_mut()
+            .for_each(|(_, value)| null_references(value, target)),
+        Object::Stream(stream) => stream
+            .dict
+            .iter_mut()
+            .for_each(|(_, value)| null_references(value, target)),
+        _ => {}
+    }
+}
+
+/// Delete a single object by id, replace every reference to it with null and prune
+/// whatever was only reachable through it. Returns whether the object existed; the
+/// document is written either way. The catalog cannot be removed.
+#[tauri::command]
+pub fn remove_object(
+    path: &str,
+    object_id: (u32, u16),
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<bool, String> {
+    let mut doc = load_document(path, None)?;
+    if doc
+        .trailer
+        .get(b"Root")
+        .and_then(|r| r.as_reference())
+        .is_ok_and(|root| root == object_id)
+    {
+        return Err(format!(
+            "Object {} {} is the document catalog and cannot be removed.",
+            object_id.0, object_id.1
+        ));
+    }
+
+    let existed = doc.objects.remove(&object_id).is_some();
+    for object in doc.objects.values_mut() {
+        null_references(object, object_id);
+    }
+    for (_, value) in doc.trailer.iter_mut() {
+        null_references(value, object_id);
+    }
+    doc.prune_objects();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save repaired PDF to '{}': {}", output_path, e))?;
+
+    Ok(existed)
+}
+
+// --- clear_broken_content: blank out pages whose content cannot be decoded ---
+
+/// Salvage a partially corrupt file: every page whose content stream cannot be decoded
+/// (see `validate_content_streams`) gets empty content, leaving it blank but valid so
+/// the rest of the document stays usable. Returns the pages that were cleared.
+#[tauri::command]
+pub fn clear_broken_content(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<u32>, String> {
+    let mut doc = load_document(path, None)?;
+
+    let mut cleared = Vec::new();
+    for (page_num, page_id) in doc.get_pages() {
+        if page_content_decodes(&doc, page_id) {
+            continue;
+        }
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), Vec::new()));
+        doc.get_dictionary_mut(page_id)
+            .map_err(|e| format!("Failed to access page {}: {}", page_num, e))?
+            .set("Contents", Object::Reference(content_id));
+        cleared.push(page_num);
+    }
+    doc.prune_objects();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save repaired PDF to '{}': {}", output_path, e))?;
+
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::{dictionary, Document, IncrementalDocument};
+
+    #[test]
+    fn test_collapse_revisions_drops_superseded_objects() {
+        let (test_dir, output_dir) = setup_unique_paths("collapse_revisions");
+        let original = test_dir.join("original.pdf");
+        let updated = test_dir.join("updated.pdf");
+        let output = output_dir.join("collapsed.pdf");
+        create_minimal_pdf(original.to_str().unwrap(), 2, "Rev").unwrap();
+
+        // Second revision: page 1 gets new content, orphaning its old stream
+        let mut incremental = IncrementalDocument::load(&original).unwrap();
+        let page_id = incremental.get_prev_documents().get_pages()[&1];
+        incremental
+            .opt_clone_object_to_new_document(page_id)
+            .unwrap();
+        let new_content = incremental.new_document.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 12 Tf 100 700 Td (Revised) Tj ET".to_vec(),
+        ));
+        incremental
+            .new_document
+            .get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Contents", Object::Reference(new_content));
+        incremental.save(&updated).unwrap();
+
+        let before = Document::load(&updated).unwrap();
+        let saved =
+            collapse_revisions(updated.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert!(saved > 0);
+
+        let after = Document::load(&output).unwrap();
+        assert!(after.objects.len() < before.objects.len());
+        assert!(!after.trailer.has(b"Prev"));
+        let before_pages = before.get_pages();
+        let after_pages = after.get_pages();
+        assert_eq!(after_pages.len(), 2);
+        for (num, page_id) in &after_pages {
+            assert_eq!(
+                after.get_page_content(*page_id).unwrap(),
+                before.get_page_content(before_pages[num]).unwrap()
+            );
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_remove_object_drops_orphan() {
+        let (test_dir, output_dir) = setup_unique_paths("remove_object");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("repaired.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Fix").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let orphan_id = doc.add_object(dictionary! { "Suspicious" => true });
+        let page_id = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("PieceInfo", Object::Reference(orphan_id));
+        doc.save(&input).unwrap();
+
+        let existed = remove_object(
+            input.to_str().unwrap(),
+            orphan_id,
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(existed);
+
+        let repaired = Document::load(&output).unwrap();
+        assert_eq!(repaired.get_pages().len(), 2);
+        assert!(repaired
+            .objects
+            .values()
+            .filter_map(|o| o.as_dict().ok())
+            .all(|d| !d.has(b"Suspicious")));
+        let page = repaired.get_dictionary(repaired.get_pages()[&1]).unwrap();
+        assert!(matches!(page.get(b"PieceInfo"), Ok(Object::Null)));
+
+        let existed = remove_object(
+            input.to_str().unwrap(),
+            (999, 0),
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(!existed);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_clear_broken_content_blanks_only_broken_page() {
+        let (test_dir, output_dir) = setup_unique_paths("clear_broken_content");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("salvaged.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Salvage").unwrap();
+
+        let mut doc = Document::load(&input).unwrap();
+        let broken_id = doc.get_pages()[&2];
+        doc.change_page_content(broken_id, b"BT /F1 12 Tf 100 700 Td (Trunc".to_vec())
+            .unwrap();
+        doc.save(&input).unwrap();
+
+        let cleared =
+            clear_broken_content(input.to_str().unwrap(), output.to_str().unwrap(), None).unwrap();
+        assert_eq!(cleared, vec![2]);
+
+        let salvaged = Document::load(&output).unwrap();
+        let pages = salvaged.get_pages();
+        assert_eq!(pages.len(), 3);
+        assert!(salvaged.get_page_content(pages[&2]).unwrap().is_empty());
+        for page in [1, 3] {
+            assert_eq!(
+                salvaged.get_page_content(pages[&page]).unwrap(),
+                doc.get_page_content(doc.get_pages()[&page]).unwrap()
+            );
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
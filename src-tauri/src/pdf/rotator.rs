@@ -1,17 +1,77 @@
+use crate::pdf::utils::{load_document, page_geometry, rebuild_clean};
 use lopdf::{Document, Error as LopdfError, Object, ObjectId};
 use std::fs;
 use std::path::Path;
 
-#[tauri::command]
-pub fn rotate_pdf(path: &str, pages: Vec<u32>, rotation: i32, output_path: &str) -> Result<(), String> {
+/// Check a requested angle and mode; returns whether the mode is `"absolute"`.
+fn parse_rotation_request(rotation: i32, mode: Option<&str>) -> Result<bool, String> {
     if ![0, 90, 180, 270, -90, -180, -270].contains(&rotation) {
         return Err("Invalid rotation angle. Must be one of 0, 90, 180, 270.".to_string());
     }
-
-    let input_path = Path::new(path);
-    if !input_path.exists() {
-        return Err(format!("Input file not found: {}", path));
+    match mode {
+        None | Some("relative") => Ok(false),
+        Some("absolute") => Ok(true),
+        Some(other) => Err(format!(
+            "Invalid rotation mode '{}'. Must be 'relative' or 'absolute'.",
+            other
+        )),
     }
+}
+
+/// The `/Rotate` each selected page (all when `pages` is empty) ends up with, as
+/// (page number, page id, degrees in 0..360).
+fn planned_rotations(
+    doc: &Document,
+    pages: &[u32],
+    rotation: i32,
+    absolute: bool,
+) -> Result<Vec<(u32, ObjectId, i32)>, String> {
+    let page_ids = doc.get_pages();
+    let targets: Vec<(u32, ObjectId)> = if pages.is_empty() {
+        page_ids.into_iter().collect()
+    } else {
+        pages
+            .iter()
+            .map(|p| {
+                page_ids
+                    .get(p)
+                    .map(|id| (*p, *id))
+                    .ok_or_else(|| format!("Page {} not found in document.", p))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+
+    targets
+        .into_iter()
+        .map(|(page_num, page_id)| {
+            // Relative turns start from the effective angle, including one inherited
+            // from the page tree, as preview_rotation and get_rotations report it
+            let current_rotation = page_geometry(doc, page_id).1 as i32;
+            let new_rotation = if absolute {
+                rotation.rem_euclid(360)
+            } else {
+                (current_rotation + rotation).rem_euclid(360)
+            };
+            Ok((page_num, page_id, new_rotation))
+        })
+        .collect()
+}
+
+/// Rotate the given pages (all when `pages` is empty). In the default `"relative"`
+/// mode `rotation` is added to each page's current `/Rotate`; in `"absolute"` mode the
+/// page is set to exactly `rotation`, normalized to 0, 90, 180 or 270.
+#[tauri::command]
+pub fn rotate_pdf(
+    path: &str,
+    pages: Vec<u32>,
+    rotation: i32,
+    output_path: &str,
+    clean: Option<bool>,
+    mode: Option<String>,
+) -> Result<(), String> {
+    let absolute = parse_rotation_request(rotation, mode.as_deref())?;
+
+    let mut doc = load_document(path, None)?;
 
     if let Some(parent_dir) = Path::new(output_path).parent() {
         if !parent_dir.exists() {
@@ -25,36 +85,110 @@ pub fn rotate_pdf(path: &str, pages: Vec<u32>, rotation: i32, output_path: &str)
         }
     }
 
-    let mut doc = Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
-
-    let page_ids = doc.get_pages();
-    let pages_to_rotate: Vec<ObjectId> = if pages.is_empty() {
-        page_ids.values().cloned().collect()
-    } else {
-        pages
-            .iter()
-            .map(|p| {
-                page_ids
-                    .get(p)
-                    .cloned()
-                    .ok_or_else(|| format!("Page {} not found in document.", p))
-            })
-            .collect::<Result<Vec<ObjectId>, String>>()?
-    };
-
-    for page_id in pages_to_rotate {
+    for (_, page_id, new_rotation) in planned_rotations(&doc, &pages, rotation, absolute)? {
         let page_dict = doc.get_object_mut(page_id)
             .and_then(|obj| obj.as_dict_mut())
             .map_err(|e: LopdfError| format!("Failed to get page dictionary for page {:?}: {}", page_id, e))?;
 
-        let current_rotation = page_dict
-            .get(b"Rotate")
-            .and_then(|obj| obj.as_i64())
-            .unwrap_or(0) as i32;
+        page_dict.set("Rotate", Object::Integer(new_rotation as i64));
+    }
 
-        let new_rotation = (current_rotation + rotation) % 360;
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
 
-        page_dict.set("Rotate", Object::Integer(new_rotation as i64));
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save rotated PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// What `rotate_pdf` would leave each page at, without writing anything: one
+/// (page number, degrees) pair per page, where pages outside `pages` keep their
+/// current effective rotation.
+#[tauri::command]
+pub fn preview_rotation(
+    path: &str,
+    pages: Vec<u32>,
+    rotation: i32,
+    mode: Option<String>,
+) -> Result<Vec<(u32, i32)>, String> {
+    let absolute = parse_rotation_request(rotation, mode.as_deref())?;
+    let doc = load_document(path, None)?;
+
+    let mut result: Vec<(u32, i32)> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_num, page_id)| (page_num, page_geometry(&doc, page_id).1 as i32))
+        .collect();
+    for (page_num, _, new_rotation) in planned_rotations(&doc, &pages, rotation, absolute)? {
+        result[page_num as usize - 1].1 = new_rotation;
+    }
+    Ok(result)
+}
+
+/// Effective rotation of every page in degrees, including any `/Rotate` inherited
+/// from the page tree.
+#[tauri::command]
+pub fn get_rotations(path: &str) -> Result<Vec<i64>, String> {
+    let doc = load_document(path, None)?;
+
+    Ok(doc
+        .get_pages()
+        .into_values()
+        .map(|page_id| page_geometry(&doc, page_id).1)
+        .collect())
+}
+
+/// Rotate every page identically by setting `/Rotate` once on the root Pages node,
+/// which all pages inherit, and removing the per-page and intermediate overrides.
+#[tauri::command]
+pub fn set_global_rotation(
+    path: &str,
+    rotation: i32,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if ![0, 90, 180, 270, -90, -180, -270].contains(&rotation) {
+        return Err("Invalid rotation angle. Must be one of 0, 90, 180, 270.".to_string());
+    }
+
+    let mut doc = load_document(path, None)?;
+
+    let root_pages_id = doc
+        .catalog()
+        .and_then(|c| c.get(b"Pages"))
+        .and_then(|p| p.as_reference())
+        .map_err(|e| format!("Failed to find page tree: {}", e))?;
+    for object in doc.objects.values_mut() {
+        if let Object::Dictionary(dict) = object {
+            let is_tree_node = dict
+                .get(b"Type")
+                .and_then(|t| t.as_name())
+                .is_ok_and(|t| t == b"Page" || t == b"Pages");
+            if is_tree_node {
+                dict.remove(b"Rotate");
+            }
+        }
+    }
+    doc.get_dictionary_mut(root_pages_id)
+        .map_err(|e| format!("Failed to access page tree: {}", e))?
+        .set("Rotate", Object::Integer(rotation.rem_euclid(360) as i64));
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
     }
 
     doc.save(output_path)
@@ -123,6 +257,8 @@ mod tests {
             pages_to_rotate.clone(),
             90,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_ok(), "rotate_pdf failed: {:?}", result.err());
@@ -168,6 +304,8 @@ mod tests {
             pages_to_rotate,
             45,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_err(), "Function should fail for invalid angle");
@@ -190,6 +328,8 @@ mod tests {
             vec![],
             180,
             output_path.to_str().unwrap(),
+            None,
+            None,
         );
 
         assert!(result.is_ok(), "rotate_pdf failed: {:?}", result.err());
@@ -216,4 +356,209 @@ mod tests {
             ),
         }
     }
+
+    #[test]
+    fn test_set_global_rotation_moves_rotate_to_pages_node() {
+        let env = TestEnvironment::new("rotate_global");
+        let mixed_path = env.output_path("mixed.pdf");
+        let output_path = env.output_path("global.pdf");
+        rotate_pdf(
+            env.input_path_str(),
+            vec![2],
+            180,
+            mixed_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let result = set_global_rotation(
+            mixed_path.to_str().unwrap(),
+            -90,
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "set_global_rotation failed: {:?}",
+            result.err()
+        );
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages_node = output_doc
+            .catalog()
+            .and_then(|c| c.get_deref(b"Pages", &output_doc))
+            .and_then(|p| p.as_dict())
+            .unwrap();
+        assert_eq!(pages_node.get(b"Rotate").unwrap().as_i64().unwrap(), 270);
+        for page_id in output_doc.get_pages().values() {
+            let page_dict = output_doc.get_dictionary(*page_id).unwrap();
+            assert!(page_dict.get(b"Rotate").is_err());
+        }
+
+        let rotations = get_rotations(output_path.to_str().unwrap()).unwrap();
+        assert_eq!(rotations, vec![270, 270, 270]);
+    }
+
+    #[test]
+    fn test_rotate_pdf_absolute_mode_sets_angle() {
+        let env = TestEnvironment::new("rotate_absolute");
+        let rotated_path = env.output_path("rotated.pdf");
+        let relative_path = env.output_path("relative.pdf");
+        let absolute_path = env.output_path("absolute.pdf");
+        let negative_path = env.output_path("negative.pdf");
+        rotate_pdf(
+            env.input_path_str(),
+            vec![1],
+            90,
+            rotated_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let stored_rotation = |path: &PathBuf| {
+            let doc = Document::load(path).unwrap();
+            let page_dict = doc.get_dictionary(doc.get_pages()[&1]).unwrap();
+            page_dict.get(b"Rotate").unwrap().as_i64().unwrap()
+        };
+        let rotated = rotated_path.to_str().unwrap();
+        let relative = Some("relative".to_string());
+        rotate_pdf(
+            rotated,
+            vec![1],
+            90,
+            relative_path.to_str().unwrap(),
+            None,
+            relative,
+        )
+        .unwrap();
+        assert_eq!(stored_rotation(&relative_path), 180);
+
+        let absolute = Some("absolute".to_string());
+        rotate_pdf(
+            rotated,
+            vec![1],
+            90,
+            absolute_path.to_str().unwrap(),
+            None,
+            absolute.clone(),
+        )
+        .unwrap();
+        assert_eq!(stored_rotation(&absolute_path), 90);
+
+        rotate_pdf(
+            rotated,
+            vec![1],
+            -90,
+            negative_path.to_str().unwrap(),
+            None,
+            absolute,
+        )
+        .unwrap();
+        assert_eq!(stored_rotation(&negative_path), 270);
+
+        let invalid = rotate_pdf(
+            rotated,
+            vec![1],
+            90,
+            negative_path.to_str().unwrap(),
+            None,
+            Some("sideways".to_string()),
+        );
+        assert!(invalid
+            .unwrap_err()
+            .contains("Invalid rotation mode 'sideways'"));
+    }
+
+    #[test]
+    fn test_rotate_pdf_negative_rotation_is_normalized() {
+        let env = TestEnvironment::new("rotate_negative");
+        for (rotation, expected) in [(-90, 270), (-270, 90)] {
+            let output_path = env.output_path(&format!("rotated_{}.pdf", expected));
+            rotate_pdf(
+                env.input_path_str(),
+                vec![2],
+                rotation,
+                output_path.to_str().unwrap(),
+                None,
+                None,
+            )
+            .unwrap();
+
+            let output_doc = Document::load(&output_path).unwrap();
+            let page_dict = output_doc
+                .get_dictionary(output_doc.get_pages()[&2])
+                .unwrap();
+            assert_eq!(
+                page_dict.get(b"Rotate").unwrap().as_i64().unwrap(),
+                expected,
+                "rotating by {}",
+                rotation
+            );
+        }
+    }
+
+    #[test]
+    fn test_preview_rotation_mixed_pages() {
+        let env = TestEnvironment::new("preview_rotation");
+        let mut doc = Document::load(env.input_path_str()).unwrap();
+        let pages = doc.get_pages();
+        doc.get_dictionary_mut(pages[&2]).unwrap().set("Rotate", 90);
+        doc.get_dictionary_mut(pages[&3])
+            .unwrap()
+            .set("Rotate", 180);
+        let input = env.output_path("rotated_input.pdf");
+        doc.save(&input).unwrap();
+        let input = input.to_str().unwrap();
+
+        assert_eq!(
+            preview_rotation(input, vec![1, 2], 270, None).unwrap(),
+            vec![(1, 270), (2, 0), (3, 180)]
+        );
+        assert_eq!(
+            preview_rotation(input, vec![], 90, Some("absolute".to_string())).unwrap(),
+            vec![(1, 90), (2, 90), (3, 90)]
+        );
+        // The preview leaves the file untouched
+        assert_eq!(get_rotations(input).unwrap(), vec![0, 90, 180]);
+
+        assert!(preview_rotation(input, vec![4], 90, None)
+            .unwrap_err()
+            .contains("Page 4 not found"));
+        assert!(preview_rotation(input, vec![1], 45, None)
+            .unwrap_err()
+            .contains("Invalid rotation angle"));
+    }
+
+    #[test]
+    fn test_rotate_pdf_relative_starts_from_inherited_rotation() {
+        let env = TestEnvironment::new("rotate_inherited");
+        let global_path = env.output_path("global.pdf");
+        let output_path = env.output_path("turned.pdf");
+        set_global_rotation(
+            env.input_path_str(),
+            270,
+            global_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            get_rotations(global_path.to_str().unwrap()).unwrap(),
+            vec![270, 270, 270]
+        );
+
+        rotate_pdf(
+            global_path.to_str().unwrap(),
+            vec![1],
+            90,
+            output_path.to_str().unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let rotations = get_rotations(output_path.to_str().unwrap()).unwrap();
+        assert_eq!(rotations, vec![0, 270, 270]);
+    }
 }
@@ -1,9 +1,12 @@
-use lopdf::{Document, Object};
-use std::path::Path;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use crate::pdf::utils::{file_size, get_inherited_attribute, load_document, rebuild_clean};
 use std::fs;
 
 #[tauri::command]
-pub fn sanitize_pdf(path: &str, output_path: &str) -> Result<(), String> {
+pub fn sanitize_pdf(path: &str, output_path: &str, clean: Option<bool>) -> Result<(), String> {
     let input_path = Path::new(path);
     if !input_path.exists() || !input_path.is_file() {
         return Err(format!("Input file not found: {}", path));
@@ -31,10 +34,443 @@ pub fn sanitize_pdf(path: &str, output_path: &str) -> Result<(), String> {
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save sanitized PDF: {}", e))?;
     Ok(())
 }
 
+/// Drop the `/MCID` link from a `BDC` property list. Returns true if the operation
+/// changed; a property list left empty turns the operator into a plain `BMC`.
+fn strip_mcid(operation: &mut Operation) -> bool {
+    if operation.operator != "BDC" || operation.operands.len() != 2 {
+        return false;
+    }
+    let Object::Dictionary(properties) = &mut operation.operands[1] else {
+        return false;
+    };
+    if properties.remove(b"MCID").is_none() {
+        return false;
+    }
+    if properties.is_empty() {
+        operation.operator = "BMC".to_string();
+        operation.operands.truncate(1);
+    }
+    true
+}
+
+/// Remove the logical structure (tagging) from a PDF: the catalog's `/StructTreeRoot`
+/// and `/MarkInfo`, every `/StructParent(s)` back-reference, and the `/MCID` links in
+/// page content. The orphaned structure elements are pruned before saving.
+/// Returns the number of bytes saved (negative if the output grew).
+#[tauri::command]
+pub fn untag_pdf(path: &str, output_path: &str, clean: Option<bool>) -> Result<i64, String> {
+    let mut doc = load_document(path, None)?;
+
+    let original_size = file_size(path)?;
+
+    let root_id = doc
+        .trailer
+        .get(b"Root")
+        .and_then(|root| root.as_reference())
+        .map_err(|e| format!("Failed to read document catalog: {}", e))?;
+    let catalog = doc
+        .get_dictionary_mut(root_id)
+        .map_err(|e| format!("Failed to read document catalog: {}", e))?;
+    catalog.remove(b"StructTreeRoot");
+    catalog.remove(b"MarkInfo");
+
+    // Pages, annotations and XObjects point back into the structure tree
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        dict.remove(b"StructParent");
+        dict.remove(b"StructParents");
+    }
+
+    for (page_num, page_id) in doc.get_pages() {
+        let data = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        let mut content = Content::decode(&data)
+            .map_err(|e| format!("Failed to parse content of page {}: {}", page_num, e))?;
+        let mut changed = false;
+        for operation in &mut content.operations {
+            changed |= strip_mcid(operation);
+        }
+        if changed {
+            let encoded = content
+                .encode()
+                .map_err(|e| format!("Failed to encode content of page {}: {}", page_num, e))?;
+            doc.change_page_content(page_id, encoded)
+                .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))?;
+        }
+    }
+
+    doc.prune_objects();
+    doc.compress();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save untagged PDF to '{}': {}", output_path, e))?;
+
+    Ok(original_size as i64 - file_size(output_path)? as i64)
+}
+
+/// Actions whose `/F` entry names a file outside the document.
+const EXTERNAL_FILE_ACTIONS: [&[u8]; 4] = [b"GoToR", b"GoToE", b"Launch", b"ImportData"];
+
+/// Whether a dictionary points at data outside the document: a stream whose data lives
+/// in an external `/F` file, a URL file specification (`/FS /URL`), a file
+/// specification without embedded `/EF` data, or an action that opens another file.
+fn is_external_reference(dict: &Dictionary, is_stream: bool) -> bool {
+    let name_is = |key: &[u8], expected: &[u8]| {
+        dict.get(key)
+            .and_then(|o| o.as_name())
+            .is_ok_and(|n| n == expected)
+    };
+    if is_stream {
+        return dict.has(b"F");
+    }
+    if name_is(b"FS", b"URL") {
+        return true;
+    }
+    if name_is(b"Type", b"Filespec") && !dict.has(b"EF") {
+        return true;
+    }
+    dict.has(b"F")
+        && dict
+            .get(b"S")
+            .and_then(|o| o.as_name())
+            .is_ok_and(|s| EXTERNAL_FILE_ACTIONS.contains(&s))
+}
+
+/// Walk `object` and everything it references, stopping at page objects and `/Parent`
+/// and `/P` back-links so one page's search does not spill into its neighbours.
+fn references_external_data(
+    doc: &Document,
+    object: &Object,
+    page_ids: &HashSet<ObjectId>,
+    visited: &mut HashSet<ObjectId>,
+) -> bool {
+    match object {
+        Object::Reference(id) => {
+            if page_ids.contains(id) || !visited.insert(*id) {
+                return false;
+            }
+            doc.get_object(*id)
+                .is_ok_and(|target| references_external_data(doc, target, page_ids, visited))
+        }
+        Object::Array(items) => items
+            .iter()
+            .any(|item| references_external_data(doc, item, page_ids, visited)),
+        Object::Dictionary(dict) => {
+            dictionary_references_external_data(doc, dict, false, page_ids, visited)
+        }
+        Object::Stream(stream) => {
+            dictionary_references_external_data(doc, &stream.dict, true, page_ids, visited)
+        }
+        _ => false,
+    }
+}
+
+fn dictionary_references_external_data(
+    doc: &Document,
+    dict: &Dictionary,
+    is_stream: bool,
+    page_ids: &HashSet<ObjectId>,
+    visited: &mut HashSet<ObjectId>,
+) -> bool {
+    is_external_reference(dict, is_stream)
+        || dict.iter().any(|(key, value)| {
+            key != b"Parent"
+                && key != b"P"
+                && references_external_data(doc, value, page_ids, visited)
+        })
+}
+
+/// List the pages whose resources or annotations depend on files outside the
+/// document: external stream data, URL file specifications, unembedded file
+/// specifications or actions that open other files. Such pages do not render or behave
+/// the same once the PDF is moved, and opening them can reach out to the network.
+#[tauri::command]
+pub fn find_external_references(path: &str) -> Result<Vec<u32>, String> {
+    let doc = load_document(path, None)?;
+    let pages = doc.get_pages();
+    let page_ids: HashSet<ObjectId> = pages.values().copied().collect();
+
+    let mut flagged = Vec::new();
+    for (page_num, page_id) in pages {
+        let mut visited = HashSet::new();
+        let resources = get_inherited_attribute(&doc, page_id, b"Resources");
+        let annotations = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| page.get(b"Annots").ok());
+        let external = resources
+            .into_iter()
+            .chain(annotations)
+            .any(|object| references_external_data(&doc, object, &page_ids, &mut visited));
+        if external {
+            flagged.push(page_num);
+        }
+    }
+
+    Ok(flagged)
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct EmbedReport {
+    /// File names whose data is now stored inside the document.
+    pub embedded: Vec<String>,
+    /// References that could not be embedded: missing files, URLs, and actions that
+    /// open another document.
+    pub unresolved: Vec<String>,
+}
+
+/// The file name in a file specification, which is either a string or a dictionary
+/// with `/UF` or `/F`.
+fn file_spec_name(spec: &Object) -> Option<String> {
+    let name = match spec {
+        Object::Dictionary(dict) => dict.get(b"UF").or_else(|_| dict.get(b"F")).ok()?,
+        other => other,
+    };
+    name.as_str()
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(bytes).to_string())
+}
+
+struct Embedder {
+    /// Canonical form of the directory external names are resolved against.
+    base_dir: PathBuf,
+    next_id: u32,
+    new_streams: Vec<(ObjectId, Stream)>,
+    report: EmbedReport,
+}
+
+impl Embedder {
+    /// The file `name` refers to inside `base_dir`. Names come from the document, so
+    /// absolute paths, `..` components and symlinks leading outside `base_dir` are
+    /// refused rather than letting a crafted PDF copy arbitrary local files.
+    fn resolve(&self, name: &str) -> Option<PathBuf> {
+        let relative = Path::new(name);
+        let plain = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !plain {
+            return None;
+        }
+        let resolved = self.base_dir.join(relative).canonicalize().ok()?;
+        resolved.starts_with(&self.base_dir).then_some(resolved)
+    }
+
+    fn read(&mut self, name: &str) -> Option<Vec<u8>> {
+        match self.resolve(name).and_then(|file| fs::read(file).ok()) {
+            Some(data) => {
+                self.report.embedded.push(name.to_string());
+                Some(data)
+            }
+            None => {
+                self.report.unresolved.push(name.to_string());
+                None
+            }
+        }
+    }
+
+    /// Replace external stream data (`/F`) with the file's bytes, which were encoded
+    /// with `/FFilter` rather than `/Filter`.
+    fn embed_stream_data(&mut self, stream: &mut Stream) {
+        let Some(name) = stream.dict.get(b"F").ok().and_then(file_spec_name) else {
+            return;
+        };
+        let Some(data) = self.read(&name) else {
+            return;
+        };
+        for (external, internal) in [
+            (b"FFilter".as_slice(), b"Filter".as_slice()),
+            (b"FDecodeParms".as_slice(), b"DecodeParms".as_slice()),
+        ] {
+            match stream.dict.remove(external) {
+                Some(value) => stream.dict.set(internal, value),
+                None => {
+                    stream.dict.remove(internal);
+                }
+            }
+        }
+        stream.dict.remove(b"F");
+        stream.dict.remove(b"DL");
+        stream.set_content(data);
+    }
+
+    /// Attach an `/EF` embedded file stream to a file specification that lacks one.
+    fn embed_file_spec(&mut self, spec: &mut Dictionary) {
+        if spec.has(b"EF") {
+            return;
+        }
+        let Some(name) = file_spec_name(&Object::Dictionary(spec.clone())) else {
+            return;
+        };
+        let is_url = spec
+            .get(b"FS")
+            .and_then(|o| o.as_name())
+            .is_ok_and(|fs| fs == b"URL");
+        if is_url {
+            self.report.unresolved.push(name);
+            return;
+        }
+        let Some(data) = self.read(&name) else {
+            return;
+        };
+        let size = data.len() as i64;
+        let mut stream = Stream::new(
+            dictionary! { "Type" => "EmbeddedFile", "Params" => dictionary! { "Size" => size } },
+            data,
+        );
+        let _ = stream.compress();
+        let id = (self.next_id, 0);
+        self.next_id += 1;
+        self.new_streams.push((id, stream));
+        spec.set("Type", "Filespec");
+        spec.set("EF", dictionary! { "F" => Object::Reference(id) });
+    }
+
+    fn visit(&mut self, object: &mut Object) {
+        match object {
+            Object::Array(items) => items.iter_mut().for_each(|item| self.visit(item)),
+            Object::Stream(stream) => {
+                if stream.dict.has(b"F") {
+                    self.embed_stream_data(stream);
+                }
+                self.visit_dictionary(&mut stream.dict);
+            }
+            Object::Dictionary(dict) => {
+                let is_file_spec = dict
+                    .get(b"Type")
+                    .and_then(|o| o.as_name())
+                    .is_ok_and(|t| t == b"Filespec")
+                    || dict.get(b"FS").and_then(|o| o.as_name()).is_ok();
+                if is_file_spec {
+                    self.embed_file_spec(dict);
+                }
+                self.visit_dictionary(dict);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_dictionary(&mut self, dict: &mut Dictionary) {
+        let external_action = dict.has(b"F")
+            && dict
+                .get(b"S")
+                .and_then(|o| o.as_name())
+                .is_ok_and(|s| EXTERNAL_FILE_ACTIONS.contains(&s));
+        if external_action {
+            if let Some(name) = dict.get(b"F").ok().and_then(file_spec_name) {
+                self.report.unresolved.push(name);
+            }
+            return;
+        }
+        for (key, value) in dict.iter_mut() {
+            // An annotation's /FS holds an inline file specification
+            if key == b"FS" {
+                if let Object::Dictionary(spec) = value {
+                    self.embed_file_spec(spec);
+                }
+            }
+            self.visit(value);
+        }
+    }
+}
+
+/// Make a document self-contained: external stream data and file specifications
+/// without embedded data are resolved against `base_dir`, read, and stored in the PDF.
+/// Names that would reach outside `base_dir` are not read and count as unresolved.
+/// URLs, missing files and actions that open other documents are left as they are
+/// and reported as unresolved.
+#[tauri::command]
+pub fn embed_external_references(
+    path: &str,
+    base_dir: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<EmbedReport, String> {
+    let mut doc = load_document(path, None)?;
+
+    if !Path::new(base_dir).is_dir() {
+        return Err(format!("Base directory not found: {}", base_dir));
+    }
+
+    let base_dir = Path::new(base_dir)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve base directory '{}': {}", base_dir, e))?;
+    let mut embedder = Embedder {
+        base_dir,
+        next_id: doc.max_id + 1,
+        new_streams: Vec::new(),
+        report: EmbedReport {
+            embedded: Vec::new(),
+            unresolved: Vec::new(),
+        },
+    };
+    for object in doc.objects.values_mut() {
+        embedder.visit(object);
+    }
+    for (id, stream) in embedder.new_streams {
+        doc.objects.insert(id, Object::Stream(stream));
+    }
+    doc.max_id = embedder.next_id - 1;
+    let mut report = embedder.report;
+    for names in [&mut report.embedded, &mut report.unresolved] {
+        names.sort();
+        names.dedup();
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path).map_err(|e| {
+        format!(
+            "Failed to save self-contained PDF to '{}': {}",
+            output_path, e
+        )
+    })?;
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -56,7 +492,8 @@ mod tests {
 
         let result = sanitize_pdf(
             input_path.to_str().unwrap(),
-            output_path.to_str().unwrap()
+            output_path.to_str().unwrap(),
+            None
         );
 
         assert!(result.is_ok());
@@ -68,4 +505,259 @@ mod tests {
 
         teardown_unique_paths(&test_dir, &output_dir);
     }
+
+    #[test]
+    fn test_untag_pdf_removes_structure_tree() {
+        use crate::pdf::parser::is_tagged;
+        use lopdf::{dictionary, Stream};
+
+        let (test_dir, output_dir) = setup_unique_paths("untag");
+        let input_path = test_dir.join("tagged.pdf");
+        let output_path = output_dir.join("untagged.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Untag").unwrap();
+
+        // Tag page 1: marked content with an MCID, a P element and a parent tree
+        let mut doc = Document::load(&input_path).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let content = doc.add_object(Stream::new(
+            dictionary! {},
+            b"/P <</MCID 0>> BDC BT /F1 12 Tf 100 700 Td (Tagged) Tj ET EMC".to_vec(),
+        ));
+        let paragraph = doc.add_object(dictionary! {
+            "Type" => "StructElem",
+            "S" => "P",
+            "Pg" => Object::Reference(page_id),
+            "K" => 0_i64,
+        });
+        let tree_root = doc.add_object(dictionary! {
+            "Type" => "StructTreeRoot",
+            "K" => Object::Reference(paragraph),
+            "ParentTree" => dictionary! {
+                "Nums" => vec![Object::Integer(0), vec![Object::Reference(paragraph)].into()],
+            },
+        });
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("Contents", Object::Reference(content));
+        page.set("StructParents", 0_i64);
+        let root_id = doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let catalog = doc.get_dictionary_mut(root_id).unwrap();
+        catalog.set("MarkInfo", dictionary! { "Marked" => true });
+        catalog.set("StructTreeRoot", Object::Reference(tree_root));
+        doc.save(&input_path).unwrap();
+        assert_eq!(is_tagged(input_path.to_str().unwrap()), Ok(true));
+
+        let result = untag_pdf(input_path.to_str().unwrap(), output_path.to_str().unwrap(), None);
+        assert!(result.is_ok(), "untag_pdf failed: {:?}", result.err());
+
+        assert_eq!(is_tagged(output_path.to_str().unwrap()), Ok(false));
+        let untagged = Document::load(&output_path).unwrap();
+        assert_eq!(untagged.get_pages().len(), 2);
+        let page_id = untagged.get_pages()[&1];
+        assert!(!untagged.get_dictionary(page_id).unwrap().has(b"StructParents"));
+        let content = Content::decode(&untagged.get_page_content(page_id).unwrap()).unwrap();
+        assert_eq!(content.operations[0].operator, "BMC");
+        // The structure element was pruned along with the tree
+        assert!(!untagged
+            .objects
+            .values()
+            .any(|o| o.type_name().is_ok_and(|t| t == "StructElem")));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_find_external_references_flags_linked_pages() {
+        use lopdf::{dictionary, Stream};
+
+        let (test_dir, output_dir) = setup_unique_paths("external_refs");
+        let input_path = test_dir.join("input.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 3, "External").unwrap();
+
+        let mut doc = Document::load(&input_path).unwrap();
+        let pages = doc.get_pages();
+        // Page 1 draws an image whose samples live in a file next to the PDF
+        let image = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "F" => Object::string_literal("photo.raw"),
+            },
+            Vec::new(),
+        ));
+        let shared_resources = doc
+            .get_dictionary(pages[&1])
+            .unwrap()
+            .get_deref(b"Resources", &doc)
+            .unwrap()
+            .as_dict()
+            .unwrap()
+            .clone();
+        let mut resources = shared_resources;
+        resources.set("XObject", dictionary! { "Im1" => Object::Reference(image) });
+        doc.get_dictionary_mut(pages[&1])
+            .unwrap()
+            .set("Resources", resources);
+        // Page 3 has an attachment that only points at a URL
+        let annotation = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "FileAttachment",
+            "Rect" => vec![0.into(), 0.into(), 20.into(), 20.into()],
+            "FS" => dictionary! {
+                "FS" => "URL",
+                "F" => Object::string_literal("https://example.com/data.csv"),
+            },
+            "P" => Object::Reference(pages[&3]),
+        });
+        doc.get_dictionary_mut(pages[&3])
+            .unwrap()
+            .set("Annots", vec![Object::Reference(annotation)]);
+        doc.save(&input_path).unwrap();
+
+        let flagged = find_external_references(input_path.to_str().unwrap()).unwrap();
+        assert_eq!(flagged, vec![1, 3]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_embed_external_references_makes_pages_self_contained() {
+        use lopdf::{dictionary, Stream};
+
+        let (test_dir, output_dir) = setup_unique_paths("embed_external");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("embedded.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Linked").unwrap();
+        fs::write(test_dir.join("photo.raw"), [0u8, 128, 255, 64]).unwrap();
+
+        let mut doc = Document::load(&input_path).unwrap();
+        let pages = doc.get_pages();
+        let image = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+                "F" => Object::string_literal("photo.raw"),
+            },
+            Vec::new(),
+        ));
+        let mut resources = doc
+            .get_dictionary(pages[&1])
+            .unwrap()
+            .get_deref(b"Resources", &doc)
+            .unwrap()
+            .as_dict()
+            .unwrap()
+            .clone();
+        resources.set("XObject", dictionary! { "Im1" => Object::Reference(image) });
+        doc.get_dictionary_mut(pages[&1])
+            .unwrap()
+            .set("Resources", resources);
+        let annotation = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "FileAttachment",
+            "Rect" => vec![0.into(), 0.into(), 20.into(), 20.into()],
+            "FS" => dictionary! { "Type" => "Filespec", "F" => Object::string_literal("missing.csv") },
+        });
+        doc.get_dictionary_mut(pages[&2])
+            .unwrap()
+            .set("Annots", vec![Object::Reference(annotation)]);
+        doc.save(&input_path).unwrap();
+        assert_eq!(
+            find_external_references(input_path.to_str().unwrap()).unwrap(),
+            vec![1, 2]
+        );
+
+        let report = embed_external_references(
+            input_path.to_str().unwrap(),
+            test_dir.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            EmbedReport {
+                embedded: vec!["photo.raw".to_string()],
+                unresolved: vec!["missing.csv".to_string()],
+            }
+        );
+
+        assert_eq!(
+            find_external_references(output_path.to_str().unwrap()).unwrap(),
+            vec![2]
+        );
+        let embedded = Document::load(&output_path).unwrap();
+        let stream = embedded.get_object(image).unwrap().as_stream().unwrap();
+        assert!(!stream.dict.has(b"F"));
+        assert_eq!(stream.content, vec![0u8, 128, 255, 64]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_embed_external_references_stays_inside_base_dir() {
+        use lopdf::dictionary;
+
+        let (test_dir, output_dir) = setup_unique_paths("embed_external_escape");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("embedded.pdf");
+        let base_dir = test_dir.join("base");
+        fs::create_dir_all(&base_dir).unwrap();
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Escape").unwrap();
+        let secret = test_dir.join("secret.txt");
+        fs::write(&secret, b"do not share").unwrap();
+        fs::write(base_dir.join("notes.txt"), b"fine").unwrap();
+
+        let names = [
+            secret.to_str().unwrap().to_string(),
+            "../secret.txt".to_string(),
+            "sub/../../secret.txt".to_string(),
+            "notes.txt".to_string(),
+        ];
+        let mut doc = Document::load(&input_path).unwrap();
+        let annotations: Vec<Object> = names
+            .iter()
+            .map(|name| {
+                Object::Reference(doc.add_object(dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "FileAttachment",
+                    "Rect" => vec![0.into(), 0.into(), 20.into(), 20.into()],
+                    "FS" => dictionary! {
+                        "Type" => "Filespec",
+                        "F" => Object::string_literal(name.as_str()),
+                    },
+                }))
+            })
+            .collect();
+        let page = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page)
+            .unwrap()
+            .set("Annots", annotations);
+        doc.save(&input_path).unwrap();
+
+        let report = embed_external_references(
+            input_path.to_str().unwrap(),
+            base_dir.to_str().unwrap(),
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(report.embedded, vec!["notes.txt".to_string()]);
+        let mut rejected = names[..3].to_vec();
+        rejected.sort();
+        assert_eq!(report.unresolved, rejected);
+        let written = fs::read(&output_path).unwrap();
+        assert!(!written
+            .windows(b"do not share".len())
+            .any(|w| w == b"do not share"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
@@ -1,8 +1,22 @@
-use lopdf::{Document, content::Content};
+use crate::pdf::annotations::remove_duplicate_annotations;
+use crate::pdf::utils::{load_document, rebuild_clean, validate_input_path};
+use lopdf::content::Content;
+use lopdf::Document;
 
+/// Draw every annotation that has an appearance stream into the page content and
+/// remove it. With `dedup_annotations`, annotations repeating an earlier one on the same
+/// page are dropped first, keeping the first copy.
 #[tauri::command]
-pub fn flatten_annotations(path: &str, output_path: &str) -> Result<(), String> {
-    let mut doc = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+pub fn flatten_annotations(
+    path: &str,
+    output_path: &str,
+    dedup_annotations: Option<bool>,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+    if dedup_annotations.unwrap_or(false) {
+        remove_duplicate_annotations(&mut doc);
+    }
     
     // Collect page IDs to avoid borrow checker issues
     let page_ids: Vec<(u32, lopdf::ObjectId)> = doc.get_pages().into_iter().collect();
@@ -90,23 +104,41 @@ pub fn flatten_annotations(path: &str, output_path: &str) -> Result<(), String>
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| format!("Failed to save flattened PDF: {}", e))?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn decrypt_pdf(path: &str, password: &str, output_path: &str) -> Result<(), String> {
-    let mut doc = Document::load(path)
-        .map_err(|e| format!("Failed to load: {}", e))?;
-    
-    if doc.is_encrypted() {
-        doc.decrypt(password.as_bytes()).map_err(|e| format!("Decryption failed: {}. Ensure password is correct.", e))?;
+pub fn decrypt_pdf(
+    path: &str,
+    password: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let mut doc = load_document(path, Some(password))?;
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
     }
-    
+
     doc.save(output_path).map_err(|e| format!("Failed to save decrypted PDF: {}", e))?;
     Ok(())
 }
 
+/// Whether the document is encrypted, i.e. its trailer has an `/Encrypt` entry. Nothing
+/// is decrypted, so the UI can ask for a password before running other commands.
+#[tauri::command]
+pub fn is_encrypted(path: &str) -> Result<bool, String> {
+    validate_input_path(path)?;
+
+    let doc = Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
+    Ok(doc.trailer.has(b"Encrypt"))
+}
+
 #[tauri::command]
 pub fn encrypt_pdf(_path: &str, _user_password: &str, _owner_password: &str, _output_path: &str) -> Result<(), String> {
     // lopdf supports encryption but it's often more reliable to use a specialized tool or ensure compatibility.
@@ -124,88 +156,128 @@ pub fn encrypt_pdf(_path: &str, _user_password: &str, _owner_password: &str, _ou
     Err("Encryption currently in development to ensure 100% security standards.".to_string())
 }
 
-#[tauri::command]
-pub fn compress_pdf(path: &str, output_path: &str, preset: &str) -> Result<(), String> {
-    let mut doc = Document::load(path).map_err(|e| format!("Failed to load PDF: {}", e))?;
-    
-    match preset.to_lowercase().as_str() {
-        "web" => {
-            // High compression: compress streams and prune unused objects
-            doc.prune_objects();
-            doc.compress();
-        }
-        "print" => {
-            // Lossless: only prune unused objects, no re-compression of streams
-            doc.prune_objects();
-        }
-        "min" | "minimal" => {
-            // Extreme: prune, compress, and remove non-essential document metadata
-            doc.prune_objects();
-            doc.compress();
-            
-            // Remove Metadata from Root
-            if let Ok(root_id) = doc.trailer.get(b"Root").and_then(|obj| obj.as_reference()) {
-                if let Ok(mut root) = doc.get_object(root_id).and_then(|obj| obj.as_dict()).cloned() {
-                    root.remove(b"Metadata");
-                    root.remove(b"PieceInfo");
-                    doc.objects.insert(root_id, lopdf::Object::Dictionary(root));
-                }
-            }
-            // Remove Info dictionary
-            doc.trailer.remove(b"Info");
-        }
-        _ => {
-            doc.prune_objects();
-            doc.compress();
-        }
-    }
-    
-    doc.save(output_path).map_err(|e| format!("Failed to save compressed PDF: {}", e))?;
-    Ok(())
-}
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
-    use lopdf::Document;
+    use lopdf::{dictionary, Document, Object};
 
     #[test]
-    fn test_decrypt_unencrypted_pdf() {
-        let (test_dir, output_dir) = setup_unique_paths("decrypt");
+    fn test_flatten_annotations_dedup_keeps_first() {
+        let (test_dir, output_dir) = setup_unique_paths("flatten_dedup");
         let input_path = test_dir.join("input.pdf");
         let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Dedup").unwrap();
 
-        create_minimal_pdf(input_path.to_str().unwrap(), 1, "DecryptTest").unwrap();
+        let mut doc = Document::load(&input_path).unwrap();
+        let note = |name: &str, contents: &str| {
+            dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Text",
+                "Rect" => vec![10.into(), 10.into(), 30.into(), 30.into()],
+                "Contents" => Object::string_literal(contents),
+                "NM" => Object::string_literal(name),
+            }
+        };
+        let annots: Vec<Object> = [
+            note("first", "Check"),
+            note("other", "Different"),
+            note("copy", "Check"),
+            note("second copy", "Check"),
+        ]
+        .into_iter()
+        .map(|annot| Object::Reference(doc.add_object(annot)))
+        .collect();
+        let page_id = doc.get_pages()[&1];
+        doc.get_dictionary_mut(page_id)
+            .unwrap()
+            .set("Annots", annots);
+        doc.save(&input_path).unwrap();
 
-        let result = decrypt_pdf(
+        flatten_annotations(
             input_path.to_str().unwrap(),
-            "password",
-            output_path.to_str().unwrap()
-        );
+            output_path.to_str().unwrap(),
+            Some(true),
+            None,
+        )
+        .unwrap();
 
-        assert!(result.is_ok());
-        assert!(output_path.exists());
+        let output_doc = Document::load(&output_path).unwrap();
+        let page = output_doc
+            .get_dictionary(output_doc.get_pages()[&1])
+            .unwrap();
+        let names: Vec<&[u8]> = page
+            .get(b"Annots")
+            .and_then(|a| a.as_array())
+            .unwrap()
+            .iter()
+            .map(|annot| {
+                output_doc
+                    .dereference(annot)
+                    .and_then(|(_, a)| a.as_dict())
+                    .and_then(|a| a.get(b"NM"))
+                    .and_then(|nm| nm.as_str())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(names, vec![&b"first"[..], b"other"]);
 
         teardown_unique_paths(&test_dir, &output_dir);
     }
 
     #[test]
-    fn test_compress_pdf_success() {
-        let (test_dir, output_dir) = setup_unique_paths("compress");
+    fn test_decrypt_unencrypted_pdf() {
+        let (test_dir, output_dir) = setup_unique_paths("decrypt");
         let input_path = test_dir.join("input.pdf");
         let output_path = output_dir.join("output.pdf");
 
-        create_minimal_pdf(input_path.to_str().unwrap(), 2, "CompressTest").unwrap();
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "DecryptTest").unwrap();
 
-        let result = compress_pdf(
+        let result = decrypt_pdf(
             input_path.to_str().unwrap(),
+            "password",
             output_path.to_str().unwrap(),
-            "web"
+            None
         );
 
         assert!(result.is_ok());
         assert!(output_path.exists());
 
+        let missing = decrypt_pdf(
+            test_dir.join("missing.pdf").to_str().unwrap(),
+            "password",
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(missing.unwrap_err().starts_with("Input file not found"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_is_encrypted() {
+        let (test_dir, output_dir) = setup_unique_paths("is_encrypted");
+        let plain_path = test_dir.join("plain.pdf");
+        let locked_path = test_dir.join("locked.pdf");
+        create_minimal_pdf(plain_path.to_str().unwrap(), 1, "Plain").unwrap();
+        create_minimal_pdf(locked_path.to_str().unwrap(), 1, "Locked").unwrap();
+
+        let mut doc = Document::load(&locked_path).unwrap();
+        let encrypt_id = doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 1,
+            "R" => 2,
+            "Length" => 40,
+            "P" => -4,
+            "O" => Object::string_literal(vec![0u8; 32]),
+            "U" => Object::string_literal(vec![0u8; 32]),
+        });
+        doc.trailer.set("Encrypt", Object::Reference(encrypt_id));
+        doc.save(&locked_path).unwrap();
+
+        assert_eq!(is_encrypted(plain_path.to_str().unwrap()), Ok(false));
+        assert_eq!(is_encrypted(locked_path.to_str().unwrap()), Ok(true));
+
         teardown_unique_paths(&test_dir, &output_dir);
     }
 }
@@ -0,0 +1,642 @@
+use crate::pdf::colorconv::naive_cmyk;
+use crate::pdf::utils::{get_inherited_attribute, load_document, rebuild_clean};
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// Colorant names that are not spot inks
+const NON_SPOT_COLORANTS: [&[u8]; 6] = [b"All", b"None", b"Cyan", b"Magenta", b"Yellow", b"Black"];
+
+/// Collect the spot colorants named by a color space (Separation, DeviceN, or an
+/// Indexed/Pattern space built on one of them).
+fn collect_colorants(doc: &Document, space: &Object, inks: &mut BTreeSet<String>) {
+    let Ok((_, space)) = doc.dereference(space) else {
+        return;
+    };
+    let Ok(items) = space.as_array() else {
+        return;
+    };
+    let mut add = |name: &Object| {
+        if let Ok(name) = name.as_name() {
+            if !NON_SPOT_COLORANTS.contains(&name) {
+                inks.insert(String::from_utf8_lossy(name).to_string());
+            }
+        }
+    };
+    match items.first().and_then(|f| f.as_name().ok()) {
+        Some(b"Separation") => {
+            if let Some(name) = items.get(1) {
+                add(name);
+            }
+        }
+        Some(b"DeviceN") => {
+            if let Some(Ok((_, names))) = items.get(1).map(|n| doc.dereference(n)) {
+                for name in names.as_array().map(|a| a.as_slice()).unwrap_or_default() {
+                    add(name);
+                }
+            }
+        }
+        Some(b"Indexed") | Some(b"Pattern") => {
+            if let Some(base) = items.get(1) {
+                collect_colorants(doc, base, inks);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Scan a resource dictionary's color spaces, shadings and XObjects (recursing into
+/// forms) for spot colorants.
+fn scan_resources(
+    doc: &Document,
+    resources: &Dictionary,
+    visited: &mut HashSet<ObjectId>,
+    inks: &mut BTreeSet<String>,
+) {
+    let entries = |key: &[u8]| -> Vec<&Object> {
+        resources
+            .get_deref(key, doc)
+            .and_then(|o| o.as_dict())
+            .map(|d| d.iter().map(|(_, v)| v).collect())
+            .unwrap_or_default()
+    };
+
+    for space in entries(b"ColorSpace") {
+        collect_colorants(doc, space, inks);
+    }
+    for shading in entries(b"Shading") {
+        let dict = match doc.dereference(shading) {
+            Ok((_, Object::Dictionary(dict))) => dict,
+            Ok((_, Object::Stream(stream))) => &stream.dict,
+            _ => continue,
+        };
+        if let Ok(space) = dict.get(b"ColorSpace") {
+            collect_colorants(doc, space, inks);
+        }
+    }
+    for xobject in entries(b"XObject") {
+        let Object::Reference(id) = xobject else {
+            continue;
+        };
+        if !visited.insert(*id) {
+            continue;
+        }
+        let Ok(stream) = doc.get_object(*id).and_then(|o| o.as_stream()) else {
+            continue;
+        };
+        if let Ok(space) = stream.dict.get(b"ColorSpace") {
+            collect_colorants(doc, space, inks);
+        }
+        if let Ok(form_resources) = stream
+            .dict
+            .get_deref(b"Resources", doc)
+            .and_then(|r| r.as_dict())
+        {
+            scan_resources(doc, form_resources, visited, inks);
+        }
+    }
+}
+
+/// List the spot colorants (Separation and DeviceN inks such as "PANTONE 185 C")
+/// used by the document's pages, sorted by name. Process colors are not reported, so
+/// a CMYK/RGB-only document returns an empty list.
+#[tauri::command]
+pub fn list_separations(path: &str) -> Result<Vec<String>, String> {
+    let doc = load_document(path, None)?;
+
+    let mut inks = BTreeSet::new();
+    let mut visited = HashSet::new();
+    for page_id in doc.get_pages().into_values() {
+        if let Some(Object::Dictionary(resources)) =
+            get_inherited_attribute(&doc, page_id, b"Resources")
+        {
+            scan_resources(&doc, resources, &mut visited, &mut inks);
+        }
+    }
+
+    Ok(inks.into_iter().collect())
+}
+
+// --- flatten_separations: spot colors to process CMYK ---
+
+/// A Separation color space that can be re-expressed in DeviceCMYK.
+struct SpotConversion {
+    name: String,
+    function: Object,
+    alternate_components: usize,
+}
+
+impl SpotConversion {
+    /// Parse `[/Separation name alternate tintTransform]` if its alternate is a
+    /// Gray/RGB/CMYK space and the tint transform can be evaluated here.
+    fn from_space(doc: &Document, space: &Object) -> Option<Self> {
+        let items = doc.dereference(space).ok()?.1.as_array().ok()?;
+        if items.len() != 4 || items[0].as_name().ok()? != b"Separation" {
+            return None;
+        }
+        let name = String::from_utf8_lossy(items[1].as_name().ok()?).to_string();
+        let alternate_components = match doc.dereference(&items[2]).ok()?.1 {
+            Object::Name(n) => match n.as_slice() {
+                b"DeviceGray" | b"CalGray" => 1,
+                b"DeviceRGB" | b"CalRGB" => 3,
+                b"DeviceCMYK" => 4,
+                _ => return None,
+            },
+            Object::Array(a) if a.first().and_then(|o| o.as_name().ok()) == Some(b"ICCBased") => {
+                let profile = doc.dereference(a.get(1)?).ok()?.1.as_stream().ok()?;
+                profile.dict.get(b"N").and_then(|n| n.as_i64()).ok()? as usize
+            }
+            _ => return None,
+        };
+        let function = doc.dereference(&items[3]).ok()?.1.clone();
+        let conversion = SpotConversion {
+            name,
+            function,
+            alternate_components,
+        };
+        conversion.cmyk(1.0).map(|_| conversion)
+    }
+
+    /// Evaluate the tint transform at `tint` and express the result as CMYK.
+    fn cmyk(&self, tint: f64) -> Option<[f64; 4]> {
+        let values = evaluate_tint_function(&self.function, tint.clamp(0.0, 1.0))?;
+        match (self.alternate_components, values.as_slice()) {
+            (1, [g]) => Some([0.0, 0.0, 0.0, 1.0 - g]),
+            (3, [r, g, b]) => Some(naive_cmyk(*r, *g, *b)),
+            (4, [c, m, y, k]) => Some([*c, *m, *y, *k]),
+            _ => None,
+        }
+    }
+}
+
+fn number_array(dict: &Dictionary, key: &[u8]) -> Option<Vec<f64>> {
+    dict.get(key)
+        .and_then(|o| o.as_array())
+        .ok()?
+        .iter()
+        .map(|o| o.as_float().ok().map(|v| v as f64))
+        .collect()
+}
+
+/// Evaluate a one-input PDF function: type 2 (exponential) or type 0 (sampled, 8 bits
+/// per sample). PostScript (type 4) and stitching functions are not supported.
+fn evaluate_tint_function(function: &Object, tint: f64) -> Option<Vec<f64>> {
+    let (dict, samples) = match function {
+        Object::Dictionary(dict) => (dict, None),
+        Object::Stream(stream) => (
+            &stream.dict,
+            Some(
+                stream
+                    .decompressed_content()
+                    .unwrap_or_else(|_| stream.content.clone()),
+            ),
+        ),
+        _ => return None,
+    };
+    match dict.get(b"FunctionType").and_then(|t| t.as_i64()).ok()? {
+        2 => {
+            let c0 = number_array(dict, b"C0").unwrap_or_else(|| vec![0.0]);
+            let c1 = number_array(dict, b"C1").unwrap_or_else(|| vec![1.0]);
+            let n = dict.get(b"N").and_then(|n| n.as_float()).ok()? as f64;
+            let factor = tint.powf(n);
+            Some(
+                c0.iter()
+                    .zip(&c1)
+                    .map(|(a, b)| a + factor * (b - a))
+                    .collect(),
+            )
+        }
+        0 => {
+            let samples = samples?;
+            if dict.get(b"BitsPerSample").and_then(|b| b.as_i64()).ok()? != 8 {
+                return None;
+            }
+            let size = *number_array(dict, b"Size")?.first()? as usize;
+            let range = number_array(dict, b"Range")?;
+            let outputs = range.len() / 2;
+            let encode =
+                number_array(dict, b"Encode").unwrap_or_else(|| vec![0.0, size as f64 - 1.0]);
+            let decode = number_array(dict, b"Decode").unwrap_or_else(|| range.clone());
+            if size < 2
+                || outputs == 0
+                || range.len() % 2 != 0
+                || decode.len() < 2 * outputs
+                || samples.len() < size * outputs
+                || encode.len() < 2
+            {
+                return None;
+            }
+            let position =
+                (encode[0] + tint * (encode[1] - encode[0])).clamp(0.0, size as f64 - 1.0);
+            let (low, frac) = (position.floor() as usize, position.fract());
+            let high = (low + 1).min(size - 1);
+            Some(
+                (0..outputs)
+                    .map(|j| {
+                        let a = samples[low * outputs + j] as f64;
+                        let b = samples[high * outputs + j] as f64;
+                        let sample = (a + frac * (b - a)) / 255.0;
+                        let value = decode[2 * j] + sample * (decode[2 * j + 1] - decode[2 * j]);
+                        value.clamp(range[2 * j], range[2 * j + 1])
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Convertible Separation spaces in a resource dictionary's `/ColorSpace`, by resource name.
+fn resource_spots(
+    doc: &Document,
+    resources: Option<&Dictionary>,
+) -> HashMap<Vec<u8>, SpotConversion> {
+    resources
+        .and_then(|r| r.get_deref(b"ColorSpace", doc).ok())
+        .and_then(|c| c.as_dict().ok())
+        .map(|spaces| {
+            spaces
+                .iter()
+                .filter_map(|(name, space)| {
+                    SpotConversion::from_space(doc, space).map(|spot| (name.clone(), spot))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cmyk_operands(cmyk: [f64; 4]) -> Vec<Object> {
+    cmyk.iter()
+        .map(|v| Object::Real(((v * 10000.0).round() / 10000.0) as f32))
+        .collect()
+}
+
+/// Rewrite content that paints with the given Separation resources: `cs`/`CS`
+/// switch to DeviceCMYK (with the spot's initial full tint) and `sc`/`scn` tints are
+/// replaced by their CMYK equivalents. Returns the spot names that were used.
+fn convert_spot_operations(
+    content: &mut Content,
+    spots: &HashMap<Vec<u8>, SpotConversion>,
+) -> BTreeSet<String> {
+    let mut used = BTreeSet::new();
+    let (mut fill, mut stroke): (Option<&SpotConversion>, Option<&SpotConversion>) = (None, None);
+    let mut operations = Vec::with_capacity(content.operations.len());
+    for mut operation in content.operations.drain(..) {
+        match operation.operator.as_str() {
+            "cs" | "CS" => {
+                let is_fill = operation.operator == "cs";
+                let spot = operation
+                    .operands
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .and_then(|n| spots.get(n));
+                if is_fill {
+                    fill = spot;
+                } else {
+                    stroke = spot;
+                }
+                if let Some(cmyk) = spot.and_then(|s| s.cmyk(1.0)) {
+                    used.insert(spot.map(|s| s.name.clone()).unwrap_or_default());
+                    operation.operands = vec![Object::Name(b"DeviceCMYK".to_vec())];
+                    operations.push(operation);
+                    let set = if is_fill { "sc" } else { "SC" };
+                    operations.push(Operation::new(set, cmyk_operands(cmyk)));
+                    continue;
+                }
+            }
+            "sc" | "scn" | "SC" | "SCN" => {
+                let spot = if operation.operator.starts_with('s') {
+                    fill
+                } else {
+                    stroke
+                };
+                let tint = operation.operands.first().and_then(|o| o.as_float().ok());
+                if let (Some(spot), Some(tint), 1) = (spot, tint, operation.operands.len()) {
+                    if let Some(cmyk) = spot.cmyk(tint as f64) {
+                        operation.operands = cmyk_operands(cmyk);
+                    }
+                }
+            }
+            "g" | "rg" | "k" => fill = None,
+            "G" | "RG" | "K" => stroke = None,
+            _ => {}
+        }
+        operations.push(operation);
+    }
+    content.operations = operations;
+    used
+}
+
+/// Drop converted spot entries from `/ColorSpace` resource dictionaries held inline in
+/// `dict` (at any depth). Returns true if anything was removed.
+fn strip_spot_entries(doc: &Document, dict: &mut Dictionary, converted: &BTreeSet<String>) -> bool {
+    let mut changed = false;
+    for (key, value) in dict.iter_mut() {
+        let Object::Dictionary(inner) = value else {
+            continue;
+        };
+        if key.as_slice() == b"ColorSpace" {
+            changed |= remove_converted_spaces(doc, inner, converted);
+        } else {
+            changed |= strip_spot_entries(doc, inner, converted);
+        }
+    }
+    changed
+}
+
+fn remove_converted_spaces(
+    doc: &Document,
+    spaces: &mut Dictionary,
+    converted: &BTreeSet<String>,
+) -> bool {
+    let names: Vec<Vec<u8>> = spaces
+        .iter()
+        .filter(|(_, space)| {
+            SpotConversion::from_space(doc, space).is_some_and(|s| converted.contains(&s.name))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in &names {
+        spaces.remove(name);
+    }
+    !names.is_empty()
+}
+
+/// Replace Separation (spot) colors used for painting in page and form content with
+/// their process equivalent, evaluated through each spot's tint transform into
+/// DeviceCMYK. Spots whose tint transform is a PostScript or stitching function, and
+/// spots used only by images or shadings, are left as they are. Returns the names of
+/// the converted separations.
+#[tauri::command]
+pub fn flatten_separations(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let mut doc = load_document(path, None)?;
+
+    let mut converted = BTreeSet::new();
+    for (page_num, page_id) in doc.get_pages() {
+        let resources =
+            get_inherited_attribute(&doc, page_id, b"Resources").and_then(|r| r.as_dict().ok());
+        let spots = resource_spots(&doc, resources);
+        if spots.is_empty() {
+            continue;
+        }
+        let data = doc
+            .get_page_content(page_id)
+            .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+        let mut content = Content::decode(&data)
+            .map_err(|e| format!("Failed to parse content of page {}: {}", page_num, e))?;
+        let used = convert_spot_operations(&mut content, &spots);
+        if used.is_empty() {
+            continue;
+        }
+        let encoded = content
+            .encode()
+            .map_err(|e| format!("Failed to encode content of page {}: {}", page_num, e))?;
+        doc.change_page_content(page_id, encoded)
+            .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))?;
+        converted.extend(used);
+    }
+
+    let form_ids: Vec<ObjectId> = doc
+        .objects
+        .iter()
+        .filter(|(_, o)| {
+            o.as_stream()
+                .and_then(|s| s.dict.get(b"Subtype"))
+                .and_then(|s| s.as_name())
+                .is_ok_and(|s| s == b"Form")
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    for id in form_ids {
+        let Ok(form) = doc.get_object(id).and_then(|o| o.as_stream()) else {
+            continue;
+        };
+        let resources = form
+            .dict
+            .get_deref(b"Resources", &doc)
+            .and_then(|r| r.as_dict())
+            .ok();
+        let spots = resource_spots(&doc, resources);
+        let data = form
+            .decompressed_content()
+            .unwrap_or_else(|_| form.content.clone());
+        let Ok(mut content) = Content::decode(&data) else {
+            continue;
+        };
+        let used = convert_spot_operations(&mut content, &spots);
+        if used.is_empty() {
+            continue;
+        }
+        let encoded = content
+            .encode()
+            .map_err(|e| format!("Failed to encode form XObject {:?}: {}", id, e))?;
+        let mut replacement = Stream::new(form.dict.clone(), encoded);
+        replacement.dict.remove(b"Filter");
+        replacement.dict.remove(b"DecodeParms");
+        doc.objects.insert(id, Object::Stream(replacement));
+        converted.extend(used);
+    }
+
+    // The content no longer selects the converted spots, so drop them from the
+    // resources; prune_objects then removes their now-unused tint transforms
+    let shared_space_dicts: HashSet<ObjectId> = doc
+        .objects
+        .values()
+        .filter_map(|o| match o {
+            Object::Dictionary(dict) => Some(dict),
+            Object::Stream(stream) => Some(&stream.dict),
+            _ => None,
+        })
+        .flat_map(|dict| {
+            dict.iter()
+                .filter_map(|(_, v)| v.as_dict().ok())
+                .chain(std::iter::once(dict))
+                .filter_map(|d| d.get(b"ColorSpace").and_then(|c| c.as_reference()).ok())
+        })
+        .filter(|id| matches!(doc.get_object(*id), Ok(Object::Dictionary(_))))
+        .collect();
+    let mut edits = Vec::new();
+    for (id, object) in &doc.objects {
+        let mut edited = object.clone();
+        let changed = match &mut edited {
+            Object::Dictionary(dict) if shared_space_dicts.contains(id) => {
+                remove_converted_spaces(&doc, dict, &converted)
+            }
+            Object::Dictionary(dict) => strip_spot_entries(&doc, dict, &converted),
+            Object::Stream(stream) => strip_spot_entries(&doc, &mut stream.dict, &converted),
+            _ => false,
+        };
+        if changed {
+            edits.push((*id, edited));
+        }
+    }
+    doc.objects.extend(edits);
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    doc.prune_objects();
+    doc.compress();
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(converted.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    /// Fill a rectangle on page 1 with a 50% tint of a "SpotRed" Separation whose
+    /// alternate is CMYK 0 1 1 0.
+    fn add_spot_fill(path: &std::path::Path) {
+        let mut doc = Document::load(path).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let tint = doc.add_object(Stream::new(
+            dictionary! {
+                "FunctionType" => 2,
+                "Domain" => vec![0.into(), 1.into()],
+                "C0" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+                "C1" => vec![0.into(), 1.into(), 1.into(), 0.into()],
+                "N" => 1,
+            },
+            vec![],
+        ));
+        let spot = Object::Array(vec![
+            Object::Name(b"Separation".to_vec()),
+            Object::Name(b"SpotRed".to_vec()),
+            Object::Name(b"DeviceCMYK".to_vec()),
+            Object::Reference(tint),
+        ]);
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set(
+            "Resources",
+            dictionary! { "ColorSpace" => dictionary! { "CS0" => spot } },
+        );
+        doc.change_page_content(page_id, b"/CS0 cs 0.5 sc 0 0 100 100 re f".to_vec())
+            .unwrap();
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_evaluate_tint_function_rejects_malformed_sampled_function() {
+        let sampled = |range: Vec<Object>, decode: Option<Vec<Object>>| {
+            let mut dict = dictionary! {
+                "FunctionType" => 0,
+                "Domain" => vec![0.into(), 1.into()],
+                "Size" => vec![2.into()],
+                "BitsPerSample" => 8,
+                "Range" => range,
+            };
+            if let Some(decode) = decode {
+                dict.set("Decode", decode);
+            }
+            Object::Stream(Stream::new(dict, vec![0, 0, 255, 255]))
+        };
+        let two_outputs = || vec![0.into(), 1.into(), 0.into(), 1.into()];
+
+        let valid = sampled(two_outputs(), None);
+        assert_eq!(evaluate_tint_function(&valid, 1.0), Some(vec![1.0, 1.0]));
+
+        let short_decode = sampled(two_outputs(), Some(vec![0.into(), 1.into()]));
+        assert_eq!(evaluate_tint_function(&short_decode, 0.5), None);
+
+        let odd_range = sampled(vec![0.into(), 1.into(), 0.into()], None);
+        assert_eq!(evaluate_tint_function(&odd_range, 0.5), None);
+    }
+
+    #[test]
+    fn test_list_separations_finds_spot_color() {
+        let (test_dir, output_dir) = setup_unique_paths("list_separations");
+        let input = test_dir.join("spot.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Spot").unwrap();
+        add_spot_fill(&input);
+
+        let inks = list_separations(input.to_str().unwrap()).unwrap();
+        assert_eq!(inks, vec!["SpotRed".to_string()]);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_list_separations_process_only() {
+        let (test_dir, output_dir) = setup_unique_paths("list_separations_none");
+        let input = test_dir.join("process.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Process").unwrap();
+
+        assert!(list_separations(input.to_str().unwrap())
+            .unwrap()
+            .is_empty());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_flatten_separations_converts_spot_fill() {
+        let (test_dir, output_dir) = setup_unique_paths("flatten_separations");
+        let input = test_dir.join("spot.pdf");
+        let output = output_dir.join("process.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Spot").unwrap();
+        add_spot_fill(&input);
+
+        let converted =
+            flatten_separations(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert_eq!(converted, Ok(vec!["SpotRed".to_string()]));
+
+        assert!(list_separations(output.to_str().unwrap())
+            .unwrap()
+            .is_empty());
+        let doc = Document::load(&output).unwrap();
+        let mentions_separation = doc.objects.values().any(|o| {
+            o.as_array()
+                .is_ok_and(|a| a.first().and_then(|f| f.as_name().ok()) == Some(b"Separation"))
+        });
+        assert!(!mentions_separation);
+
+        let page_id = doc.get_pages()[&1];
+        let content = Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+        let colors: Vec<(String, Vec<f32>)> = content
+            .operations
+            .iter()
+            .filter(|o| o.operator.starts_with("sc") || o.operator == "cs")
+            .map(|o| {
+                (
+                    o.operator.clone(),
+                    o.operands
+                        .iter()
+                        .filter_map(|v| v.as_float().ok())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(colors[0].0, "cs");
+        // Half tint of the 0 1 1 0 alternate
+        assert_eq!(
+            colors.last().unwrap(),
+            &("sc".to_string(), vec![0.0, 0.5, 0.5, 0.0])
+        );
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::pdf::utils::rebuild_clean;
 use lopdf::{dictionary, Document, Object};
 use std::fs;
 use std::path::Path;
@@ -29,6 +30,7 @@ fn color_array(color: Option<[f32; 3]>) -> Object {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_signature_visual(
     path: &str,
     page: u32,
@@ -37,6 +39,7 @@ pub fn add_signature_visual(
     color: Option<[f32; 3]>,
     width: Option<f32>,
     output_path: &str,
+    clean: Option<bool>,
 ) -> Result<(), String> {
     if page == 0 {
         return Err("Page number must be 1-based.".to_string());
@@ -156,6 +159,10 @@ pub fn add_signature_visual(
         }
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path)
         .map_err(|e| format!("Failed to save signed PDF to '{}': {}", output_path, e))?;
 
@@ -164,6 +171,7 @@ pub fn add_signature_visual(
 
 /// Validate inputs for cryptographic signing without yet performing CMS signing.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn sign_pdf_pfx(
     path: &str,
     page: u32,
@@ -174,6 +182,7 @@ pub fn sign_pdf_pfx(
     location: Option<String>,
     contact: Option<String>,
     output_path: &str,
+    clean: Option<bool>,
 ) -> Result<(), String> {
     if page == 0 {
         return Err("Page number must be 1-based.".to_string());
@@ -326,6 +335,11 @@ pub fn sign_pdf_pfx(
         }
     }
 
+    // Rebuild before the byte ranges are laid out, so the signature covers the final file
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     // Save to buffer with placeholders
     let mut buffer = Vec::new();
     doc.save_to(&mut buffer)
@@ -509,6 +523,7 @@ mod tests {
             Some([0.2, 0.8, 0.4]),
             Some(2.5),
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_ok(), "add_signature_visual failed: {:?}", result.err());
     }
@@ -531,6 +546,7 @@ mod tests {
             None,
             None,
             output.to_str().unwrap(),
+            None,
         );
         assert!(result.is_err());
     }
@@ -1,24 +1,28 @@
-use crate::pdf::utils::manual_deep_copy;
-use lopdf::{dictionary, Document, Object};
+use crate::pdf::cancel::CancellationToken;
+use crate::pdf::extractor_text::extract_page_text;
+use crate::pdf::merger::build_from_page_sequence;
+use crate::pdf::outline::read_outline_entries;
+use crate::pdf::utils::{get_inherited_attribute, load_document, manual_deep_copy, rebuild_clean};
+use lopdf::{dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::Path;
+use tauri::State;
 
 // --- split_pdf Function using Manual Deep Copy ---
 #[tauri::command]
-pub fn split_pdf(path: &str, pages: Vec<u32>, output_path: &str) -> Result<(), String> {
+pub fn split_pdf(
+    path: &str,
+    pages: Vec<u32>,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
     // --- Input Validation & Dir Creation ---
     if pages.is_empty() {
         return Err("The list of pages to extract cannot be empty.".to_string());
     }
-    let input_path = Path::new(path);
-    if !input_path.exists() {
-        return Err(format!("Input file not found: {}", path));
-    }
-    if !input_path.is_file() {
-        return Err(format!("Input path is not a file: {}", path));
-    }
     // --- Load Original Document ---
-    let doc = Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
+    let doc = load_document(path, None)?;
 
     // --- Prepare New Document ---
     let mut new_doc = Document::with_version(doc.version.clone());
@@ -124,8 +128,15 @@ pub fn split_pdf(path: &str, pages: Vec<u32>, output_path: &str) -> Result<(), S
         .trailer
         .set("Root", Object::Reference(new_catalog_id));
 
-    // --- Compress and Save ---
+    // --- Drop Orphans, Compress and Save ---
+    // The deep copy followed each page's old /Parent into the source page tree, pulling
+    // in pages that were not selected; nothing references them once Parent is rewritten
+    new_doc.prune_objects();
     new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
     new_doc
         .save(output_path)
         .map_err(|e| format!("Failed to save split PDF to '{}': {}", output_path, e))?;
@@ -133,12 +144,569 @@ pub fn split_pdf(path: &str, pages: Vec<u32>, output_path: &str) -> Result<(), S
     Ok(())
 }
 
+// --- split_by_size_respecting_bookmarks ---
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct SplitPart {
+    pub path: String,
+    pub start_page: u32,
+    pub end_page: u32,
+}
+
+/// Start pages of the top-level bookmarks, sorted and deduplicated.
+fn top_level_bookmark_pages(doc: &Document) -> Vec<u32> {
+    let mut pages: Vec<u32> = read_outline_entries(doc)
+        .iter()
+        .filter_map(|entry| entry.page)
+        .collect();
+    pages.sort_unstable();
+    pages.dedup();
+    pages
+}
+
+/// Rough serialized size of an object, not counting the objects it references.
+fn estimated_object_size(object: &Object) -> u64 {
+    match object {
+        Object::Stream(stream) => {
+            stream.content.len() as u64
+                + estimated_object_size(&Object::Dictionary(stream.dict.clone()))
+        }
+        Object::Dictionary(dict) => {
+            dict.iter()
+                .map(|(key, value)| key.len() as u64 + 2 + estimated_object_size(value))
+                .sum::<u64>()
+                + 4
+        }
+        Object::Array(items) => {
+            items
+                .iter()
+                .map(|i| estimated_object_size(i) + 1)
+                .sum::<u64>()
+                + 2
+        }
+        Object::String(bytes, _) | Object::Name(bytes) => bytes.len() as u64 + 2,
+        _ => 10,
+    }
+}
+
+fn push_references(object: &Object, queue: &mut Vec<ObjectId>) {
+    match object {
+        Object::Reference(id) => queue.push(*id),
+        Object::Array(items) => items.iter().for_each(|i| push_references(i, queue)),
+        Object::Dictionary(dict) => dict
+            .iter()
+            .filter(|(key, _)| key.as_slice() != b"Parent")
+            .for_each(|(_, value)| push_references(value, queue)),
+        Object::Stream(stream) => push_references(&Object::Dictionary(stream.dict.clone()), queue),
+        _ => {}
+    }
+}
+
+/// Every object a page needs (content, resources, annotations), with its estimated size.
+fn page_object_sizes(doc: &Document, page_id: ObjectId) -> Vec<(ObjectId, u64)> {
+    let mut queue = vec![page_id];
+    if let Ok(page) = doc.get_dictionary(page_id) {
+        if !page.has(b"Resources") {
+            if let Some(resources) = get_inherited_attribute(doc, page_id, b"Resources") {
+                push_references(resources, &mut queue);
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut sizes = Vec::new();
+    while let Some(id) = queue.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if let Ok(object) = doc.get_object(id) {
+            sizes.push((id, estimated_object_size(object)));
+            push_references(object, &mut queue);
+        }
+    }
+    sizes
+}
+
+/// A part being filled while planning a size-capped split.
+#[derive(Default)]
+struct PartPlan {
+    range: Option<(u32, u32)>,
+    objects: HashSet<ObjectId>,
+    size: u64,
+}
+
+impl PartPlan {
+    /// Estimated bytes `pages` would add, counting objects already in the part once.
+    fn added_size(
+        &self,
+        page_sizes: &BTreeMap<u32, Vec<(ObjectId, u64)>>,
+        pages: (u32, u32),
+    ) -> u64 {
+        let mut counted = HashSet::new();
+        (pages.0..=pages.1)
+            .flat_map(|p| page_sizes[&p].iter())
+            .filter(|(id, _)| !self.objects.contains(id) && counted.insert(*id))
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    fn fits(
+        &self,
+        page_sizes: &BTreeMap<u32, Vec<(ObjectId, u64)>>,
+        pages: (u32, u32),
+        max_bytes: u64,
+    ) -> bool {
+        self.size + self.added_size(page_sizes, pages) <= max_bytes
+    }
+
+    fn take(&mut self, page_sizes: &BTreeMap<u32, Vec<(ObjectId, u64)>>, pages: (u32, u32)) {
+        self.size += self.added_size(page_sizes, pages);
+        for p in pages.0..=pages.1 {
+            self.objects
+                .extend(page_sizes[&p].iter().map(|(id, _)| *id));
+        }
+        self.range = Some(self.range.map_or(pages, |(start, _)| (start, pages.1)));
+    }
+}
+
+/// Group chapters into page ranges under `max_bytes`, keeping chapters whole where
+/// they fit and breaking an oversized chapter between pages in a part of its own.
+fn plan_parts(
+    chapters: &[(u32, u32)],
+    page_sizes: &BTreeMap<u32, Vec<(ObjectId, u64)>>,
+    max_bytes: u64,
+) -> Vec<(u32, u32)> {
+    let mut ranges = Vec::new();
+    let mut part = PartPlan::default();
+    for &chapter in chapters {
+        if part.range.is_some() && !part.fits(page_sizes, chapter, max_bytes) {
+            ranges.extend(std::mem::take(&mut part).range);
+        }
+        if part.range.is_some() || part.fits(page_sizes, chapter, max_bytes) {
+            part.take(page_sizes, chapter);
+            continue;
+        }
+        for page in chapter.0..=chapter.1 {
+            if part.range.is_some() && !part.fits(page_sizes, (page, page), max_bytes) {
+                ranges.extend(std::mem::take(&mut part).range);
+            }
+            part.take(page_sizes, (page, page));
+        }
+    }
+    ranges.extend(part.range);
+    ranges
+}
+
+/// Write each inclusive page range of `doc` to `output_dir` as `<name>_part<N>.pdf`,
+/// where `<name>` is the input file's stem.
+fn write_page_ranges(
+    doc: &Document,
+    input_path: &Path,
+    ranges: &[(u32, u32)],
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<SplitPart>, String> {
+    let output_dir_path = Path::new(output_dir);
+    if !output_dir_path.exists() {
+        fs::create_dir_all(output_dir_path).map_err(|e| {
+            format!(
+                "Failed to create output directory '{}': {}",
+                output_dir_path.display(),
+                e
+            )
+        })?;
+    }
+
+    let stem = input_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "document".to_string());
+    let pages = doc.get_pages();
+    let docs = std::slice::from_ref(doc);
+    let mut parts = Vec::with_capacity(ranges.len());
+    for (index, &(start, end)) in ranges.iter().enumerate() {
+        let part_path = output_dir_path.join(format!("{}_part{}.pdf", stem, index + 1));
+        let part_path = part_path.to_string_lossy().to_string();
+        let sequence: Vec<(usize, ObjectId)> = (start..=end).map(|p| (0, pages[&p])).collect();
+        build_from_page_sequence(docs, &sequence, &part_path, None, clean)?;
+        parts.push(SplitPart {
+            path: part_path,
+            start_page: start,
+            end_page: end,
+        });
+    }
+
+    Ok(parts)
+}
+
+/// Split a PDF into parts of at most `max_bytes` (estimated from the uncompressed
+/// objects each part needs, so the written files usually come out smaller). Parts break
+/// between top-level bookmarks wherever the chapters fit; a chapter too big for one
+/// part on its own starts a fresh part and is broken between pages.
+#[tauri::command]
+pub fn split_by_size_respecting_bookmarks(
+    path: &str,
+    max_bytes: u64,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<SplitPart>, String> {
+    if max_bytes == 0 {
+        return Err("The maximum part size must be greater than 0 bytes.".to_string());
+    }
+    let input_path = Path::new(path);
+    let doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let page_count = pages.len() as u32;
+    if page_count == 0 {
+        return Err(format!("Document '{}' has no pages to split.", path));
+    }
+
+    // Chapters run from one bookmark's page to the page before the next; any
+    // pages ahead of the first bookmark form their own leading chapter.
+    let mut starts = top_level_bookmark_pages(&doc);
+    if starts.first() != Some(&1) {
+        starts.insert(0, 1);
+    }
+    let chapters: Vec<(u32, u32)> = starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| (start, starts.get(i + 1).map_or(page_count, |next| next - 1)))
+        .collect();
+
+    let page_sizes: BTreeMap<u32, Vec<(ObjectId, u64)>> = pages
+        .iter()
+        .map(|(&num, &id)| (num, page_object_sizes(&doc, id)))
+        .collect();
+    let ranges = plan_parts(&chapters, &page_sizes, max_bytes);
+
+    write_page_ranges(&doc, input_path, &ranges, output_dir, clean)
+}
+
+// --- split_on_text ---
+
+/// Split a concatenated batch back into its documents: every page whose text contains
+/// `marker` starts a new output file. Pages before the first marker (if any) form
+/// their own leading file. Returns the created paths in page order.
+#[tauri::command]
+pub fn split_on_text(
+    path: &str,
+    marker: &str,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if marker.trim().is_empty() {
+        return Err("The marker text cannot be empty.".to_string());
+    }
+    let input_path = Path::new(path);
+    let doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let mut starts = Vec::new();
+    for (&page_num, &page_id) in &pages {
+        let text = extract_page_text(&doc, page_id)
+            .map_err(|e| format!("Failed to extract text of page {}: {}", page_num, e))?;
+        if text.contains(marker) {
+            starts.push(page_num);
+        }
+    }
+    if starts.is_empty() {
+        return Err(format!("Marker '{}' was not found in '{}'.", marker, path));
+    }
+    if starts[0] != 1 {
+        starts.insert(0, 1);
+    }
+
+    let page_count = pages.len() as u32;
+    let ranges: Vec<(u32, u32)> = starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| (start, starts.get(i + 1).map_or(page_count, |next| next - 1)))
+        .collect();
+
+    let parts = write_page_ranges(&doc, input_path, &ranges, output_dir, clean)?;
+    Ok(parts.into_iter().map(|part| part.path).collect())
+}
+
+// --- split_with_overlap ---
+
+/// Inclusive page ranges of `pages_per_chunk` pages where each range starts `overlap`
+/// pages before the previous one ended. The last range may be shorter.
+fn overlapping_ranges(page_count: u32, pages_per_chunk: u32, overlap: u32) -> Vec<(u32, u32)> {
+    let mut ranges = Vec::new();
+    let mut start = 1;
+    loop {
+        let end = (start + pages_per_chunk - 1).min(page_count);
+        ranges.push((start, end));
+        if end == page_count {
+            return ranges;
+        }
+        start = end + 1 - overlap;
+    }
+}
+
+/// Split a PDF into chunks of `pages_per_chunk` pages where consecutive chunks share
+/// `overlap` pages for context (e.g. 1-4, 4-7, 7-10). Returns the created paths.
+#[tauri::command]
+pub fn split_with_overlap(
+    path: &str,
+    pages_per_chunk: u32,
+    overlap: u32,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if pages_per_chunk == 0 {
+        return Err("Pages per chunk must be at least 1.".to_string());
+    }
+    if overlap >= pages_per_chunk {
+        return Err(format!(
+            "Overlap ({}) must be smaller than pages per chunk ({}).",
+            overlap, pages_per_chunk
+        ));
+    }
+    let input_path = Path::new(path);
+    let doc = load_document(path, None)?;
+
+    let page_count = doc.get_pages().len() as u32;
+    if page_count == 0 {
+        return Err(format!("Document '{}' has no pages to split.", path));
+    }
+    let ranges = overlapping_ranges(page_count, pages_per_chunk, overlap);
+
+    let parts = write_page_ranges(&doc, input_path, &ranges, output_dir, clean)?;
+    Ok(parts.into_iter().map(|part| part.path).collect())
+}
+
+// --- split_into_chunks ---
+
+/// Split a PDF into sequential files of `pages_per_chunk` pages each (`chunk_001.pdf`,
+/// `chunk_002.pdf`, ...); the last file holds whatever pages remain. Returns the
+/// created paths. `cancel_operation` stops the split between files with "Operation
+/// cancelled" and removes the chunks already written. The command runs off the main
+/// thread so the cancel request is handled while it works, and clears any earlier
+/// cancel before starting.
+#[tauri::command(async)]
+pub fn split_into_chunks(
+    path: &str,
+    pages_per_chunk: u32,
+    output_dir: &str,
+    cancel: State<'_, CancellationToken>,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    cancel.reset();
+    split_into_chunks_cancellable(path, pages_per_chunk, output_dir, &cancel, clean)
+}
+
+/// `split_into_chunks` with an explicit cancellation token, for callers outside a
+/// Tauri command.
+pub(crate) fn split_into_chunks_cancellable(
+    path: &str,
+    pages_per_chunk: u32,
+    output_dir: &str,
+    cancel: &CancellationToken,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if pages_per_chunk == 0 {
+        return Err("Pages per chunk must be at least 1.".to_string());
+    }
+    let doc = load_document(path, None)?;
+
+    let page_count = doc.get_pages().len() as u32;
+    if page_count == 0 {
+        return Err(format!("Document '{}' has no pages to split.", path));
+    }
+    let ranges = overlapping_ranges(page_count, pages_per_chunk, 0);
+    let width = ranges.len().to_string().len().max(3);
+    let entries: Vec<(String, u32, u32)> = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            (
+                format!("chunk_{:0width$}.pdf", index + 1, width = width),
+                start,
+                end,
+            )
+        })
+        .collect();
+
+    write_named_page_ranges(&doc, &entries, output_dir, Some(cancel), clean)
+}
+
+// --- split_by_plan ---
+
+/// Turn a user-supplied name into a safe file name in the output directory: path
+/// separators, characters Windows rejects and control characters become `_`, leading
+/// and trailing dots and spaces are dropped, and `.pdf` is appended when missing.
+fn sanitize_file_name(name: &str) -> Option<String> {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || "/\\:*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+    if cleaned.is_empty() {
+        return None;
+    }
+    if cleaned.to_lowercase().ends_with(".pdf") {
+        Some(cleaned.to_string())
+    } else {
+        Some(format!("{}.pdf", cleaned))
+    }
+}
+
+/// Carve a document into individually named files following a plan of
+/// `(file name, start page, end page)` entries (inclusive, 1-based), e.g. exported
+/// from a spreadsheet. Every entry is validated before anything is written. Returns
+/// the created paths in plan order.
+#[tauri::command]
+pub fn split_by_plan(
+    path: &str,
+    plan: Vec<(String, u32, u32)>,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if plan.is_empty() {
+        return Err("The split plan cannot be empty.".to_string());
+    }
+    let doc = load_document(path, None)?;
+    let page_count = doc.get_pages().len() as u32;
+
+    let mut names = HashSet::new();
+    let mut entries = Vec::with_capacity(plan.len());
+    for (index, (name, start, end)) in plan.iter().enumerate() {
+        let file_name = sanitize_file_name(name)
+            .ok_or_else(|| format!("Plan entry {} has an empty file name.", index + 1))?;
+        if !names.insert(file_name.to_lowercase()) {
+            return Err(format!(
+                "Plan entry {} repeats the file name '{}'.",
+                index + 1,
+                file_name
+            ));
+        }
+        if *start == 0 || end < start || *end > page_count {
+            return Err(format!(
+                "Plan entry {} ('{}') has invalid range {}-{}; pages must be between 1 and {} with start <= end.",
+                index + 1,
+                name,
+                start,
+                end,
+                page_count
+            ));
+        }
+        entries.push((file_name, *start, *end));
+    }
+
+    write_named_page_ranges(&doc, &entries, output_dir, None, clean)
+}
+
+/// Write each `(file name, start, end)` inclusive page range of `doc` into
+/// `output_dir`, returning the created paths in order.
+fn write_named_page_ranges(
+    doc: &Document,
+    entries: &[(String, u32, u32)],
+    output_dir: &str,
+    cancel: Option<&CancellationToken>,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let output_dir_path = Path::new(output_dir);
+    if !output_dir_path.exists() {
+        fs::create_dir_all(output_dir_path).map_err(|e| {
+            format!(
+                "Failed to create output directory '{}': {}",
+                output_dir_path.display(),
+                e
+            )
+        })?;
+    }
+
+    let pages = doc.get_pages();
+    let docs = std::slice::from_ref(doc);
+    let mut created = Vec::with_capacity(entries.len());
+    for (file_name, start, end) in entries {
+        if let Some(Err(e)) = cancel.map(CancellationToken::check) {
+            // Do not leave a partial set of files behind
+            for part_path in &created {
+                fs::remove_file(part_path).ok();
+            }
+            return Err(e);
+        }
+        let part_path = output_dir_path
+            .join(file_name)
+            .to_string_lossy()
+            .to_string();
+        let sequence: Vec<(usize, ObjectId)> = (*start..=*end).map(|p| (0, pages[&p])).collect();
+        build_from_page_sequence(docs, &sequence, &part_path, None, clean)?;
+        created.push(part_path);
+    }
+
+    Ok(created)
+}
+
+// --- split_into_n ---
+
+/// Split `page_count` pages into `n` consecutive inclusive ranges whose sizes differ by
+/// at most one page, with the larger ranges first.
+fn even_ranges(page_count: u32, n: u32) -> Vec<(u32, u32)> {
+    let (base, extra) = (page_count / n, page_count % n);
+    let mut start = 1;
+    (0..n)
+        .map(|index| {
+            let size = base + u32::from(index < extra);
+            let range = (start, start + size - 1);
+            start += size;
+            range
+        })
+        .collect()
+}
+
+/// Split a PDF into exactly `n` files (`part_01.pdf`, `part_02.pdf`, ...) with the
+/// pages spread as evenly as possible; earlier files take the extra pages when the
+/// page count does not divide evenly. Returns the created paths.
+#[tauri::command]
+pub fn split_into_n(
+    path: &str,
+    n: u32,
+    output_dir: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if n == 0 {
+        return Err("Number of output files must be at least 1.".to_string());
+    }
+    let doc = load_document(path, None)?;
+
+    let page_count = doc.get_pages().len() as u32;
+    if n > page_count {
+        return Err(format!(
+            "Cannot split '{}' into {} files: it only has {} pages.",
+            path, n, page_count
+        ));
+    }
+    let width = n.to_string().len().max(2);
+    let entries: Vec<(String, u32, u32)> = even_ranges(page_count, n)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            (
+                format!("part_{:0width$}.pdf", index + 1, width = width),
+                start,
+                end,
+            )
+        })
+        .collect();
+
+    write_named_page_ranges(&doc, &entries, output_dir, None, clean)
+}
+
 // --- Tests ---
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
-    use lopdf::Document;
+    use lopdf::{dictionary, Document, Object};
     use std::fs;
     use std::path::PathBuf;
 
@@ -189,6 +757,7 @@ mod tests {
             env.input_path_str(),
             pages_to_extract.clone(),
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_ok(), "split_pdf failed: {:?}", result.err());
@@ -198,6 +767,29 @@ mod tests {
         assert_eq!(output_doc.get_pages().len(), pages_to_extract.len());
     }
 
+    #[test]
+    fn test_split_pdf_drops_unselected_pages() {
+        let env = TestEnvironment::new("split_prune");
+        let output_path = env.output_path("split_2.pdf");
+
+        split_pdf(
+            env.input_path_str(),
+            vec![2],
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let source_doc = Document::load(env.input_path_str()).unwrap();
+        let output_doc = Document::load(&output_path).unwrap();
+        assert_eq!(output_doc.get_pages().len(), 1);
+        assert!(output_doc.objects.len() < source_doc.objects.len());
+        assert_eq!(
+            output_doc.extract_text(&[1]).unwrap().trim(),
+            "Sample-Page 2"
+        );
+    }
+
     #[test]
     fn test_split_pdf_invalid_page() {
         let env = TestEnvironment::new("split_invalid_page");
@@ -208,6 +800,7 @@ mod tests {
             env.input_path_str(),
             pages_to_extract,
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err());
@@ -224,6 +817,7 @@ mod tests {
             env.input_path_str(),
             pages_to_extract,
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err());
@@ -240,6 +834,7 @@ mod tests {
             bad_input_path.to_str().unwrap(),
             vec![1],
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err());
@@ -257,6 +852,7 @@ mod tests {
             env.input_path_str(),
             pages_to_extract,
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err());
@@ -275,9 +871,297 @@ mod tests {
             not_pdf_path.to_str().unwrap(),
             vec![1],
             output_path.to_str().unwrap(),
+            None,
         );
 
         assert!(result.is_err());
         teardown_unique_paths(&env.test_dir, &env.output_dir);
     }
+
+    /// Six pages whose content is padded to roughly `padding` bytes each, with
+    /// top-level bookmarks on the given pages.
+    fn create_book(env: &TestEnvironment, bookmarks: &[u32], padding: usize) -> PathBuf {
+        use crate::pdf::outline::{set_pdf_outline, OutlineItem};
+        use lopdf::Stream;
+
+        let plain = env.test_dir.join("plain.pdf");
+        let book = env.test_dir.join("book.pdf");
+        create_minimal_pdf(plain.to_str().unwrap(), 6, "Book").unwrap();
+        let mut doc = Document::load(&plain).unwrap();
+        for (num, page_id) in doc.get_pages() {
+            let content = format!(
+                "BT /F1 12 Tf 100 700 Td (Page {}) Tj ET\n%{}",
+                num,
+                "x".repeat(padding)
+            );
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+            doc.get_dictionary_mut(page_id)
+                .unwrap()
+                .set("Contents", Object::Reference(content_id));
+        }
+        doc.prune_objects();
+        doc.save(&plain).unwrap();
+
+        let items = bookmarks
+            .iter()
+            .map(|&page| OutlineItem {
+                title: format!("Chapter at {}", page),
+                page: Some(page),
+                children: vec![],
+            })
+            .collect();
+        set_pdf_outline(plain.to_str().unwrap(), items, book.to_str().unwrap(), None).unwrap();
+        book
+    }
+
+    #[test]
+    fn test_split_by_size_breaks_at_bookmarks() {
+        let env = TestEnvironment::new("split_size_bookmarks");
+        let book = create_book(&env, &[1, 3, 5], 5000);
+        let out_dir = env.output_path("parts");
+
+        // Room for three pages, but each chapter is two: parts follow the chapters
+        let parts = split_by_size_respecting_bookmarks(
+            book.to_str().unwrap(),
+            16_000,
+            out_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let ranges: Vec<(u32, u32)> = parts.iter().map(|p| (p.start_page, p.end_page)).collect();
+        assert_eq!(ranges, vec![(1, 2), (3, 4), (5, 6)]);
+        for part in &parts {
+            assert_eq!(Document::load(&part.path).unwrap().get_pages().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_split_by_size_breaks_oversized_chapter() {
+        let env = TestEnvironment::new("split_size_oversized");
+        let book = create_book(&env, &[1, 3], 5000);
+        let out_dir = env.output_path("parts");
+
+        // Chapter two (pages 3-6) cannot fit one part, so it breaks mid-chapter
+        let parts = split_by_size_respecting_bookmarks(
+            book.to_str().unwrap(),
+            16_000,
+            out_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        let ranges: Vec<(u32, u32)> = parts.iter().map(|p| (p.start_page, p.end_page)).collect();
+        assert_eq!(ranges, vec![(1, 2), (3, 5), (6, 6)]);
+        assert!(parts[0].path.ends_with("book_part1.pdf"));
+    }
+
+    #[test]
+    fn test_split_on_text_starts_file_at_each_marker() {
+        let env = TestEnvironment::new("split_on_text");
+        let batch = env.test_dir.join("batch.pdf");
+        create_minimal_pdf(batch.to_str().unwrap(), 6, "Batch").unwrap();
+        let mut doc = Document::load(&batch).unwrap();
+        for page in [1, 4] {
+            let page_id = doc.get_pages()[&page];
+            let content = format!("BT /F1 12 Tf 100 700 Td (START of document {}) Tj ET", page);
+            doc.change_page_content(page_id, content.into_bytes())
+                .unwrap();
+        }
+        doc.save(&batch).unwrap();
+        let out_dir = env.output_path("docs");
+
+        let paths = split_on_text(
+            batch.to_str().unwrap(),
+            "START",
+            out_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            assert_eq!(Document::load(path).unwrap().get_pages().len(), 3);
+        }
+        assert!(paths[0].ends_with("batch_part1.pdf"));
+    }
+
+    #[test]
+    fn test_split_with_overlap_shares_boundary_pages() {
+        let env = TestEnvironment::new("split_overlap");
+        let study = env.test_dir.join("study.pdf");
+        create_minimal_pdf(study.to_str().unwrap(), 10, "Study").unwrap();
+        let out_dir = env.output_path("chunks");
+
+        let paths = split_with_overlap(
+            study.to_str().unwrap(),
+            4,
+            1,
+            out_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let ranges: Vec<(String, String)> = paths
+            .iter()
+            .map(|path| {
+                let chunk = Document::load(path).unwrap();
+                let pages = chunk.get_pages();
+                let first = extract_page_text(&chunk, pages[&1]).unwrap();
+                let last = extract_page_text(&chunk, pages[&(pages.len() as u32)]).unwrap();
+                (first.trim().to_string(), last.trim().to_string())
+            })
+            .collect();
+        let expected = [(1, 4), (4, 7), (7, 10)];
+        assert_eq!(ranges.len(), expected.len());
+        for ((first, last), (start, end)) in ranges.iter().zip(expected) {
+            assert!(
+                first.ends_with(&format!("Study-Page {}", start)),
+                "{}",
+                first
+            );
+            assert!(last.ends_with(&format!("Study-Page {}", end)), "{}", last);
+        }
+
+        assert!(split_with_overlap(
+            study.to_str().unwrap(),
+            4,
+            4,
+            out_dir.to_str().unwrap(),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_split_by_plan_writes_named_files() {
+        let env = TestEnvironment::new("split_by_plan");
+        let scan = env.test_dir.join("scan.pdf");
+        create_minimal_pdf(scan.to_str().unwrap(), 6, "Scan").unwrap();
+        let out_dir = env.output_path("named");
+
+        let plan = vec![
+            ("Invoice 2024/001".to_string(), 1, 2),
+            ("contract.pdf".to_string(), 3, 6),
+        ];
+        let paths = split_by_plan(
+            scan.to_str().unwrap(),
+            plan,
+            out_dir.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("Invoice 2024_001.pdf"));
+        assert!(paths[1].ends_with("contract.pdf"));
+        assert_eq!(Document::load(&paths[0]).unwrap().get_pages().len(), 2);
+        assert_eq!(Document::load(&paths[1]).unwrap().get_pages().len(), 4);
+
+        let bad = vec![("late".to_string(), 5, 7)];
+        assert!(
+            split_by_plan(scan.to_str().unwrap(), bad, out_dir.to_str().unwrap(), None)
+                .unwrap_err()
+                .contains("invalid range 5-7")
+        );
+    }
+
+    #[test]
+    fn test_split_into_n_spreads_pages_evenly() {
+        let env = TestEnvironment::new("split_into_n");
+        let report = env.test_dir.join("report.pdf");
+        create_minimal_pdf(report.to_str().unwrap(), 10, "Report").unwrap();
+        let out_dir = env.output_path("parts");
+
+        let paths =
+            split_into_n(report.to_str().unwrap(), 3, out_dir.to_str().unwrap(), None).unwrap();
+        let names: Vec<String> = paths
+            .iter()
+            .map(|p| {
+                Path::new(p)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names, ["part_01.pdf", "part_02.pdf", "part_03.pdf"]);
+        let counts: Vec<usize> = paths
+            .iter()
+            .map(|p| Document::load(p).unwrap().get_pages().len())
+            .collect();
+        assert_eq!(counts, [4, 3, 3]);
+
+        assert!(split_into_n(
+            report.to_str().unwrap(),
+            11,
+            out_dir.to_str().unwrap(),
+            None
+        )
+        .is_err());
+        assert!(
+            split_into_n(report.to_str().unwrap(), 0, out_dir.to_str().unwrap(), None).is_err()
+        );
+    }
+
+    #[test]
+    fn test_split_into_chunks_last_chunk_is_shorter() {
+        let env = TestEnvironment::new("split_into_chunks");
+        let five = env.test_dir.join("five.pdf");
+        create_minimal_pdf(five.to_str().unwrap(), 5, "Chunk").unwrap();
+        let out_dir = env.output_path("chunks");
+
+        let token = CancellationToken::default();
+        let paths = split_into_chunks_cancellable(
+            five.to_str().unwrap(),
+            2,
+            out_dir.to_str().unwrap(),
+            &token,
+            None,
+        )
+        .unwrap();
+        let names: Vec<String> = paths
+            .iter()
+            .map(|p| {
+                Path::new(p)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(names, ["chunk_001.pdf", "chunk_002.pdf", "chunk_003.pdf"]);
+        let counts: Vec<usize> = paths
+            .iter()
+            .map(|p| Document::load(p).unwrap().get_pages().len())
+            .collect();
+        assert_eq!(counts, [2, 2, 1]);
+
+        assert!(split_into_chunks_cancellable(
+            five.to_str().unwrap(),
+            0,
+            out_dir.to_str().unwrap(),
+            &token,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_split_into_chunks_stops_when_cancelled() {
+        let env = TestEnvironment::new("split_into_chunks_cancelled");
+        let five = env.test_dir.join("five.pdf");
+        create_minimal_pdf(five.to_str().unwrap(), 5, "Chunk").unwrap();
+        let out_dir = env.output_path("chunks");
+
+        let token = CancellationToken::default();
+        token.cancel();
+        let result = split_into_chunks_cancellable(
+            five.to_str().unwrap(),
+            2,
+            out_dir.to_str().unwrap(),
+            &token,
+            None,
+        );
+        assert_eq!(result, Err("Operation cancelled".to_string()));
+        assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 0);
+    }
 }
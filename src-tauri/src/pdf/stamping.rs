@@ -0,0 +1,361 @@
+use crate::pdf::utils::{
+    add_page_resource, get_inherited_attribute, load_document, rebuild_clean, rect_from_object,
+};
+use chrono::format::{Item, StrftimeItems};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, ObjectId};
+use std::fs;
+use std::path::Path;
+
+const STAMP_FONT: &str = "StampHelv";
+const STAMP_SIZE: f64 = 10.0;
+/// Distance from the page edges, in points.
+const STAMP_MARGIN: f64 = 36.0;
+/// Helvetica digits are all 0.556 em wide; other glyphs average about half an em.
+const HELVETICA_DIGIT_WIDTH: f64 = 0.556;
+const HELVETICA_AVERAGE_CHAR_WIDTH: f64 = 0.5;
+const PAGE_NUMBER_POSITIONS: [&str; 3] = ["bottom-center", "bottom-right", "top-right"];
+/// Timestamps go bottom-left, clear of page numbers in the other footer positions.
+const TIMESTAMP_POSITION: &str = "bottom-left";
+
+/// Baseline origin for text `text_width` points wide at `position` on a page with the
+/// given MediaBox.
+fn stamp_origin(position: &str, media_box: [f64; 4], text_width: f64) -> (f64, f64) {
+    let [x0, y0, x1, y1] = media_box;
+    let (left, right) = (x0.min(x1), x0.max(x1));
+    let (bottom, top) = (y0.min(y1), y0.max(y1));
+    match position {
+        "bottom-left" => (left + STAMP_MARGIN, bottom + STAMP_MARGIN),
+        "bottom-center" => ((left + right - text_width) / 2.0, bottom + STAMP_MARGIN),
+        "bottom-right" => (right - STAMP_MARGIN - text_width, bottom + STAMP_MARGIN),
+        _ => (
+            right - STAMP_MARGIN - text_width,
+            top - STAMP_MARGIN - STAMP_SIZE,
+        ),
+    }
+}
+
+/// Font dictionary shared by every stamp added to `doc`.
+fn add_stamp_font(doc: &mut Document) -> ObjectId {
+    doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    })
+}
+
+/// Draw `text` at `position` on a page in the stamp font (`font_id`, added to the
+/// page's resources). The existing content is wrapped in `q`/`Q` first so the page's
+/// own graphics state cannot move or recolour the stamp.
+fn stamp_page_text(
+    doc: &mut Document,
+    page_num: u32,
+    page_id: ObjectId,
+    font_id: ObjectId,
+    text: &str,
+    position: &str,
+) -> Result<(), String> {
+    add_page_resource(doc, page_id, "Font", STAMP_FONT, Object::Reference(font_id))?;
+
+    let media_box = get_inherited_attribute(doc, page_id, b"MediaBox")
+        .and_then(rect_from_object)
+        .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+    let text_em: f64 = text
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                HELVETICA_DIGIT_WIDTH
+            } else {
+                HELVETICA_AVERAGE_CHAR_WIDTH
+            }
+        })
+        .sum();
+    let (x, y) = stamp_origin(position, media_box, text_em * STAMP_SIZE);
+    let encoded: Vec<u8> = text
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect();
+
+    let stamp = Content {
+        operations: vec![
+            Operation::new("q", vec![]),
+            Operation::new("BT", vec![]),
+            Operation::new(
+                "Tf",
+                vec![Object::Name(STAMP_FONT.into()), STAMP_SIZE.into()],
+            ),
+            Operation::new("Td", vec![x.into(), y.into()]),
+            Operation::new("Tj", vec![Object::string_literal(encoded)]),
+            Operation::new("ET", vec![]),
+            Operation::new("Q", vec![]),
+        ],
+    }
+    .encode()
+    .map_err(|e| format!("Failed to encode stamp for page {}: {}", page_num, e))?;
+
+    let original = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read content of page {}: {}", page_num, e))?;
+    let mut content = b"q\n".to_vec();
+    content.extend_from_slice(&original);
+    content.extend_from_slice(b"\nQ\n");
+    content.extend_from_slice(&stamp);
+    doc.change_page_content(page_id, content)
+        .map_err(|e| format!("Failed to update content of page {}: {}", page_num, e))
+}
+
+fn save_stamped(doc: &mut Document, output_path: &str, clean: Option<bool>) -> Result<(), String> {
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        *doc = rebuild_clean(doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save stamped PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+/// Stamp sequential numbers on every page, starting at `start_at` on the first page,
+/// at `position` ("bottom-center", "bottom-right" or "top-right") relative to the
+/// MediaBox. Each page gets the Helvetica font in its own resources, also when they are
+/// inherited from the page tree or shared with other pages.
+#[tauri::command]
+pub fn add_page_numbers(
+    path: &str,
+    start_at: u32,
+    position: String,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if !PAGE_NUMBER_POSITIONS.contains(&position.as_str()) {
+        return Err(format!(
+            "Invalid position '{}'. Must be one of {}.",
+            position,
+            PAGE_NUMBER_POSITIONS.join(", ")
+        ));
+    }
+
+    let mut doc = load_document(path, None)?;
+    let font_id = add_stamp_font(&mut doc);
+
+    for (index, (page_num, page_id)) in doc.get_pages().into_iter().enumerate() {
+        let number = (start_at as u64 + index as u64).to_string();
+        stamp_page_text(&mut doc, page_num, page_id, font_id, &number, &position)?;
+    }
+
+    save_stamped(&mut doc, output_path, clean)
+}
+
+/// Stamp the current local date and time, formatted with the strftime-style `format`
+/// (e.g. `"Last modified %Y-%m-%d %H:%M"`), in the bottom-left footer of every page.
+#[tauri::command]
+pub fn stamp_timestamp(
+    path: &str,
+    format: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid timestamp format '{}'.", format));
+    }
+    let timestamp = chrono::Local::now()
+        .format_with_items(items.into_iter())
+        .to_string();
+    if timestamp.trim().is_empty() {
+        return Err(format!(
+            "Timestamp format '{}' produces empty text.",
+            format
+        ));
+    }
+
+    let mut doc = load_document(path, None)?;
+    let font_id = add_stamp_font(&mut doc);
+
+    for (page_num, page_id) in doc.get_pages() {
+        stamp_page_text(
+            &mut doc,
+            page_num,
+            page_id,
+            font_id,
+            &timestamp,
+            TIMESTAMP_POSITION,
+        )?;
+    }
+
+    save_stamped(&mut doc, output_path, clean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    fn page_font_names(doc: &Document, page: u32) -> Vec<String> {
+        let page_id = doc.get_pages()[&page];
+        let resources = get_inherited_attribute(doc, page_id, b"Resources")
+            .and_then(|r| doc.dereference(r).ok())
+            .and_then(|(_, r)| r.as_dict().ok())
+            .unwrap();
+        resources
+            .get_deref(b"Font", doc)
+            .and_then(|f| f.as_dict())
+            .unwrap()
+            .iter()
+            .map(|(name, _)| String::from_utf8_lossy(name).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_add_page_numbers_counts_from_start() {
+        let (test_dir, output_dir) = setup_unique_paths("page_numbers");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("numbered.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Bates").unwrap();
+
+        let result = add_page_numbers(
+            input.to_str().unwrap(),
+            41,
+            "bottom-right".to_string(),
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "add_page_numbers failed: {:?}",
+            result.err()
+        );
+
+        let doc = Document::load(&output).unwrap();
+        for (page, number) in [(1, "41"), (2, "42"), (3, "43")] {
+            let text = doc.extract_text(&[page]).unwrap();
+            assert!(
+                text.contains(&format!("Bates-Page {}", page)) && text.contains(number),
+                "page {} text: {:?}",
+                page,
+                text
+            );
+            assert!(page_font_names(&doc, page).contains(&STAMP_FONT.to_string()));
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_add_page_numbers_with_inherited_resources() {
+        let (test_dir, output_dir) = setup_unique_paths("page_numbers_inherited");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("numbered.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "Inherited").unwrap();
+
+        // Move the shared resources up to the page tree root
+        let mut doc = Document::load(&input).unwrap();
+        let pages = doc.get_pages();
+        let resources = doc
+            .get_dictionary(pages[&1])
+            .unwrap()
+            .get(b"Resources")
+            .unwrap()
+            .clone();
+        for page_id in pages.values() {
+            doc.get_dictionary_mut(*page_id)
+                .unwrap()
+                .remove(b"Resources");
+        }
+        let root_pages = doc
+            .catalog()
+            .unwrap()
+            .get(b"Pages")
+            .and_then(|p| p.as_reference())
+            .unwrap();
+        doc.get_dictionary_mut(root_pages)
+            .unwrap()
+            .set("Resources", resources);
+        doc.save(&input).unwrap();
+
+        add_page_numbers(
+            input.to_str().unwrap(),
+            1,
+            "top-right".to_string(),
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let doc = Document::load(&output).unwrap();
+        for page in [1, 2] {
+            let fonts = page_font_names(&doc, page);
+            assert!(fonts.contains(&"F1".to_string()));
+            assert!(fonts.contains(&STAMP_FONT.to_string()));
+        }
+        assert!(doc.extract_text(&[2]).unwrap().contains("Inherited-Page 2"));
+
+        let invalid = add_page_numbers(
+            input.to_str().unwrap(),
+            1,
+            "middle".to_string(),
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(invalid.unwrap_err().contains("Invalid position 'middle'"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_stamp_timestamp_adds_footer_to_every_page() {
+        let (test_dir, output_dir) = setup_unique_paths("stamp_timestamp");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("stamped.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 3, "Controlled").unwrap();
+
+        let result = stamp_timestamp(
+            input.to_str().unwrap(),
+            "Printed %Y-%m-%d",
+            output.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "stamp_timestamp failed: {:?}", result.err());
+
+        let doc = Document::load(&output).unwrap();
+        assert_eq!(doc.get_pages().len(), 3);
+        let year = chrono::Local::now().format("%Y").to_string();
+        for page in 1..=3 {
+            let text = doc.extract_text(&[page]).unwrap();
+            assert!(
+                text.contains("Controlled-Page")
+                    && text.contains("Printed")
+                    && text.contains(&year),
+                "page {} text: {:?}",
+                page,
+                text
+            );
+        }
+
+        for format in ["%Q", "   "] {
+            let invalid = stamp_timestamp(
+                input.to_str().unwrap(),
+                format,
+                output.to_str().unwrap(),
+                None,
+            );
+            assert!(invalid.is_err(), "format {:?} was accepted", format);
+        }
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
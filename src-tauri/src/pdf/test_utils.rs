@@ -61,6 +61,93 @@ pub fn create_minimal_pdf(
     Ok(())
 }
 
+/// RC4 keystream applied to `data`; encrypting and decrypting are the same operation.
+#[cfg(test)]
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: Vec<u8> = (0..=255).collect();
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, j as usize);
+    }
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|byte| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(state[i as usize]);
+            state.swap(i as usize, j as usize);
+            byte ^ state[state[i as usize].wrapping_add(state[j as usize]) as usize]
+        })
+        .collect()
+}
+
+/// Create a minimal PDF (see `create_minimal_pdf`) with a `/Title` of
+/// "{text_prefix} Report", encrypted with the standard security handler (40-bit RC4,
+/// revision 2) under `user_password`.
+#[cfg(test)]
+pub fn create_encrypted_pdf(
+    file_path: &str,
+    num_pages: u32,
+    text_prefix: &str,
+    user_password: &str,
+) -> std::io::Result<()> {
+    use lopdf::encryption::{decrypt_object, get_encryption_key};
+
+    // Padding string from the standard security handler (PDF 32000-1, 7.6.3.3)
+    const PAD_BYTES: [u8; 32] = [
+        0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01,
+        0x08, 0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53,
+        0x69, 0x7A,
+    ];
+
+    create_minimal_pdf(file_path, num_pages, text_prefix)?;
+    let mut doc = Document::load(file_path).map_err(std::io::Error::other)?;
+    let info_id = doc.add_object(dictionary! {
+        "Title" => Object::string_literal(format!("{} Report", text_prefix)),
+    });
+    doc.trailer.set("Info", Object::Reference(info_id));
+    let file_id = Object::string_literal(b"pdf-manager-test".to_vec());
+    doc.trailer.set("ID", vec![file_id.clone(), file_id]);
+    let encrypt_id = doc.add_object(dictionary! {
+        "Filter" => "Standard",
+        "V" => 1,
+        "R" => 2,
+        "Length" => 40,
+        "P" => -4,
+        "O" => Object::string_literal(vec![0x4F; 32]),
+        "U" => Object::string_literal(vec![0; 32]),
+    });
+    doc.trailer.set("Encrypt", Object::Reference(encrypt_id));
+
+    let key = get_encryption_key(&doc, user_password, false).map_err(std::io::Error::other)?;
+    doc.get_dictionary_mut(encrypt_id)
+        .map_err(std::io::Error::other)?
+        .set("U", Object::string_literal(rc4(&key, &PAD_BYTES)));
+    for (&id, object) in doc.objects.iter_mut() {
+        if id == encrypt_id {
+            continue;
+        }
+        match object {
+            Object::Stream(stream) => {
+                let encrypted = decrypt_object(&key, id, &Object::Stream(stream.clone()))
+                    .map_err(std::io::Error::other)?;
+                stream.set_content(encrypted);
+            }
+            Object::Dictionary(dict) if id == info_id => {
+                for (_, value) in dict.iter_mut() {
+                    if let Ok(encrypted) = decrypt_object(&key, id, value) {
+                        *value = Object::string_literal(encrypted);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    doc.save(file_path)?;
+    Ok(())
+}
+
 #[cfg(test)]
 pub fn setup_unique_paths(name: &str) -> (std::path::PathBuf, std::path::PathBuf) {
     let test_dir = std::env::temp_dir().join(format!("pdf_test_{}_{}", name, uuid::Uuid::new_v4()));
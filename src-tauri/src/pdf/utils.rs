@@ -1,14 +1,24 @@
-use lopdf::{Document, Error as LopdfError, Object, ObjectId};
+use lopdf::encryption::DecryptionError;
+use lopdf::{Dictionary, Document, Error as LopdfError, Object, ObjectId};
 use std::collections::{HashMap, HashSet, VecDeque};
 
+/// Copy `ids_to_copy` and everything they reference from `source_doc` into
+/// `target_doc`, returning the old → new id mapping.
+///
+/// Objects are visited in a fixed order (seed ids sorted, then breadth-first in
+/// dictionary/array order), so identical inputs always receive the same new object
+/// numbers and the saved output is byte-for-byte reproducible.
 pub fn manual_deep_copy(
     source_doc: &Document,
     target_doc: &mut Document,
     ids_to_copy: &[ObjectId],
 ) -> Result<HashMap<ObjectId, ObjectId>, LopdfError> {
+    let mut seed_ids = ids_to_copy.to_vec();
+    seed_ids.sort();
+    seed_ids.dedup();
     let mut id_map: HashMap<ObjectId, ObjectId> = HashMap::new();
-    let mut queue: VecDeque<ObjectId> = ids_to_copy.iter().cloned().collect();
-    let mut processed: HashSet<ObjectId> = ids_to_copy.iter().cloned().collect();
+    let mut queue: VecDeque<ObjectId> = seed_ids.iter().cloned().collect();
+    let mut processed: HashSet<ObjectId> = seed_ids.iter().cloned().collect();
     let mut loop_count = 0;
     let max_loops = (source_doc.objects.len() + ids_to_copy.len()) * 2; // Safety limit
 
@@ -45,7 +55,10 @@ pub fn manual_deep_copy(
     }
 
     // --- Second Pass: Update references in copied objects ---
-    for (_old_id, new_id) in &id_map {
+    // Walk the copies in id order rather than HashMap order so the pass is stable
+    let mut new_ids: Vec<ObjectId> = id_map.values().cloned().collect();
+    new_ids.sort();
+    for new_id in &new_ids {
         // Iterate only over successfully copied objects
         match target_doc.get_object_mut(*new_id) {
             Ok(target_object) => {
@@ -142,3 +155,202 @@ pub fn update_references_recursive(
     }
     Ok(())
 }
+
+/// Resolve an inheritable page attribute (`/MediaBox`, `/Resources`, `/CropBox`, `/Rotate`)
+/// by walking up the `/Parent` chain until a node defines it. References are followed so
+/// callers always get the underlying object.
+pub fn get_inherited_attribute<'a>(
+    doc: &'a Document,
+    page_id: ObjectId,
+    key: &[u8],
+) -> Option<&'a Object> {
+    let mut current = Some(page_id);
+    let mut visited: HashSet<ObjectId> = HashSet::new();
+
+    while let Some(node_id) = current {
+        if !visited.insert(node_id) {
+            // Cyclic Parent chain in a malformed PDF
+            return None;
+        }
+        let node = doc.get_dictionary(node_id).ok()?;
+        if let Ok(value) = node.get(key) {
+            return match value {
+                Object::Reference(id) => doc.get_object(*id).ok(),
+                other => Some(other),
+            };
+        }
+        current = node.get(b"Parent").and_then(|p| p.as_reference()).ok();
+    }
+    None
+}
+
+/// Read a 4-number PDF rectangle (integers or reals) into `[x0, y0, x1, y1]`.
+pub fn rect_from_object(object: &Object) -> Option<[f64; 4]> {
+    let arr = object.as_array().ok()?;
+    if arr.len() != 4 {
+        return None;
+    }
+    let mut rect = [0.0; 4];
+    for (slot, value) in rect.iter_mut().zip(arr) {
+        *slot = value.as_float().ok()? as f64;
+    }
+    Some(rect)
+}
+
+/// Check that `path` names an existing file, with the messages every command reports
+/// for a missing or non-file input.
+pub fn validate_input_path(path: &str) -> Result<(), String> {
+    let input_path = std::path::Path::new(path);
+    if !input_path.exists() {
+        return Err(format!("Input file not found: {}", path));
+    }
+    if !input_path.is_file() {
+        return Err(format!("Input path is not a file: {}", path));
+    }
+    Ok(())
+}
+
+/// Validate `path` and load the document, decrypting it when it is encrypted. Without a
+/// `password` the empty user password is tried, which opens files that only restrict
+/// permissions. A wrong password is reported as "Incorrect password", separately from
+/// files that cannot be read at all.
+pub fn load_document(path: &str, password: Option<&str>) -> Result<Document, String> {
+    validate_input_path(path)?;
+
+    let mut doc =
+        Document::load(path).map_err(|e| format!("Failed to load PDF '{}': {}", path, e))?;
+    if doc.is_encrypted() {
+        doc.decrypt(password.unwrap_or("")).map_err(|e| match e {
+            LopdfError::Decryption(DecryptionError::IncorrectPassword) if password.is_some() => {
+                format!("Incorrect password for '{}'.", path)
+            }
+            LopdfError::Decryption(DecryptionError::IncorrectPassword) => {
+                format!("'{}' is password protected; a password is required.", path)
+            }
+            e => format!("Failed to decrypt PDF '{}': {}", path, e),
+        })?;
+    }
+    Ok(doc)
+}
+
+/// Fail with a descriptive error when `doc` has more than `max` pages, so pipelines
+/// with a page cap can stop before writing an oversized file.
+pub fn assert_max_pages(doc: &Document, max: u32) -> Result<(), String> {
+    assert_page_total(doc.get_pages().len(), max)
+}
+
+/// `assert_max_pages` for a page count known before the document is built.
+pub fn assert_page_total(count: usize, max: u32) -> Result<(), String> {
+    if count > max as usize {
+        return Err(format!("Result would have {} pages, limit is {}.", count, max));
+    }
+    Ok(())
+}
+
+/// Size of a file on disk, used to report bytes saved by size-reducing commands.
+pub fn file_size(path: &str) -> Result<u64, String> {
+    std::fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read size of '{}': {}", path, e))
+}
+
+/// Visible box of a page (CropBox, else MediaBox) and its rotation in degrees.
+pub fn page_geometry(doc: &Document, page_id: ObjectId) -> ([f64; 4], i64) {
+    let rect = get_inherited_attribute(doc, page_id, b"CropBox")
+        .or_else(|| get_inherited_attribute(doc, page_id, b"MediaBox"))
+        .and_then(rect_from_object)
+        .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+    let rotate = get_inherited_attribute(doc, page_id, b"Rotate")
+        .and_then(|r| r.as_i64().ok())
+        .unwrap_or(0)
+        .rem_euclid(360);
+    (rect, rotate)
+}
+
+/// Add `value` as `/name` in the page's `category` resource dictionary (Font,
+/// ExtGState, ...). Shared resource dictionaries are updated in place; inherited ones
+/// are copied onto the page first so its siblings are left alone.
+pub fn add_page_resource(
+    doc: &mut Document,
+    page_id: ObjectId,
+    category: &str,
+    name: &str,
+    value: Object,
+) -> Result<(), String> {
+    let resources_id = match doc
+        .get_dictionary(page_id)
+        .map_err(|e| format!("Failed to access page {:?}: {}", page_id, e))?
+        .get(b"Resources")
+    {
+        Ok(Object::Reference(id)) => Some(*id),
+        Ok(_) => None,
+        Err(_) => {
+            let inherited = get_inherited_attribute(doc, page_id, b"Resources")
+                .and_then(|r| doc.dereference(r).ok())
+                .and_then(|(_, r)| r.as_dict().ok())
+                .cloned()
+                .unwrap_or_default();
+            doc.get_dictionary_mut(page_id)
+                .map_err(|e| format!("Failed to access page {:?}: {}", page_id, e))?
+                .set("Resources", inherited);
+            None
+        }
+    };
+    let resources = match resources_id {
+        Some(id) => doc.get_dictionary_mut(id),
+        None => doc
+            .get_dictionary_mut(page_id)
+            .and_then(|page| page.get_mut(b"Resources"))
+            .and_then(|r| r.as_dict_mut()),
+    }
+    .map_err(|e| format!("Failed to access resources of page {:?}: {}", page_id, e))?;
+
+    let category_id = match resources.get(category.as_bytes()) {
+        Ok(Object::Reference(id)) => Some(*id),
+        Ok(Object::Dictionary(_)) => None,
+        _ => {
+            resources.set(category, Dictionary::new());
+            None
+        }
+    };
+    let entries = match category_id {
+        Some(id) => doc.get_dictionary_mut(id),
+        None => resources
+            .get_mut(category.as_bytes())
+            .and_then(|c| c.as_dict_mut()),
+    }
+    .map_err(|e| format!("Failed to access /{} resources: {}", category, e))?;
+    entries.set(name, value);
+    Ok(())
+}
+
+/// Rebuild a document as a single clean revision: everything reachable from the
+/// trailer's `/Root` and `/Info` is deep-copied into a fresh document, so orphaned and
+/// superseded objects (and leftover object or xref streams) are left behind. The copy
+/// is renumbered densely and its streams compressed. Used by the `clean` save option.
+pub fn rebuild_clean(doc: &Document) -> Result<Document, String> {
+    let trailer_ref = |key: &[u8]| doc.trailer.get(key).and_then(|o| o.as_reference()).ok();
+    let root_id = trailer_ref(b"Root").ok_or("Document has no catalog (/Root) to rebuild from.")?;
+    let info_id = trailer_ref(b"Info");
+
+    let mut clean = Document::with_version(doc.version.clone());
+    let seeds: Vec<ObjectId> = std::iter::once(root_id).chain(info_id).collect();
+    let id_map = manual_deep_copy(doc, &mut clean, &seeds)
+        .map_err(|e| format!("Failed to rebuild document: {}", e))?;
+
+    let new_root_id = id_map
+        .get(&root_id)
+        .ok_or("Document catalog (/Root) could not be copied.")?;
+    clean.trailer.set("Root", Object::Reference(*new_root_id));
+    if let Some(new_info_id) = info_id.and_then(|id| id_map.get(&id)) {
+        clean.trailer.set("Info", Object::Reference(*new_info_id));
+    }
+    if let Ok(file_id) = doc.trailer.get(b"ID") {
+        clean.trailer.set("ID", file_id.clone());
+    }
+
+    clean.prune_objects();
+    clean.renumber_objects();
+    clean.compress();
+    Ok(clean)
+}
@@ -0,0 +1,339 @@
+use crate::pdf::utils::{load_document, rebuild_clean};
+use lopdf::xref::XrefType;
+use lopdf::{Dictionary, Document, Object};
+use std::fs;
+use std::path::Path;
+
+const SUPPORTED_TARGETS: [&str; 5] = ["1.3", "1.4", "1.5", "1.6", "1.7"];
+
+/// Trailer entries that only make sense on a cross-reference stream dictionary.
+const XREF_STREAM_KEYS: [&[u8]; 8] = [
+    b"Type",
+    b"W",
+    b"Index",
+    b"Filter",
+    b"DecodeParms",
+    b"Length",
+    b"XRefStm",
+    b"Prev",
+];
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.trim().split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Drop object streams and cross-reference streams (PDF 1.5). The objects they held
+/// were already unpacked when the document was loaded, so they are written out as
+/// regular objects with a classic xref table. Returns which of the two were present.
+fn strip_object_streams(doc: &mut Document) -> (bool, bool) {
+    let has_type = |doc: &Document, type_name: &str| {
+        doc.objects
+            .values()
+            .any(|o| o.type_name().is_ok_and(|name| name == type_name))
+    };
+    let object_streams = has_type(doc, "ObjStm");
+    let xref_streams = has_type(doc, "XRef")
+        || matches!(
+            doc.reference_table.cross_reference_type,
+            XrefType::CrossReferenceStream
+        );
+
+    doc.objects.retain(|_, object| {
+        !object
+            .type_name()
+            .is_ok_and(|name| name == "ObjStm" || name == "XRef")
+    });
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    for key in XREF_STREAM_KEYS {
+        doc.trailer.remove(key);
+    }
+    (object_streams, xref_streams)
+}
+
+/// Remove soft masks, constant alpha, blend modes and transparency groups (PDF 1.4)
+/// from a dictionary and any dictionaries nested directly inside it. Content is drawn
+/// fully opaque afterwards.
+fn strip_transparency(dict: &mut Dictionary) -> bool {
+    let mut removed = dict.remove(b"SMask").is_some();
+    for key in [b"CA".as_slice(), b"ca".as_slice()] {
+        // /CA is also a caption string in widget appearance dictionaries
+        if dict.get(key).is_ok_and(|o| o.as_float().is_ok()) {
+            removed |= dict.remove(key).is_some();
+        }
+    }
+    if dict
+        .get(b"BM")
+        .and_then(|o| o.as_name())
+        .is_ok_and(|n| n != b"Normal" && n != b"Compatible")
+    {
+        removed |= dict.remove(b"BM").is_some();
+    }
+    let transparency_group = dict
+        .get(b"Group")
+        .and_then(|o| o.as_dict())
+        .and_then(|g| g.get(b"S"))
+        .and_then(|s| s.as_name())
+        .is_ok_and(|s| s == b"Transparency");
+    if transparency_group {
+        removed |= dict.remove(b"Group").is_some();
+    }
+    for (_, value) in dict.iter_mut() {
+        removed |= strip_nested_transparency(value);
+    }
+    removed
+}
+
+fn strip_nested_transparency(object: &mut Object) -> bool {
+    match object {
+        Object::Dictionary(dict) => strip_transparency(dict),
+        Object::Stream(stream) => strip_transparency(&mut stream.dict),
+        Object::Array(items) => items.iter_mut().fold(false, |removed, item| {
+            strip_nested_transparency(item) | removed
+        }),
+        _ => false,
+    }
+}
+
+/// Rewrite a document for an older PDF version and strip what that version cannot
+/// represent. Returns the features that were removed.
+///
+/// - Below 1.5: object streams and cross-reference streams are written out as regular
+///   objects with a classic xref table.
+/// - Below 1.4: transparency (soft masks, constant alpha, blend modes and transparency
+///   groups) is removed, so affected content renders opaque.
+///
+/// Targets 1.5 to 1.7 only change the declared version.
+#[tauri::command]
+pub fn downgrade_version(
+    path: &str,
+    target: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if !SUPPORTED_TARGETS.contains(&target) {
+        return Err(format!(
+            "Unsupported target version '{}'. Must be one of {}.",
+            target,
+            SUPPORTED_TARGETS.join(", ")
+        ));
+    }
+    let mut doc = load_document(path, None)?;
+
+    let target_version = parse_version(target).unwrap_or((1, 7));
+    let current_version = parse_version(&doc.version).unwrap_or((1, 7));
+    if target_version > current_version {
+        return Err(format!(
+            "Document '{}' is PDF {}, which is already older than the target {}.",
+            path, doc.version, target
+        ));
+    }
+
+    let mut removed = Vec::new();
+    if target_version < (1, 5) {
+        let (object_streams, xref_streams) = strip_object_streams(&mut doc);
+        if object_streams {
+            removed.push("object streams".to_string());
+        }
+        if xref_streams {
+            removed.push("cross-reference streams".to_string());
+        }
+    }
+    if target_version < (1, 4) {
+        let flattened = doc.objects.values_mut().fold(false, |removed, object| {
+            strip_nested_transparency(object) | removed
+        });
+        if flattened {
+            removed.push("transparency".to_string());
+        }
+    }
+
+    // The catalog /Version entry (PDF 1.4) overrides the header when it is newer
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"Version");
+    }
+    doc.version = target.to_string();
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save downgraded PDF to '{}': {}", output_path, e))?;
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::dictionary;
+
+    /// Write a one-page PDF 1.7 file whose catalog, page tree and page live in an object
+    /// stream, indexed by a cross-reference stream.
+    fn create_object_stream_pdf(path: &str) {
+        let packed = [
+            "<< /Type /Catalog /Pages 3 0 R >>",
+            "<< /Type /Pages /Kids [4 0 R] /Count 1 >>",
+            "<< /Type /Page /Parent 3 0 R /MediaBox [0 0 612 792] /Contents 5 0 R >>",
+        ];
+        let mut header = String::new();
+        let mut body = String::new();
+        for (index, object) in packed.iter().enumerate() {
+            header.push_str(&format!("{} {} ", index + 2, body.len()));
+            body.push_str(object);
+            body.push('\n');
+        }
+        let obj_stm = format!("{}{}", header, body);
+
+        let mut file = b"%PDF-1.7\n".to_vec();
+        let obj_stm_offset = file.len();
+        file.extend(
+            format!(
+                "1 0 obj\n<< /Type /ObjStm /N 3 /First {} /Length {} >>\nstream\n{}\nendstream\nendobj\n",
+                header.len(),
+                obj_stm.len(),
+                obj_stm
+            )
+            .into_bytes(),
+        );
+        let content = "0 0 m 100 100 l S";
+        let content_offset = file.len();
+        file.extend(
+            format!(
+                "5 0 obj\n<< /Length {} >>\nstream\n{}\nendstream\nendobj\n",
+                content.len(),
+                content
+            )
+            .into_bytes(),
+        );
+
+        let xref_offset = file.len();
+        let entry = |kind: u8, field: usize, extra: u16| {
+            let mut bytes = vec![kind];
+            bytes.extend((field as u32).to_be_bytes());
+            bytes.extend(extra.to_be_bytes());
+            bytes
+        };
+        let mut entries = entry(0, 0, 65535);
+        entries.extend(entry(1, obj_stm_offset, 0));
+        entries.extend(entry(2, 1, 0));
+        entries.extend(entry(2, 1, 1));
+        entries.extend(entry(2, 1, 2));
+        entries.extend(entry(1, content_offset, 0));
+        entries.extend(entry(1, xref_offset, 0));
+        file.extend(
+            format!(
+                "6 0 obj\n<< /Type /XRef /Size 7 /W [1 4 2] /Root 2 0 R /Length {} >>\nstream\n",
+                entries.len()
+            )
+            .into_bytes(),
+        );
+        file.extend(entries);
+        file.extend(format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF", xref_offset).into_bytes());
+
+        fs::write(path, file).unwrap();
+    }
+
+    #[test]
+    fn test_downgrade_version_removes_object_streams() {
+        let (test_dir, output_dir) = setup_unique_paths("downgrade_objstm");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("legacy.pdf");
+        create_object_stream_pdf(input.to_str().unwrap());
+        let original = Document::load(&input).unwrap();
+        assert!(original
+            .objects
+            .values()
+            .any(|o| o.type_name().is_ok_and(|t| t == "ObjStm")));
+
+        let removed = downgrade_version(
+            input.to_str().unwrap(),
+            "1.4",
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            removed,
+            vec![
+                "object streams".to_string(),
+                "cross-reference streams".to_string()
+            ]
+        );
+
+        let raw = fs::read(&output).unwrap();
+        assert!(raw.starts_with(b"%PDF-1.4"));
+        assert!(!raw.windows(7).any(|w| w == b"/ObjStm"));
+        assert!(!raw.windows(5).any(|w| w == b"/XRef"));
+        assert!(raw.windows(5).any(|w| w == b"xref\n"));
+
+        let doc = Document::load(&output).unwrap();
+        assert_eq!(doc.get_pages().len(), 1);
+        assert!(matches!(
+            doc.reference_table.cross_reference_type,
+            XrefType::CrossReferenceTable
+        ));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_downgrade_version_flattens_transparency_for_1_3() {
+        let (test_dir, output_dir) = setup_unique_paths("downgrade_transparency");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("legacy.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Alpha").unwrap();
+        let mut doc = Document::load(&input).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let page = doc.get_dictionary_mut(page_id).unwrap();
+        page.set("Group", dictionary! { "S" => "Transparency" });
+        page.set(
+            "Resources",
+            dictionary! {
+                "ExtGState" => dictionary! {
+                    "GS1" => dictionary! { "Type" => "ExtGState", "ca" => 0.5, "BM" => "Multiply" },
+                },
+            },
+        );
+        doc.save(&input).unwrap();
+
+        let removed = downgrade_version(
+            input.to_str().unwrap(),
+            "1.3",
+            output.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(removed.contains(&"transparency".to_string()));
+
+        let doc = Document::load(&output).unwrap();
+        assert_eq!(doc.version, "1.3");
+        let page = doc.get_dictionary(doc.get_pages()[&1]).unwrap();
+        assert!(page.get(b"Group").is_err());
+        let gs = page
+            .get(b"Resources")
+            .and_then(|r| r.as_dict())
+            .and_then(|r| r.get(b"ExtGState"))
+            .and_then(|e| e.as_dict())
+            .and_then(|e| e.get(b"GS1"))
+            .and_then(|g| g.as_dict())
+            .unwrap();
+        assert!(gs.get(b"ca").is_err() && gs.get(b"BM").is_err());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
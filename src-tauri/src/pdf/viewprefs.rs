@@ -0,0 +1,155 @@
+use crate::pdf::extractor_text::detect_text_direction;
+use crate::pdf::utils::{load_document, rebuild_clean};
+use lopdf::{Dictionary, Object};
+use std::fs;
+use std::path::Path;
+
+#[derive(serde::Serialize)]
+pub struct ViewSettings {
+    pub page_mode: Option<String>,
+    pub page_layout: Option<String>,
+    pub has_open_action: bool,
+}
+
+/// Report how the document asks to be opened: `/PageMode` (e.g. `UseOutlines`,
+/// `FullScreen`), `/PageLayout` (e.g. `TwoColumnLeft`) and whether an `/OpenAction`
+/// is set. Missing entries mean the viewer's defaults apply.
+#[tauri::command]
+pub fn get_view_settings(path: &str) -> Result<ViewSettings, String> {
+    let doc = load_document(path, None)?;
+    let catalog = doc
+        .catalog()
+        .map_err(|e| format!("Failed to read catalog of '{}': {}", path, e))?;
+
+    let name_entry = |key: &[u8]| {
+        catalog
+            .get_deref(key, &doc)
+            .and_then(|o| o.as_name())
+            .map(|n| String::from_utf8_lossy(n).to_string())
+            .ok()
+    };
+
+    Ok(ViewSettings {
+        page_mode: name_entry(b"PageMode"),
+        page_layout: name_entry(b"PageLayout"),
+        has_open_action: catalog.has(b"OpenAction"),
+    })
+}
+
+/// Detect the document's predominant text direction (see `detect_text_direction`) and
+/// record it as `/ViewerPreferences /Direction` (`L2R` or `R2L`), so viewers lay out
+/// facing pages and scroll in reading order. Returns the detected "ltr" or "rtl".
+#[tauri::command]
+pub fn set_text_direction(
+    path: &str,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<String, String> {
+    let direction = detect_text_direction(path)?;
+    let mut doc = load_document(path, None)?;
+    let value = if direction == "rtl" { "R2L" } else { "L2R" };
+
+    let prefs_ref = doc
+        .catalog()
+        .and_then(|c| c.get(b"ViewerPreferences"))
+        .and_then(|p| p.as_reference())
+        .ok();
+    match prefs_ref {
+        Some(prefs_id) => doc
+            .get_dictionary_mut(prefs_id)
+            .map_err(|e| format!("Failed to read viewer preferences: {}", e))?
+            .set("Direction", value),
+        None => {
+            let catalog = doc
+                .catalog_mut()
+                .map_err(|e| format!("Failed to read catalog of '{}': {}", path, e))?;
+            let mut prefs = catalog
+                .get(b"ViewerPreferences")
+                .and_then(|p| p.as_dict())
+                .cloned()
+                .unwrap_or_else(|_| Dictionary::new());
+            prefs.set("Direction", value);
+            catalog.set("ViewerPreferences", Object::Dictionary(prefs));
+        }
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save PDF to '{}': {}", output_path, e))?;
+
+    Ok(direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Document;
+
+    #[test]
+    fn test_get_view_settings_reports_layout() {
+        let (test_dir, output_dir) = setup_unique_paths("view_settings");
+        let input = test_dir.join("input.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 2, "View").unwrap();
+
+        let defaults = get_view_settings(input.to_str().unwrap()).unwrap();
+        assert!(defaults.page_layout.is_none());
+        assert!(defaults.page_mode.is_none());
+        assert!(!defaults.has_open_action);
+
+        let mut doc = Document::load(&input).unwrap();
+        let first_page = doc.get_pages()[&1];
+        let catalog_id = doc.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let catalog = doc.get_dictionary_mut(catalog_id).unwrap();
+        catalog.set("PageLayout", "TwoColumnLeft");
+        catalog.set("PageMode", "UseOutlines");
+        catalog.set(
+            "OpenAction",
+            vec![Object::Reference(first_page), "Fit".into()],
+        );
+        doc.save(&input).unwrap();
+
+        let settings = get_view_settings(input.to_str().unwrap()).unwrap();
+        assert_eq!(settings.page_layout.as_deref(), Some("TwoColumnLeft"));
+        assert_eq!(settings.page_mode.as_deref(), Some("UseOutlines"));
+        assert!(settings.has_open_action);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_set_text_direction_writes_viewer_preference() {
+        let (test_dir, output_dir) = setup_unique_paths("set_text_direction");
+        let input = test_dir.join("input.pdf");
+        let output = output_dir.join("directed.pdf");
+        create_minimal_pdf(input.to_str().unwrap(), 1, "Direction").unwrap();
+
+        let direction = set_text_direction(input.to_str().unwrap(), output.to_str().unwrap(), None);
+        assert_eq!(direction.unwrap(), "ltr");
+
+        let doc = Document::load(&output).unwrap();
+        let prefs = doc
+            .catalog()
+            .and_then(|c| c.get(b"ViewerPreferences"))
+            .and_then(|p| p.as_dict())
+            .unwrap();
+        assert_eq!(prefs.get(b"Direction").unwrap().as_name().unwrap(), b"L2R");
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
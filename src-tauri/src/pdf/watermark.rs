@@ -1,10 +1,35 @@
-use lopdf::{dictionary, Document, Object, content::Content};
+use crate::pdf::utils::{load_document, rebuild_clean};
+use lopdf::{dictionary, Object, ObjectId, content::Content};
+use std::collections::BTreeSet;
 
+/// Stamp `text` diagonally on the given pages (1-based, duplicates ignored); an empty
+/// `pages` list stamps every page.
 #[tauri::command]
-pub fn add_watermark(path: &str, text: &str, opacity: f32, color: [f32; 3], output_path: &str) -> Result<(), String> {
-    let mut doc = Document::load(path).map_err(|e| e.to_string())?;
-    
-    for (_, page_id) in doc.get_pages() {
+pub fn add_watermark(path: &str, text: &str, opacity: f32, color: [f32; 3], pages: Vec<u32>, output_path: &str, clean: Option<bool>) -> Result<(), String> {
+    let mut doc = load_document(path, None)?;
+
+    let page_ids = doc.get_pages();
+    let targets: Vec<ObjectId> = if pages.is_empty() {
+        page_ids.values().cloned().collect()
+    } else {
+        // A page listed twice is still stamped once
+        let pages: BTreeSet<u32> = pages.into_iter().collect();
+        pages
+            .iter()
+            .map(|p| {
+                page_ids.get(p).cloned().ok_or_else(|| {
+                    format!(
+                        "Page number {} not found in document '{}' (which has {} pages).",
+                        p,
+                        path,
+                        page_ids.len()
+                    )
+                })
+            })
+            .collect::<Result<Vec<ObjectId>, String>>()?
+    };
+
+    for page_id in targets {
         // ... (rest of the logic remains the same until content creation)
         let gs_name = "WatermarkGS";
         let font_name = "WatermarkFont";
@@ -68,6 +93,10 @@ pub fn add_watermark(path: &str, text: &str, opacity: f32, color: [f32; 3], outp
             .map_err(|e| format!("Failed to add watermark content: {}", e))?;
     }
 
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
     doc.save(output_path).map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -76,6 +105,7 @@ pub fn add_watermark(path: &str, text: &str, opacity: f32, color: [f32; 3], outp
 mod tests {
     use super::*;
     use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Document;
 
     #[test]
     fn test_add_watermark_success() {
@@ -89,7 +119,9 @@ mod tests {
             "CONFIDENTIAL",
             0.5,
             [1.0, 0.0, 0.0],
-            output_path.to_str().unwrap()
+            vec![],
+            output_path.to_str().unwrap(),
+            None
         );
         
         assert!(result.is_ok());
@@ -98,4 +130,49 @@ mod tests {
         
         teardown_unique_paths(&test_dir, &output_dir);
     }
+
+    #[test]
+    fn test_add_watermark_selected_pages_only() {
+        let (test_dir, output_dir) = setup_unique_paths("watermark_selected_pages");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 3, "Selected").unwrap();
+
+        let result = add_watermark(
+            input_path.to_str().unwrap(),
+            "DRAFT",
+            0.4,
+            [0.5, 0.5, 0.5],
+            vec![2, 2],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_ok(), "add_watermark failed: {:?}", result.err());
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages = output_doc.get_pages();
+        let stream_count = |page: u32| {
+            let page_dict = output_doc.get_dictionary(pages[&page]).unwrap();
+            match page_dict.get(b"Contents").unwrap() {
+                Object::Array(streams) => streams.len(),
+                _ => 1,
+            }
+        };
+        assert_eq!(stream_count(1), 1);
+        assert_eq!(stream_count(2), 2);
+        assert_eq!(stream_count(3), 1);
+
+        let invalid = add_watermark(
+            input_path.to_str().unwrap(),
+            "DRAFT",
+            0.4,
+            [0.5, 0.5, 0.5],
+            vec![4],
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(invalid.unwrap_err().contains("Page number 4 not found"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
 }
@@ -0,0 +1,294 @@
+use crate::pdf::utils::{
+    add_page_resource, get_inherited_attribute, load_document, rebuild_clean, rect_from_object,
+};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Object, Stream};
+use std::fs;
+use std::path::Path;
+
+const TEXT_WATERMARK_FONT: &str = "WatermarkHelv";
+const TEXT_WATERMARK_GS: &str = "WatermarkAlpha";
+const TEXT_WATERMARK_MAX_SIZE: f64 = 72.0;
+/// Average Helvetica glyph width, in em, used to estimate the text width.
+const HELVETICA_AVERAGE_CHAR_WIDTH: f64 = 0.5;
+
+/// Draw `text` diagonally across the center of every page in Helvetica, at the given
+/// opacity (0 to 1). The existing content is wrapped in `q`/`Q` so its graphics state
+/// cannot leak into the watermark. The font size is chosen to fit the page diagonal.
+///
+/// The page's own content streams are left untouched: a shared `q` stream goes in
+/// front of them and a per-page stream holding `Q` and the watermark after them, so
+/// pages without /Contents and streams shared between pages are handled too.
+#[tauri::command]
+pub fn add_text_watermark(
+    path: &str,
+    text: &str,
+    opacity: f32,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("Watermark text cannot be empty.".to_string());
+    }
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(format!(
+            "Invalid opacity {}. Opacity must be between 0 and 1.",
+            opacity
+        ));
+    }
+    let mut doc = load_document(path, None)?;
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let gs_id = doc.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => opacity,
+        "CA" => opacity,
+    });
+    let save_id = doc.add_object(Stream::new(dictionary! {}, b"q\n".to_vec()));
+    let encoded: Vec<u8> = text
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect();
+
+    for (page_num, page_id) in doc.get_pages() {
+        add_page_resource(
+            &mut doc,
+            page_id,
+            "Font",
+            TEXT_WATERMARK_FONT,
+            Object::Reference(font_id),
+        )?;
+        add_page_resource(
+            &mut doc,
+            page_id,
+            "ExtGState",
+            TEXT_WATERMARK_GS,
+            Object::Reference(gs_id),
+        )?;
+
+        let [x0, y0, x1, y1] = get_inherited_attribute(&doc, page_id, b"MediaBox")
+            .and_then(rect_from_object)
+            .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        let (width, height) = ((x1 - x0).abs(), (y1 - y0).abs());
+        let (center_x, center_y) = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+
+        // Fit the text to 80% of the page diagonal and rotate it onto that diagonal
+        let diagonal = width.hypot(height);
+        let text_em = encoded.len() as f64 * HELVETICA_AVERAGE_CHAR_WIDTH;
+        let size = (0.8 * diagonal / text_em).min(TEXT_WATERMARK_MAX_SIZE);
+        let (cos, sin) = (width / diagonal, height / diagonal);
+        // Start the baseline so the middle of the text lands on the page center
+        let (half_width, half_height) = (text_em * size / 2.0, size * 0.35);
+        let origin_x = center_x - half_width * cos + half_height * sin;
+        let origin_y = center_y - half_width * sin - half_height * cos;
+
+        let watermark = Content {
+            operations: vec![
+                Operation::new("q", vec![]),
+                Operation::new("gs", vec![Object::Name(TEXT_WATERMARK_GS.into())]),
+                Operation::new("BT", vec![]),
+                Operation::new(
+                    "Tf",
+                    vec![Object::Name(TEXT_WATERMARK_FONT.into()), size.into()],
+                ),
+                Operation::new(
+                    "Tm",
+                    vec![
+                        cos.into(),
+                        sin.into(),
+                        (-sin).into(),
+                        cos.into(),
+                        origin_x.into(),
+                        origin_y.into(),
+                    ],
+                ),
+                Operation::new("Tj", vec![Object::string_literal(encoded.clone())]),
+                Operation::new("ET", vec![]),
+                Operation::new("Q", vec![]),
+            ],
+        }
+        .encode()
+        .map_err(|e| format!("Failed to encode watermark for page {}: {}", page_num, e))?;
+
+        // Leading newline so readers that join the streams do not glue Q to the
+        // last token of the page content
+        let mut tail = b"\nQ\n".to_vec();
+        tail.extend_from_slice(&watermark);
+        let tail_id = doc.add_object(Stream::new(dictionary! {}, tail));
+
+        let mut contents = vec![Object::Reference(save_id)];
+        contents.extend(
+            doc.get_page_contents(page_id)
+                .into_iter()
+                .map(Object::Reference),
+        );
+        contents.push(Object::Reference(tail_id));
+        doc.get_dictionary_mut(page_id)
+            .map_err(|e| format!("Failed to update page {}: {}", page_num, e))?
+            .set("Contents", Object::Array(contents));
+    }
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    if clean.unwrap_or(false) {
+        doc = rebuild_clean(&doc)?;
+    }
+
+    doc.save(output_path)
+        .map_err(|e| format!("Failed to save watermarked PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+    use lopdf::Document;
+
+    #[test]
+    fn test_add_text_watermark_wraps_existing_content() {
+        let (test_dir, output_dir) = setup_unique_paths("text_watermark");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 3, "Stamped").unwrap();
+
+        let result = add_text_watermark(
+            input_path.to_str().unwrap(),
+            "CONFIDENTIAL (copy)",
+            0.3,
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "add_text_watermark failed: {:?}",
+            result.err()
+        );
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages = output_doc.get_pages();
+        assert_eq!(pages.len(), 3);
+        let content = output_doc.get_and_decode_page_content(pages[&2]).unwrap();
+        let operators: Vec<&str> = content
+            .operations
+            .iter()
+            .map(|op| op.operator.as_str())
+            .collect();
+        assert_eq!(operators.first(), Some(&"q"));
+        let restore = operators.iter().position(|op| *op == "Q").unwrap();
+        assert_eq!(operators[restore + 1..restore + 3], ["q", "gs"]);
+        let text = output_doc.extract_text(&[2]).unwrap();
+        assert!(text.contains("Stamped-Page 2") && text.contains("CONFIDENTIAL (copy)"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_add_text_watermark_page_without_contents() {
+        let (test_dir, output_dir) = setup_unique_paths("text_watermark_no_contents");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Empty").unwrap();
+        let mut doc = Document::load(&input_path).unwrap();
+        let first_page = doc.get_pages()[&1];
+        doc.get_dictionary_mut(first_page)
+            .unwrap()
+            .remove(b"Contents");
+        doc.save(&input_path).unwrap();
+
+        add_text_watermark(
+            input_path.to_str().unwrap(),
+            "DRAFT",
+            0.5,
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages = output_doc.get_pages();
+        assert_eq!(output_doc.get_page_contents(pages[&1]).len(), 2);
+        assert_eq!(output_doc.extract_text(&[1]).unwrap().trim(), "DRAFT");
+        let text = output_doc.extract_text(&[2]).unwrap();
+        assert!(text.contains("Empty-Page 2") && text.contains("DRAFT"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_add_text_watermark_leaves_shared_stream_untouched() {
+        let (test_dir, output_dir) = setup_unique_paths("text_watermark_shared");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("output.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Shared").unwrap();
+        let mut doc = Document::load(&input_path).unwrap();
+        let pages = doc.get_pages();
+        let shared = doc.get_page_contents(pages[&1])[0];
+        doc.get_dictionary_mut(pages[&2])
+            .unwrap()
+            .set("Contents", Object::Reference(shared));
+        doc.save(&input_path).unwrap();
+        let original = doc
+            .get_object(shared)
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .content
+            .clone();
+
+        add_text_watermark(
+            input_path.to_str().unwrap(),
+            "DRAFT",
+            0.5,
+            output_path.to_str().unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let output_doc = Document::load(&output_path).unwrap();
+        let pages = output_doc.get_pages();
+        for page_id in pages.values() {
+            let streams = output_doc.get_page_contents(*page_id);
+            assert_eq!(streams.len(), 3);
+            assert_eq!(streams[1], shared);
+            let content = output_doc.get_and_decode_page_content(*page_id).unwrap();
+            let count = |operator: &str| {
+                content
+                    .operations
+                    .iter()
+                    .filter(|op| op.operator == operator)
+                    .count()
+            };
+            // One wrap and one watermark per page, however many pages share the stream
+            assert_eq!((count("q"), count("Q"), count("Tj")), (2, 2, 2));
+        }
+        let stream_content = |doc: &Document| {
+            doc.get_object(shared)
+                .unwrap()
+                .as_stream()
+                .unwrap()
+                .content
+                .clone()
+        };
+        assert_eq!(stream_content(&output_doc), original);
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::pdf::utils::{
+    find_references_recursive, get_inherited_attribute, load_document, manual_deep_copy,
+    rebuild_clean, rect_from_object, update_references_recursive,
+};
+use lopdf::{dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Copy a page into `target` as a Form XObject whose BBox is the page's MediaBox,
+/// deep-copying everything its resources point at. Returns the form and its BBox.
+pub(crate) fn copy_page_as_form(
+    doc: &Document,
+    page_id: ObjectId,
+    target: &mut Document,
+) -> Result<(ObjectId, [f64; 4]), String> {
+    let bbox = get_inherited_attribute(doc, page_id, b"MediaBox")
+        .and_then(rect_from_object)
+        .unwrap_or([0.0, 0.0, 612.0, 792.0]);
+    let content = doc
+        .get_page_content(page_id)
+        .map_err(|e| format!("Failed to read page content: {}", e))?;
+    let mut resources = get_inherited_attribute(doc, page_id, b"Resources")
+        .cloned()
+        .unwrap_or_else(|| Object::Dictionary(lopdf::Dictionary::new()));
+
+    // --- Copy everything the resources point at into the target document ---
+    let mut queue: VecDeque<ObjectId> = VecDeque::new();
+    let mut seen: HashSet<ObjectId> = HashSet::new();
+    find_references_recursive(&resources, &mut queue, &mut seen)
+        .map_err(|e| format!("Failed to collect page resources: {}", e))?;
+    let resource_ids: Vec<ObjectId> = queue.into_iter().collect();
+    let id_map = manual_deep_copy(doc, target, &resource_ids)
+        .map_err(|e| format!("Failed to copy page resources: {}", e))?;
+    update_references_recursive(&mut resources, &id_map)
+        .map_err(|e| format!("Failed to remap page resources: {}", e))?;
+
+    let form = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "FormType" => 1,
+            "BBox" => Object::Array(bbox.iter().map(|v| Object::Real(*v as f32)).collect()),
+            "Resources" => resources,
+        },
+        content,
+    );
+    Ok((target.add_object(form), bbox))
+}
+
+// --- export_as_xobject: wrap a page as a reusable Form XObject ---
+
+#[tauri::command]
+pub fn export_as_xobject(
+    path: &str,
+    page: u32,
+    output_path: &str,
+    clean: Option<bool>,
+) -> Result<(), String> {
+    if page == 0 {
+        return Err("Page number must be 1-based (greater than 0).".to_string());
+    }
+    let doc = load_document(path, None)?;
+
+    let pages = doc.get_pages();
+    let page_id = *pages.get(&page).ok_or_else(|| {
+        format!(
+            "Page number {} not found in document '{}' (which has {} pages).",
+            page,
+            path,
+            pages.len()
+        )
+    })?;
+
+    if let Some(parent_dir) = Path::new(output_path).parent() {
+        if !parent_dir.exists() {
+            fs::create_dir_all(parent_dir).map_err(|e| {
+                format!(
+                    "Failed to create output directory '{}': {}",
+                    parent_dir.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    let mut new_doc = Document::with_version(doc.version.clone());
+    let (form_id, bbox) = copy_page_as_form(&doc, page_id, &mut new_doc)
+        .map_err(|e| format!("Page {}: {}", page, e))?;
+    let bbox_obj = Object::Array(bbox.iter().map(|v| Object::Real(*v as f32)).collect());
+
+    // --- Minimal single-page host so the stamp can be previewed ---
+    let pages_id = new_doc.new_object_id();
+    let host_content = new_doc.add_object(Stream::new(dictionary! {}, b"q /Fx1 Do Q".to_vec()));
+    let host_page = new_doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => Object::Reference(pages_id),
+        "MediaBox" => bbox_obj,
+        "Contents" => Object::Reference(host_content),
+        "Resources" => dictionary! {
+            "XObject" => dictionary! { "Fx1" => Object::Reference(form_id) },
+        },
+    });
+    new_doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => Object::Array(vec![Object::Reference(host_page)]),
+            "Count" => Object::Integer(1),
+        }),
+    );
+    let catalog_id = new_doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => Object::Reference(pages_id),
+    });
+    new_doc.trailer.set("Root", Object::Reference(catalog_id));
+
+    new_doc.compress();
+    if clean.unwrap_or(false) {
+        new_doc = rebuild_clean(&new_doc)?;
+    }
+
+    new_doc
+        .save(output_path)
+        .map_err(|e| format!("Failed to save XObject PDF to '{}': {}", output_path, e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::test_utils::{create_minimal_pdf, setup_unique_paths, teardown_unique_paths};
+
+    #[test]
+    fn test_export_as_xobject_success() {
+        let (test_dir, output_dir) = setup_unique_paths("xobject_export");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("stamp.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 2, "Stamp").unwrap();
+
+        let result = export_as_xobject(
+            input_path.to_str().unwrap(),
+            2,
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "export_as_xobject failed: {:?}",
+            result.err()
+        );
+
+        let doc = Document::load(&output_path).unwrap();
+        let form = doc
+            .objects
+            .values()
+            .filter_map(|o| o.as_stream().ok())
+            .find(|s| {
+                s.dict
+                    .get(b"Subtype")
+                    .and_then(|o| o.as_name())
+                    .map(|n| n == b"Form")
+                    .unwrap_or(false)
+            })
+            .expect("Output should contain a Form XObject");
+        let bbox = rect_from_object(form.dict.get(b"BBox").unwrap()).unwrap();
+        assert_eq!(bbox, [0.0, 0.0, 612.0, 792.0]);
+        assert!(form
+            .dict
+            .get(b"Resources")
+            .unwrap()
+            .as_dict()
+            .unwrap()
+            .has(b"Font"));
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+
+    #[test]
+    fn test_export_as_xobject_invalid_page() {
+        let (test_dir, output_dir) = setup_unique_paths("xobject_invalid_page");
+        let input_path = test_dir.join("input.pdf");
+        let output_path = output_dir.join("stamp.pdf");
+        create_minimal_pdf(input_path.to_str().unwrap(), 1, "Stamp").unwrap();
+
+        let result = export_as_xobject(
+            input_path.to_str().unwrap(),
+            3,
+            output_path.to_str().unwrap(),
+            None,
+        );
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Page number 3 not found"));
+        assert!(!output_path.exists());
+
+        teardown_unique_paths(&test_dir, &output_dir);
+    }
+}